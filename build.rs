@@ -17,11 +17,12 @@ impl Drop for DocValidationGuard {
     }
 }
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Existing protobuf compilation
-    let out_dir = std::env::var("OUT_DIR").unwrap();
-    tonic_build::configure()
-        .file_descriptor_set_path(format!("{}/user_stats.bin", out_dir))
-        .compile(&["proto/user_stats/user_stats.proto"], &["proto"])?;
+    // Protobuf compilation is only needed for the gRPC service
+    #[cfg(feature = "grpc")]
+    compile_protos()?;
+
+    emit_build_info();
+    emit_schema_version();
 
     // Documentation validation during build - only if explicitly enabled
     if std::env::var("ENABLE_DOC_VALIDATION").is_ok() {
@@ -31,6 +32,69 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Generate the gRPC service code and reflection descriptor set
+#[cfg(feature = "grpc")]
+fn compile_protos() -> Result<(), Box<dyn std::error::Error>> {
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    tonic_build::configure()
+        .file_descriptor_set_path(format!("{}/user_stats.bin", out_dir))
+        .compile(&["proto/user_stats/user_stats.proto"], &["proto"])?;
+    Ok(())
+}
+
+/// Export build metadata consumed by `GET /health/info` via `env!`.
+///
+/// Values fall back to "unknown" so builds outside a git checkout (e.g. the
+/// Docker context) still succeed.
+fn emit_build_info() {
+    let git_sha = command_output("git", &["rev-parse", "--short", "HEAD"]);
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".into());
+    let rust_version = command_output(&rustc, &["--version"]);
+
+    println!("cargo:rustc-env=BUILD_GIT_SHA={}", git_sha);
+    println!("cargo:rustc-env=BUILD_TIME={}", chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
+    println!("cargo:rustc-env=BUILD_RUST_VERSION={}", rust_version);
+
+    // Refresh the SHA when HEAD moves
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    let head_ref = std::fs::read_to_string(".git/HEAD").unwrap_or_default();
+    if let Some(reference) = head_ref.trim().strip_prefix("ref: ") {
+        println!("cargo:rerun-if-changed=.git/{}", reference);
+    }
+}
+
+/// Export the newest migration version this build ships, compared against
+/// `_sqlx_migrations` by `GET /health/ready`.
+///
+/// sqlx takes a migration's version from its file name up to the first `_`.
+fn emit_schema_version() {
+    println!("cargo:rerun-if-changed=migrations");
+    let version = std::fs::read_dir("migrations")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            name.strip_suffix(".sql")?.split('_').next()?.parse::<i64>().ok()
+        })
+        .max()
+        .unwrap_or(0);
+    println!("cargo:rustc-env=BUILD_SCHEMA_VERSION={}", version);
+}
+
+/// Run a command and return its trimmed stdout, or "unknown" on any failure
+fn command_output(program: &str, args: &[&str]) -> String {
+    Command::new(program)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
 /// Recursively walks through a directory and emits `cargo:rerun-if-changed` directives
 /// for all regular files found. Skips hidden files and directories.
 fn walk_and_emit_rerun_directives<P: AsRef<std::path::Path>>(
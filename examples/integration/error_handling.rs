@@ -133,10 +133,16 @@ impl ErrorHandler {
                 KitchenApiError::NotFoundError("Requested resource not found".to_string())
             }
             StatusCode::TOO_MANY_REQUESTS => {
-                let retry_after = response.headers()
+                let header_retry_after = response.headers()
                     .get("retry-after")
                     .and_then(|v| v.to_str().ok())
                     .and_then(|s| s.parse().ok());
+                // The body carries the same delay as `retry_after_secs`
+                let retry_after = match header_retry_after {
+                    Some(seconds) => Some(seconds),
+                    None => response.json::<Value>().await.ok()
+                        .and_then(|body| body.get("retry_after_secs").and_then(Value::as_u64)),
+                };
                 
                 KitchenApiError::RateLimitError(
                     "Rate limit exceeded".to_string(),
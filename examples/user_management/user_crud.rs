@@ -238,7 +238,7 @@ async fn update_user(config: &ApiConfig, token: &str, user_id: &str, new_name: &
     let response = config.client
         .put(&format!("{}/api/v1/users/{}", config.base_url, user_id))
         .header("Authorization", format!("Bearer {}", token))
-        .json(&json!(new_name))
+        .json(&json!({ "full_name": new_name }))
         .send()
         .await?;
     
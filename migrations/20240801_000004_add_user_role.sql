@@ -0,0 +1,3 @@
+-- Migration: Add a role column so administrators can manage other accounts
+ALTER TABLE users ADD COLUMN role TEXT NOT NULL DEFAULT 'user'
+    CHECK (role IN ('user', 'admin'));
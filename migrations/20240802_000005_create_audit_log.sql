@@ -0,0 +1,13 @@
+-- Migration: Create audit_log table for security-relevant events
+-- user_id has no foreign key so history survives account deletion
+CREATE TABLE audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    user_id UUID,
+    action TEXT NOT NULL,
+    details JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_audit_log_action_created_at ON audit_log(action, created_at);
+CREATE INDEX idx_audit_log_user_id_created_at ON audit_log(user_id, created_at);
+CREATE INDEX idx_audit_log_created_at ON audit_log(created_at);
@@ -0,0 +1,3 @@
+-- Migration: Track when each refresh token was last used so abandoned
+-- sessions can expire after a period of inactivity
+ALTER TABLE refresh_tokens ADD COLUMN last_used_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
@@ -0,0 +1,4 @@
+-- Migration: Allow the kitchen staff roles; values must match core::role::Role
+ALTER TABLE users DROP CONSTRAINT users_role_check;
+ALTER TABLE users ADD CONSTRAINT users_role_check
+    CHECK (role IN ('user', 'admin', 'manager', 'line_cook'));
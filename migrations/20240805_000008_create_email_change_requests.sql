@@ -0,0 +1,10 @@
+-- Migration: Pending email changes. The new address is only copied to users.email
+-- once its verification token is redeemed; one pending change per user. Only a
+-- SHA-256 hash of the token is stored, so a leaked table can't confirm changes.
+CREATE TABLE email_change_requests (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    new_email TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
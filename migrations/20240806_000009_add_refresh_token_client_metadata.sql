@@ -0,0 +1,4 @@
+-- Migration: Record the client each refresh token was issued to so sessions
+-- can be told apart when reviewing them
+ALTER TABLE refresh_tokens ADD COLUMN ip_address TEXT;
+ALTER TABLE refresh_tokens ADD COLUMN user_agent TEXT;
@@ -0,0 +1,15 @@
+-- Migration: Background jobs started by `Prefer: respond-async` requests.
+-- Clients poll a job by id until it reaches `completed` or `failed`
+CREATE TABLE jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'running', 'completed', 'failed')),
+    requested_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    result JSONB,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_jobs_requested_by ON jobs(requested_by);
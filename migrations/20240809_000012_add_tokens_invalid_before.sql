@@ -0,0 +1,3 @@
+-- Migration: Access tokens issued at or before this instant are rejected,
+-- letting a user end every session at once. NULL means no cutoff
+ALTER TABLE users ADD COLUMN tokens_invalid_before TIMESTAMPTZ;
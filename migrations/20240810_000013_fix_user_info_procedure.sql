@@ -0,0 +1,49 @@
+-- Migration: Qualify refresh_tokens columns in get_user_info_with_stats
+-- The OUT parameters user_id and created_at clash with the unqualified
+-- column names in the token subquery, so every call failed as ambiguous.
+
+CREATE OR REPLACE FUNCTION get_user_info_with_stats(p_user_id UUID)
+RETURNS TABLE (
+    user_id UUID,
+    email TEXT,
+    full_name TEXT,
+    preferences JSONB,
+    created_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ,
+    refresh_token_count BIGINT,
+    last_login TIMESTAMPTZ
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+BEGIN
+    RAISE NOTICE 'get_user_info_with_stats called for user_id: %', p_user_id;
+
+    RETURN QUERY
+    SELECT
+        u.id,
+        u.email,
+        u.full_name,
+        u.preferences,
+        u.created_at,
+        u.updated_at,
+        COALESCE(rt.token_count, 0) AS refresh_token_count,
+        rt.last_token_created AS last_login
+    FROM users u
+    LEFT JOIN (
+        SELECT
+            t.user_id AS token_user_id,
+            COUNT(*) AS token_count,
+            MAX(t.created_at) AS last_token_created
+        FROM refresh_tokens t
+        WHERE t.user_id = p_user_id
+        GROUP BY t.user_id
+    ) rt ON u.id = rt.token_user_id
+    WHERE u.id = p_user_id;
+
+    -- Raised as P0001; the gRPC stats service maps it to NOT_FOUND
+    IF NOT FOUND THEN
+        RAISE EXCEPTION 'User with ID % not found', p_user_id;
+    END IF;
+END;
+$$;
@@ -0,0 +1,3 @@
+-- Migration: Optional name shown in the UI, separate from the legal full_name.
+-- NULL means the full name is shown
+ALTER TABLE users ADD COLUMN display_name TEXT;
@@ -0,0 +1,767 @@
+use axum::{Json, Extension, extract::{Path, Query, State}, response::IntoResponse};
+use chrono::{DateTime, Utc};
+use axum::body::Body;
+use axum::http::{header, StatusCode};
+use futures_util::{stream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use utoipa::IntoParams;
+use uuid::Uuid;
+use validator::ValidationErrors;
+use crate::api::pagination::{created_between_sql, invalid, parse_timestamp, CreatedRange, CreatedRangeParams, ListParams, Page, PageLimits};
+use crate::middleware::validation::ValidationErrorResponse;
+use tracing::{info, warn, error};
+use utoipa::ToSchema;
+use crate::api::auth::{database_error_response, ErrorCode, ErrorResponse};
+use crate::middleware::auth::{AuthenticatedUser, require_admin};
+use crate::middleware::maintenance;
+use crate::api::user::{user_changed, PublicUser};
+use crate::core::user::User;
+use crate::infrastructure::audit::{self, actions};
+use serde_json::json;
+use std::sync::Arc;
+use crate::config::settings::AppSettings;
+
+/// Maintenance mode state, used as both request and response body
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MaintenanceStatus {
+    pub enabled: bool,
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/maintenance",
+    request_body = MaintenanceStatus,
+    responses(
+        (status = 200, description = "Maintenance mode updated - Rate limit: 50 req/min with 5 burst allowance", body = MaintenanceStatus),
+        (status = 401, description = "Kitchen authentication required"),
+        (status = 403, description = "Forbidden - admin role required", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    ),
+    tag = "System Health & Monitoring",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn set_maintenance_mode(AuthenticatedUser(user_id): AuthenticatedUser, State(pool): State<PgPool>, Json(payload): Json<MaintenanceStatus>) -> impl IntoResponse {
+    if let Err(response) = require_admin(&pool, user_id).await {
+        return response;
+    }
+
+    maintenance::set_enabled(payload.enabled);
+    audit::record_or_warn(&pool, Some(user_id), actions::MAINTENANCE_TOGGLED, Some(json!({ "enabled": payload.enabled }))).await;
+    info!(authenticated_user_id = %user_id, enabled = payload.enabled, "Maintenance mode toggled by admin");
+    (StatusCode::OK, Json(MaintenanceStatus { enabled: maintenance::is_enabled() })).into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/maintenance",
+    responses(
+        (status = 200, description = "Current maintenance mode state", body = MaintenanceStatus),
+        (status = 401, description = "Kitchen authentication required")
+    ),
+    tag = "System Health & Monitoring",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_maintenance_mode(AuthenticatedUser(_user_id): AuthenticatedUser) -> impl IntoResponse {
+    Json(MaintenanceStatus { enabled: maintenance::is_enabled() })
+}
+
+/// Filters accepted by `GET /api/v1/admin/audit`.
+///
+/// The time window is `created_from`/`created_to` from [`CreatedRangeParams`],
+/// as on the other list endpoints. The older `from` (inclusive) and `to`
+/// (exclusive) still work but are deprecated and can't be combined with them.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditQuery {
+    /// Exact action name, e.g. `login_failed`
+    pub action: Option<String>,
+    /// Only entries at or after this time (RFC 3339); use `created_from`
+    #[deprecated]
+    pub from: Option<String>,
+    /// Only entries before this time (RFC 3339); use `created_to`, which includes its boundary
+    #[deprecated]
+    pub to: Option<String>,
+    /// Only entries about this user
+    pub user_id: Option<Uuid>,
+    /// Maximum number of rows to return (1-100, default 20)
+    pub limit: Option<i64>,
+    /// Number of rows to skip (default 0)
+    pub offset: Option<i64>,
+}
+
+/// Validated audit filters
+struct AuditFilter {
+    action: Option<String>,
+    created: CreatedRange,
+    user_id: Option<Uuid>,
+    page: Page,
+}
+
+impl AuditQuery {
+    /// Validate the filters, folding the deprecated `from`/`to` into the
+    /// inclusive window given by `created`
+    #[allow(deprecated)]
+    fn filter(&self, created: &CreatedRangeParams, limits: PageLimits) -> Result<AuditFilter, ValidationErrorResponse> {
+        let page = ListParams { limit: self.limit, offset: self.offset, ..Default::default() }.page(limits)?;
+        let action = self.action.clone().filter(|a| !a.is_empty());
+
+        if self.from.is_none() && self.to.is_none() {
+            return Ok(AuditFilter { action, created: created.range()?, user_id: self.user_id, page });
+        }
+
+        let mut errors = ValidationErrors::new();
+        if created.created_from.is_some() || created.created_to.is_some() {
+            errors.add("from", invalid("conflicting_range", "Use either 'created_from'/'created_to' or the deprecated 'from'/'to', not both".to_string()));
+            return Err(ValidationErrorResponse::new(errors));
+        }
+
+        let from = parse_timestamp(&mut errors, "from", self.from.as_deref());
+        let to = parse_timestamp(&mut errors, "to", self.to.as_deref());
+        if let (Some(from), Some(to)) = (from, to) {
+            if from > to {
+                errors.add("to", invalid("date_range", "'to' must not be earlier than 'from'".to_string()));
+            }
+        }
+        if !errors.is_empty() {
+            return Err(ValidationErrorResponse::new(errors));
+        }
+
+        // `to` is exclusive; timestamps are stored to the microsecond
+        let to = to.map(|to| to - chrono::Duration::microseconds(1));
+        Ok(AuditFilter { action, created: CreatedRange { from, to }, user_id: self.user_id, page })
+    }
+}
+
+/// A single audit log row
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub user_id: Option<Uuid>,
+    pub action: String,
+    pub details: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One page of audit entries plus the total number of matches
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AuditLogPage {
+    pub items: Vec<AuditEntry>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// Shared WHERE clause; every filter is a bind parameter and NULL disables it.
+/// `$2`/`$3` are the inclusive `created_at` window.
+fn audit_filter_sql() -> String {
+    format!(
+        "WHERE ($1::text IS NULL OR action = $1) \
+         AND {} \
+         AND ($4::uuid IS NULL OR user_id = $4)",
+        created_between_sql(2, 3)
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/audit",
+    params(AuditQuery, CreatedRangeParams),
+    responses(
+        (status = 200, description = "Audit log entries, newest first - Rate limit: 50 req/min with 5 burst allowance", body = AuditLogPage),
+        (status = 422, description = "Invalid filter or pagination parameters", body = ValidationErrorResponse),
+        (status = 401, description = "Kitchen authentication required"),
+        (status = 403, description = "Forbidden - admin role required", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    ),
+    tag = "System Health & Monitoring",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_audit_log(AuthenticatedUser(user_id): AuthenticatedUser, State(pool): State<PgPool>, Extension(settings): Extension<Arc<AppSettings>>, Query(query): Query<AuditQuery>, Query(created): Query<CreatedRangeParams>) -> impl IntoResponse {
+    if let Err(response) = require_admin(&pool, user_id).await {
+        return response;
+    }
+
+    let filter = match query.filter(&created, settings.page_limits) {
+        Ok(filter) => filter,
+        Err(e) => return e.into_response(),
+    };
+
+    let total = sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM audit_log {}", audit_filter_sql()))
+        .bind(&filter.action)
+        .bind(filter.created.from)
+        .bind(filter.created.to)
+        .bind(filter.user_id)
+        .fetch_one(&pool)
+        .await;
+
+    let items = sqlx::query_as::<_, AuditEntry>(&format!(
+        "SELECT id, user_id, action, details, created_at FROM audit_log {} ORDER BY created_at DESC, id DESC LIMIT $5 OFFSET $6",
+        audit_filter_sql()
+    ))
+    .bind(&filter.action)
+    .bind(filter.created.from)
+    .bind(filter.created.to)
+    .bind(filter.user_id)
+    .bind(filter.page.limit)
+    .bind(filter.page.offset)
+    .fetch_all(&pool)
+    .await;
+
+    match (total, items) {
+        (Ok(total), Ok(items)) => {
+            info!(authenticated_user_id = %user_id, total, returned = items.len(), "Audit log queried");
+            (StatusCode::OK, Json(AuditLogPage { items, total, limit: filter.page.limit, offset: filter.page.offset })).into_response()
+        },
+        (Err(e), _) | (_, Err(e)) => {
+            error!(authenticated_user_id = %user_id, error = %e, "Failed to query audit log");
+            database_error_response(&e)
+        },
+    }
+}
+
+/// Options for `DELETE /api/v1/admin/users/{id}`
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PurgeUserQuery {
+    /// Also strip the user's id and details from their audit history
+    pub anonymize_audit: Option<bool>,
+}
+
+/// What a hard delete removed
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UserPurgeSummary {
+    pub user_id: Uuid,
+    pub refresh_tokens_deleted: u64,
+    pub audit_entries_anonymized: u64,
+}
+
+/// Remove a user and everything that references them inside `tx`.
+///
+/// Dependents go first so the users row is deleted last; the final audit
+/// record is written after anonymization so it is never anonymized itself.
+/// Returns `None` when the user does not exist.
+async fn purge_user(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, actor: Uuid, id: Uuid, anonymize_audit: bool) -> Result<Option<UserPurgeSummary>, sqlx::Error> {
+    let refresh_tokens_deleted = sqlx::query("DELETE FROM refresh_tokens WHERE user_id = $1")
+        .bind(id)
+        .execute(&mut **tx)
+        .await?
+        .rows_affected();
+
+    let audit_entries_anonymized = if anonymize_audit {
+        sqlx::query("UPDATE audit_log SET user_id = NULL, details = NULL WHERE user_id = $1")
+            .bind(id)
+            .execute(&mut **tx)
+            .await?
+            .rows_affected()
+    } else {
+        0
+    };
+
+    let deleted = sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(id)
+        .execute(&mut **tx)
+        .await?
+        .rows_affected();
+    if deleted == 0 {
+        return Ok(None);
+    }
+
+    let summary = UserPurgeSummary { user_id: id, refresh_tokens_deleted, audit_entries_anonymized };
+    audit::record(
+        &mut **tx,
+        Some(actor),
+        actions::USER_PURGED,
+        Some(json!({
+            "purged_user_id": id,
+            "refresh_tokens_deleted": refresh_tokens_deleted,
+            "audit_entries_anonymized": audit_entries_anonymized,
+        })),
+    )
+    .await?;
+    Ok(Some(summary))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/users/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Kitchen staff member ID to permanently remove"),
+        PurgeUserQuery
+    ),
+    responses(
+        (status = 200, description = "User and dependent rows removed - Rate limit: 50 req/min with 5 burst allowance", body = UserPurgeSummary),
+        (status = 400, description = "Admins cannot purge their own account", body = ErrorResponse),
+        (status = 401, description = "Kitchen authentication required"),
+        (status = 403, description = "Forbidden - admin role required", body = ErrorResponse),
+        (status = 404, description = "Kitchen staff member not found", body = ErrorResponse),
+        (status = 500, description = "Database error; nothing was removed", body = ErrorResponse)
+    ),
+    tag = "System Health & Monitoring",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn hard_delete_user(AuthenticatedUser(user_id): AuthenticatedUser, State(pool): State<PgPool>, Path(id): Path<Uuid>, Query(query): Query<PurgeUserQuery>) -> impl IntoResponse {
+    if let Err(response) = require_admin(&pool, user_id).await {
+        return response;
+    }
+
+    if id == user_id {
+        warn!(authenticated_user_id = %user_id, "Admin attempted to purge their own account");
+        return (StatusCode::BAD_REQUEST, Json(ErrorResponse::coded(ErrorCode::BadRequest, "Cannot purge own account", None))).into_response();
+    }
+
+    let anonymize_audit = query.anonymize_audit.unwrap_or(false);
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let summary = purge_user(&mut tx, user_id, id, anonymize_audit).await?;
+        // A missing user leaves nothing to keep; dropping the transaction rolls back
+        if summary.is_some() {
+            tx.commit().await?;
+        }
+        Ok::<_, sqlx::Error>(summary)
+    }
+    .await;
+
+    match result {
+        Ok(Some(summary)) => {
+            info!(
+                authenticated_user_id = %user_id,
+                target_user_id = %id,
+                refresh_tokens_deleted = summary.refresh_tokens_deleted,
+                audit_entries_anonymized = summary.audit_entries_anonymized,
+                "User purged by admin"
+            );
+            user_changed(&pool, id).await;
+            (StatusCode::OK, Json(summary)).into_response()
+        },
+        Ok(None) => {
+            warn!(authenticated_user_id = %user_id, target_user_id = %id, "Purge requested for unknown user");
+            (StatusCode::NOT_FOUND, ErrorResponse::not_found("user")).into_response()
+        },
+        Err(e) => {
+            error!(authenticated_user_id = %user_id, target_user_id = %id, error = %e, "Failed to purge user; transaction rolled back");
+            database_error_response(&e)
+        },
+    }
+}
+
+/// Rows the export reads ahead of the client; a slow reader holds back the
+/// query instead of the server buffering the table
+const EXPORT_BUFFER_ROWS: usize = 64;
+
+/// Streams every user as newline-delimited JSON, oldest first.
+///
+/// Rows are read with a cursor and written as they arrive, so memory stays
+/// bounded however large the table. Each line is a `PublicUser`; password
+/// hashes are never included and avatars aren't looked up. A database error
+/// mid-stream is logged and ends the response early. Every export is
+/// audited with how many users it sent and whether it finished.
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/users/export",
+    responses(
+        (status = 200, description = "One `PublicUser` JSON object per line - Rate limit: 50 req/min with 5 burst allowance", content_type = "application/x-ndjson", body = PublicUser),
+        (status = 401, description = "Kitchen authentication required"),
+        (status = 403, description = "Forbidden - admin role required", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    ),
+    tag = "System Health & Monitoring",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn export_users(AuthenticatedUser(user_id): AuthenticatedUser, State(pool): State<PgPool>) -> impl IntoResponse {
+    if let Err(response) = require_admin(&pool, user_id).await {
+        return response;
+    }
+
+    // The row stream borrows the pool, so it runs in its own task and hands
+    // lines over a bounded channel the response body drains
+    let (tx, rx) = tokio::sync::mpsc::channel::<Result<String, std::io::Error>>(EXPORT_BUFFER_ROWS);
+    tokio::spawn(async move {
+        let mut exported = 0usize;
+        let complete = {
+            let mut rows = sqlx::query_as::<_, User>("SELECT * FROM users ORDER BY created_at, id").fetch(&pool);
+            loop {
+                let line = match rows.try_next().await {
+                    Ok(Some(user)) => serde_json::to_string(&PublicUser::from(&user)).map(|json| json + "\n").map_err(std::io::Error::other),
+                    Ok(None) => break true,
+                    Err(e) => {
+                        error!(authenticated_user_id = %user_id, exported, error = %e, "User export failed mid-stream");
+                        let _ = tx.send(Err(std::io::Error::other(e))).await;
+                        break false;
+                    },
+                };
+                if tx.send(line).await.is_err() {
+                    warn!(authenticated_user_id = %user_id, exported, "Client disconnected during user export");
+                    break false;
+                }
+                exported += 1;
+            }
+        };
+        if complete {
+            info!(authenticated_user_id = %user_id, exported, "Users exported by admin");
+        }
+        // Recorded before `tx` drops, so the response only ends once it is written
+        audit::record_or_warn(&pool, Some(user_id), actions::USERS_EXPORTED, Some(json!({ "exported": exported, "complete": complete }))).await;
+        drop(tx);
+    });
+
+    let lines = stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|line| (line, rx)) });
+    (StatusCode::OK, [(header::CONTENT_TYPE, "application/x-ndjson")], Body::from_stream(lines)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request, routing::put, Router};
+    use serde_json::json;
+    use crate::test_support::{access_token, insert_user_with_role, test_pool};
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    async fn toggle(pool: PgPool, actor: Uuid, enabled: bool) -> StatusCode {
+        let token = access_token(actor);
+        let app = Router::new()
+            .route("/api/v1/admin/maintenance", put(set_maintenance_mode))
+            .with_state(pool)
+            .layer(AppSettings::default().layer());
+        let req = Request::builder()
+            .method("PUT")
+            .uri("/api/v1/admin/maintenance")
+            .header("authorization", format!("Bearer {}", token))
+            .header("content-type", "application/json")
+            .body(Body::from(json!({ "enabled": enabled }).to_string()))
+            .unwrap();
+        app.oneshot(req).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_admin_toggles_maintenance_mode() {
+        let pool = test_pool().await;
+        let admin = insert_user_with_role(&pool, "admin").await;
+
+        assert_eq!(toggle(pool.clone(), admin, true).await, StatusCode::OK);
+        assert!(maintenance::is_enabled());
+        assert_eq!(toggle(pool, admin, false).await, StatusCode::OK);
+        assert!(!maintenance::is_enabled());
+    }
+
+    async fn export_as(pool: PgPool, actor: Uuid) -> (StatusCode, Option<String>, String) {
+        let token = access_token(actor);
+        let app = Router::new()
+            .route("/api/v1/admin/users/export", axum::routing::get(export_users))
+            .with_state(pool)
+            .layer(AppSettings::default().layer());
+        let req = Request::builder()
+            .uri("/api/v1/admin/users/export")
+            .header("authorization", format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        let status = res.status();
+        let content_type = res.headers().get(header::CONTENT_TYPE).map(|v| v.to_str().unwrap().to_string());
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        (status, content_type, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_export_streams_one_user_per_line() {
+        let pool = test_pool().await;
+        let admin = insert_user_with_role(&pool, "admin").await;
+        let exported = [insert_user_with_role(&pool, "user").await, insert_user_with_role(&pool, "line_cook").await];
+
+        let (status, content_type, body) = export_as(pool.clone(), admin).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type.as_deref(), Some("application/x-ndjson"));
+        assert!(body.ends_with('\n'));
+        let users: Vec<serde_json::Value> = body.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        let ids: std::collections::HashSet<_> = users.iter().map(|u| u["id"].as_str().unwrap()).collect();
+        assert_eq!(ids.len(), users.len(), "every user is exported once");
+        for id in exported.iter().chain([&admin]) {
+            let user = users.iter().find(|u| u["id"] == id.to_string()).expect("inserted user is exported");
+            assert_eq!(user["full_name"], "Test User");
+        }
+        assert!(users.iter().all(|u| u.get("password_hash").is_none()), "password hashes are never exported");
+
+        let details: serde_json::Value = sqlx::query_scalar("SELECT details FROM audit_log WHERE user_id = $1 AND action = $2")
+            .bind(admin)
+            .bind(actions::USERS_EXPORTED)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(details, json!({ "exported": users.len(), "complete": true }));
+    }
+
+    #[tokio::test]
+    async fn test_non_admin_cannot_export_users() {
+        let pool = test_pool().await;
+        let user = insert_user_with_role(&pool, "user").await;
+        let (status, _, _) = export_as(pool, user).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_non_admin_cannot_toggle_maintenance_mode() {
+        let pool = test_pool().await;
+        let user = insert_user_with_role(&pool, "user").await;
+
+        assert_eq!(toggle(pool, user, true).await, StatusCode::FORBIDDEN);
+        assert!(!maintenance::is_enabled());
+    }
+
+    async fn audit_query(pool: PgPool, actor: Uuid, query: &str) -> (StatusCode, serde_json::Value) {
+        let token = access_token(actor);
+        let app = Router::new()
+            .route("/api/v1/admin/audit", axum::routing::get(list_audit_log))
+            .with_state(pool)
+            .layer(AppSettings::default().layer());
+        let req = Request::builder()
+            .uri(format!("/api/v1/admin/audit?{}", query))
+            .header("authorization", format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        let status = res.status();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null))
+    }
+
+    async fn insert_audit(pool: &PgPool, user_id: Uuid, action: &str, at: &str) {
+        sqlx::query("INSERT INTO audit_log (user_id, action, created_at) VALUES ($1, $2, $3::timestamptz)")
+            .bind(user_id)
+            .bind(action)
+            .bind(at)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_filters_by_action() {
+        let pool = test_pool().await;
+        let admin = insert_user_with_role(&pool, "admin").await;
+        let subject = Uuid::new_v4();
+        insert_audit(&pool, subject, "login_failed", "2024-03-01T10:00:00Z").await;
+        insert_audit(&pool, subject, "login_failed", "2024-03-01T11:00:00Z").await;
+        insert_audit(&pool, subject, "login_succeeded", "2024-03-01T12:00:00Z").await;
+
+        let (status, body) = audit_query(pool, admin, &format!("user_id={}&action=login_failed", subject)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total"], 2);
+        let items = body["items"].as_array().unwrap();
+        assert_eq!(items.len(), 2);
+        assert!(items.iter().all(|i| i["action"] == "login_failed"));
+        // Newest first
+        assert_eq!(items[0]["created_at"], "2024-03-01T11:00:00Z");
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_date_range_boundaries() {
+        let pool = test_pool().await;
+        let admin = insert_user_with_role(&pool, "admin").await;
+        let subject = Uuid::new_v4();
+        insert_audit(&pool, subject, "login_succeeded", "2024-03-01T00:00:00Z").await;
+        insert_audit(&pool, subject, "login_succeeded", "2024-03-02T00:00:00Z").await;
+        insert_audit(&pool, subject, "login_succeeded", "2024-03-03T00:00:00Z").await;
+
+        // `from` inclusive, `to` exclusive
+        let (status, body) = audit_query(
+            pool.clone(),
+            admin,
+            &format!("user_id={}&from=2024-03-01T00:00:00Z&to=2024-03-03T00:00:00Z", subject),
+        ).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total"], 2);
+
+        let (_, body) = audit_query(pool.clone(), admin, &format!("user_id={}&from=2024-03-02T00:00:00%2B00:00", subject)).await;
+        assert_eq!(body["total"], 2);
+
+        let (_, body) = audit_query(pool.clone(), admin, &format!("user_id={}&limit=1&offset=1", subject)).await;
+        assert_eq!(body["total"], 3);
+        assert_eq!(body["items"].as_array().unwrap().len(), 1);
+        assert_eq!(body["items"][0]["created_at"], "2024-03-02T00:00:00Z");
+
+        let (status, body) = audit_query(pool.clone(), admin, "from=yesterday").await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body["validation_errors"]["from"].is_array());
+
+        let (status, body) = audit_query(pool, admin, "from=2024-03-03T00:00:00Z&to=2024-03-01T00:00:00Z").await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body["validation_errors"]["to"].is_array());
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_created_range_is_inclusive() {
+        let pool = test_pool().await;
+        let admin = insert_user_with_role(&pool, "admin").await;
+        let subject = Uuid::new_v4();
+        insert_audit(&pool, subject, "login_succeeded", "2024-03-01T00:00:00Z").await;
+        insert_audit(&pool, subject, "login_succeeded", "2024-03-02T00:00:00Z").await;
+        insert_audit(&pool, subject, "login_succeeded", "2024-03-03T00:00:00Z").await;
+
+        // Unlike `to`, `created_to` includes its boundary
+        let (status, body) = audit_query(
+            pool.clone(),
+            admin,
+            &format!("user_id={}&created_from=2024-03-01T00:00:00Z&created_to=2024-03-02T00:00:00Z", subject),
+        ).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total"], 2);
+        assert_eq!(body["items"][0]["created_at"], "2024-03-02T00:00:00Z");
+
+        let (_, body) = audit_query(pool.clone(), admin, &format!("user_id={}&created_to=2024-03-01T00:00:00Z", subject)).await;
+        assert_eq!(body["total"], 1);
+
+        let (status, body) = audit_query(pool.clone(), admin, "created_from=2024-03-03T00:00:00Z&created_to=2024-03-01T00:00:00Z").await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body["validation_errors"]["created_to"].is_array());
+
+        // The deprecated pair can't be mixed with the current one
+        let (status, body) = audit_query(pool, admin, "from=2024-03-01T00:00:00Z&created_to=2024-03-02T00:00:00Z").await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body["validation_errors"]["from"].is_array());
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_requires_admin() {
+        let pool = test_pool().await;
+        let user = insert_user_with_role(&pool, "user").await;
+        let (status, _) = audit_query(pool, user, "").await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    async fn purge(pool: PgPool, actor: Uuid, target: Uuid, query: &str) -> (StatusCode, serde_json::Value) {
+        let token = access_token(actor);
+        let app = Router::new()
+            .route("/api/v1/admin/users/:id", axum::routing::delete(hard_delete_user))
+            .with_state(pool)
+            .layer(AppSettings::default().layer());
+        let req = Request::builder()
+            .method("DELETE")
+            .uri(format!("/api/v1/admin/users/{}?{}", target, query))
+            .header("authorization", format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        let status = res.status();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null))
+    }
+
+    async fn insert_refresh_token(pool: &PgPool, user_id: Uuid) {
+        sqlx::query("INSERT INTO refresh_tokens (user_id, token, expires_at) VALUES ($1, $2, NOW() + INTERVAL '1 day')")
+            .bind(user_id)
+            .bind(format!("purge-{}", Uuid::new_v4()))
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    async fn count(pool: &PgPool, sql: &str, id: Uuid) -> i64 {
+        sqlx::query_scalar(sql).bind(id).fetch_one(pool).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_hard_delete_removes_dependents_and_anonymizes_audit() {
+        let pool = test_pool().await;
+        let admin = insert_user_with_role(&pool, "admin").await;
+        let target = insert_user_with_role(&pool, "user").await;
+        insert_refresh_token(&pool, target).await;
+        insert_refresh_token(&pool, target).await;
+        insert_audit(&pool, target, "login_succeeded", "2024-03-01T00:00:00Z").await;
+
+        let (status, body) = purge(pool.clone(), admin, target, "anonymize_audit=true").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["refresh_tokens_deleted"], 2);
+        assert_eq!(body["audit_entries_anonymized"], 1);
+
+        assert_eq!(count(&pool, "SELECT COUNT(*) FROM users WHERE id = $1", target).await, 0);
+        assert_eq!(count(&pool, "SELECT COUNT(*) FROM refresh_tokens WHERE user_id = $1", target).await, 0);
+        assert_eq!(count(&pool, "SELECT COUNT(*) FROM audit_log WHERE user_id = $1", target).await, 0);
+
+        let recorded: serde_json::Value = sqlx::query_scalar(
+            "SELECT details FROM audit_log WHERE user_id = $1 AND action = $2 ORDER BY id DESC LIMIT 1",
+        )
+        .bind(admin)
+        .bind(actions::USER_PURGED)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(recorded["purged_user_id"], target.to_string());
+
+        let (status, _) = purge(pool, admin, target, "").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_hard_delete_keeps_audit_history_by_default() {
+        let pool = test_pool().await;
+        let admin = insert_user_with_role(&pool, "admin").await;
+        let target = insert_user_with_role(&pool, "user").await;
+        insert_audit(&pool, target, "login_succeeded", "2024-03-01T00:00:00Z").await;
+
+        let (status, body) = purge(pool.clone(), admin, target, "").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["audit_entries_anonymized"], 0);
+        assert_eq!(count(&pool, "SELECT COUNT(*) FROM audit_log WHERE user_id = $1", target).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_hard_delete_rolls_back_on_failure() {
+        let pool = test_pool().await;
+        let admin = insert_user_with_role(&pool, "admin").await;
+        let target = insert_user_with_role(&pool, "user").await;
+        insert_refresh_token(&pool, target).await;
+        insert_audit(&pool, target, "login_succeeded", "2024-03-01T00:00:00Z").await;
+
+        // Make the final audit insert for this target fail after every other
+        // statement in the transaction has run
+        let trigger = format!("fail_purge_{}", target.simple());
+        sqlx::query("CREATE OR REPLACE FUNCTION fail_purge_audit() RETURNS trigger AS $$ BEGIN RAISE EXCEPTION 'forced failure'; END $$ LANGUAGE plpgsql")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(&format!(
+            "CREATE TRIGGER {} BEFORE INSERT ON audit_log FOR EACH ROW \
+             WHEN (NEW.details->>'purged_user_id' = '{}') EXECUTE FUNCTION fail_purge_audit()",
+            trigger, target
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let (status, _) = purge(pool.clone(), admin, target, "anonymize_audit=true").await;
+
+        sqlx::query(&format!("DROP TRIGGER {} ON audit_log", trigger)).execute(&pool).await.unwrap();
+
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(count(&pool, "SELECT COUNT(*) FROM users WHERE id = $1", target).await, 1);
+        assert_eq!(count(&pool, "SELECT COUNT(*) FROM refresh_tokens WHERE user_id = $1", target).await, 1);
+        assert_eq!(count(&pool, "SELECT COUNT(*) FROM audit_log WHERE user_id = $1", target).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_hard_delete_requires_admin_and_rejects_self() {
+        let pool = test_pool().await;
+        let admin = insert_user_with_role(&pool, "admin").await;
+        let user = insert_user_with_role(&pool, "user").await;
+
+        let (status, _) = purge(pool.clone(), user, admin, "").await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, _) = purge(pool.clone(), admin, admin, "").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(count(&pool, "SELECT COUNT(*) FROM users WHERE id = $1", admin).await, 1);
+    }
+}
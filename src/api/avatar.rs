@@ -0,0 +1,327 @@
+//! Profile pictures.
+//!
+//! Avatars are uploaded as `multipart/form-data` and stored in the
+//! `user_avatars` table. `PublicUser` references them by URL; the URL carries
+//! the upload time so clients can cache the image until it changes.
+
+use axum::{Extension, Json, extract::{Multipart, Path, State, multipart::MultipartRejection}, response::IntoResponse};
+use axum::http::{StatusCode, header::{CACHE_CONTROL, CONTENT_TYPE}};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::ValidationErrors;
+use crate::api::auth::{database_error_response, ErrorCode, ErrorResponse};
+use crate::api::pagination::invalid;
+use crate::api::user::PublicUser;
+use crate::config::settings::AppSettings;
+use crate::middleware::auth::AuthenticatedUser;
+use crate::middleware::validation::ValidationErrorResponse;
+
+/// Largest avatar accepted unless `MAX_AVATAR_BYTES` says otherwise
+pub const DEFAULT_MAX_AVATAR_BYTES: usize = 2 * 1024 * 1024;
+
+/// Room for multipart boundaries and part headers on top of the image itself
+pub const AVATAR_MULTIPART_OVERHEAD: usize = 16 * 1024;
+
+/// Multipart field carrying the image
+pub const AVATAR_FIELD: &str = "avatar";
+
+/// Image types an avatar may have
+pub const AVATAR_CONTENT_TYPES: [&str; 3] = ["image/png", "image/jpeg", "image/webp"];
+
+/// Image type from the file signature, whatever the upload claims
+fn sniff_image_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// Versioned URL of a user's avatar
+pub fn avatar_url(user_id: Uuid, updated_at: DateTime<Utc>) -> String {
+    format!("/api/v1/users/{}/avatar?v={}", user_id, updated_at.timestamp_millis())
+}
+
+/// Fill in `avatar_url` for the users that have one
+pub async fn attach_avatar_urls(pool: &PgPool, users: &mut [PublicUser]) -> Result<(), sqlx::Error> {
+    let ids: Vec<Uuid> = users.iter().map(|user| user.id).collect();
+    let uploaded: HashMap<Uuid, DateTime<Utc>> = sqlx::query_as::<_, (Uuid, DateTime<Utc>)>(
+        "SELECT user_id, updated_at FROM user_avatars WHERE user_id = ANY($1)",
+    )
+    .bind(&ids)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .collect();
+    for user in users {
+        user.avatar_url = uploaded.get(&user.id).map(|&updated_at| avatar_url(user.id, updated_at));
+    }
+    Ok(())
+}
+
+/// A stored avatar
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AvatarUploaded {
+    /// Where the image is served; changes with every upload
+    #[schema(example = "/api/v1/users/3f1c2a9e-0000-4000-8000-000000000000/avatar?v=1722686400000")]
+    pub avatar_url: String,
+    #[schema(example = "image/png")]
+    pub content_type: String,
+    pub size_bytes: usize,
+}
+
+fn too_large(max: usize) -> axum::response::Response {
+    ErrorResponse::coded(ErrorCode::PayloadTooLarge, "Avatar too large", Some(format!("Avatars may be at most {} bytes", max))).into_response()
+}
+
+fn unsupported_type(details: impl Into<String>) -> axum::response::Response {
+    ErrorResponse::coded(ErrorCode::UnsupportedMediaType, "Unsupported avatar type", Some(details.into())).into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/users/me/avatar",
+    request_body(content = String, content_type = "multipart/form-data", description = "The image in a file field named `avatar` (PNG, JPEG or WebP)"),
+    responses(
+        (status = 200, description = "Avatar stored, replacing any previous one - Rate limit: 100 req/min with 10 burst allowance", body = AvatarUploaded),
+        (status = 400, description = "Malformed multipart body", body = ValidationErrorResponse),
+        (status = 422, description = "No `avatar` field", body = ValidationErrorResponse),
+        (status = 401, description = "Kitchen authentication required"),
+        (status = 413, description = "Image larger than `MAX_AVATAR_BYTES`", body = ErrorResponse),
+        (status = 415, description = "Not multipart, or not a PNG, JPEG or WebP image", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    ),
+    tag = "Kitchen Staff Management",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn upload_avatar(AuthenticatedUser(user_id): AuthenticatedUser, State(pool): State<PgPool>, Extension(settings): Extension<Arc<AppSettings>>, multipart: Result<Multipart, MultipartRejection>) -> impl IntoResponse {
+    let mut multipart = match multipart {
+        Ok(multipart) => multipart,
+        Err(e) => {
+            warn!(user_id = %user_id, error = %e, "Avatar upload is not multipart");
+            return unsupported_type("Upload the image as multipart/form-data");
+        },
+    };
+
+    let max = settings.max_avatar_bytes;
+    let (declared_type, data) = loop {
+        let mut field = match multipart.next_field().await {
+            Ok(Some(field)) if field.name() == Some(AVATAR_FIELD) => field,
+            Ok(Some(_)) => continue,
+            Ok(None) => {
+                warn!(user_id = %user_id, "Avatar upload without an avatar field");
+                let mut errors = ValidationErrors::new();
+                errors.add(AVATAR_FIELD, invalid("required", "Upload the image in a field named `avatar`".to_string()));
+                return ValidationErrorResponse::new(errors).into_response();
+            },
+            Err(e) if e.status() == StatusCode::PAYLOAD_TOO_LARGE => return too_large(max),
+            Err(e) => return ValidationErrorResponse::from_json_error(&format!("Malformed multipart body: {}", e)).into_response(),
+        };
+        let declared_type = field.content_type().unwrap_or_default().to_ascii_lowercase();
+        if !AVATAR_CONTENT_TYPES.contains(&declared_type.as_str()) {
+            warn!(user_id = %user_id, content_type = %declared_type, "Avatar upload with unsupported content type");
+            return unsupported_type("Avatars must be image/png, image/jpeg or image/webp");
+        }
+
+        // Stop reading as soon as the image outgrows the limit
+        let mut data = Vec::new();
+        loop {
+            match field.chunk().await {
+                Ok(Some(chunk)) if data.len() + chunk.len() > max => {
+                    warn!(user_id = %user_id, max, "Avatar upload too large");
+                    return too_large(max);
+                },
+                Ok(Some(chunk)) => data.extend_from_slice(&chunk),
+                Ok(None) => break,
+                Err(e) if e.status() == StatusCode::PAYLOAD_TOO_LARGE => return too_large(max),
+                Err(e) => return ValidationErrorResponse::from_json_error(&format!("Malformed multipart body: {}", e)).into_response(),
+            }
+        }
+        break (declared_type, data);
+    };
+
+    if sniff_image_type(&data) != Some(declared_type.as_str()) {
+        warn!(user_id = %user_id, content_type = %declared_type, "Avatar content does not match its declared type");
+        return unsupported_type(format!("The uploaded file is not a valid {} image", declared_type));
+    }
+
+    let stored = sqlx::query_scalar::<_, DateTime<Utc>>(
+        "INSERT INTO user_avatars (user_id, content_type, data) VALUES ($1, $2, $3)
+         ON CONFLICT (user_id) DO UPDATE SET content_type = EXCLUDED.content_type, data = EXCLUDED.data, updated_at = NOW()
+         RETURNING updated_at",
+    )
+    .bind(user_id)
+    .bind(&declared_type)
+    .bind(&data)
+    .fetch_one(&pool)
+    .await;
+
+    match stored {
+        Ok(updated_at) => {
+            info!(user_id = %user_id, content_type = %declared_type, size = data.len(), "Avatar stored");
+            let uploaded = AvatarUploaded { avatar_url: avatar_url(user_id, updated_at), content_type: declared_type, size_bytes: data.len() };
+            (StatusCode::OK, Json(uploaded)).into_response()
+        },
+        Err(e) => {
+            error!(user_id = %user_id, error = %e, "Failed to store avatar");
+            database_error_response(&e)
+        },
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/{id}/avatar",
+    params(
+        ("id" = Uuid, Path, description = "Kitchen staff member whose avatar to fetch")
+    ),
+    responses(
+        (status = 200, description = "The image, with its stored content type - Rate limit: 100 req/min with 10 burst allowance", content_type = "image/png"),
+        (status = 401, description = "Kitchen authentication required"),
+        (status = 404, description = "User has no avatar", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    ),
+    tag = "Kitchen Staff Management",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_avatar(_user: AuthenticatedUser, State(pool): State<PgPool>, Path(id): Path<Uuid>) -> impl IntoResponse {
+    match sqlx::query_as::<_, (String, Vec<u8>)>("SELECT content_type, data FROM user_avatars WHERE user_id = $1")
+        .bind(id)
+        .fetch_optional(&pool)
+        .await
+    {
+        // The URL changes with every upload, so the image itself never goes stale
+        Ok(Some((content_type, data))) => (
+            StatusCode::OK,
+            [(CONTENT_TYPE, content_type), (CACHE_CONTROL, "private, max-age=86400".to_string())],
+            data,
+        ).into_response(),
+        Ok(None) => ErrorResponse::not_found("avatar").into_response(),
+        Err(e) => {
+            error!(user_id = %id, error = %e, "Failed to read avatar");
+            database_error_response(&e)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, extract::DefaultBodyLimit, http::Request, routing::{get, post}, Router};
+    use tower::ServiceExt;
+    use crate::test_support::{access_token, insert_user, test_pool, unique_email};
+
+    const BOUNDARY: &str = "avatar-test-boundary";
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\0\x01\0\0\0\x01\x08\x06\0\0\0";
+
+    fn multipart_body(content_type: &str, data: &[u8]) -> Vec<u8> {
+        let mut body = format!(
+            "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"avatar\"; filename=\"me\"\r\nContent-Type: {content_type}\r\n\r\n"
+        ).into_bytes();
+        body.extend_from_slice(data);
+        body.extend_from_slice(format!("\r\n--{BOUNDARY}--\r\n").as_bytes());
+        body
+    }
+
+    fn app(pool: PgPool, max_avatar_bytes: usize) -> Router {
+        Router::new()
+            .route("/api/v1/users/me/avatar", post(upload_avatar))
+            .route("/api/v1/users/:id/avatar", get(get_avatar))
+            .layer(DefaultBodyLimit::max(max_avatar_bytes + AVATAR_MULTIPART_OVERHEAD))
+            .with_state(pool)
+            .layer(AppSettings { max_avatar_bytes, ..AppSettings::default() }.layer())
+    }
+
+    async fn upload(app: Router, user_id: Uuid, content_type: &str, data: &[u8]) -> (StatusCode, serde_json::Value) {
+        let token = access_token(user_id);
+        let req = Request::builder()
+            .method("POST")
+            .uri("/api/v1/users/me/avatar")
+            .header("authorization", format!("Bearer {}", token))
+            .header("content-type", format!("multipart/form-data; boundary={}", BOUNDARY))
+            .body(Body::from(multipart_body(content_type, data)))
+            .unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        let status = res.status();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null))
+    }
+
+    #[test]
+    fn test_sniff_image_type() {
+        assert_eq!(sniff_image_type(PNG), Some("image/png"));
+        assert_eq!(sniff_image_type(&[0xFF, 0xD8, 0xFF, 0xE0, 0, 0x10]), Some("image/jpeg"));
+        assert_eq!(sniff_image_type(b"RIFF\x24\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff_image_type(b"GIF89a"), None);
+        assert_eq!(sniff_image_type(b"RIFF\x24\0\0\0WAVEfmt "), None);
+    }
+
+    #[tokio::test]
+    async fn test_upload_valid_avatar_is_served_and_referenced() {
+        let pool = test_pool().await;
+        let user_id = insert_user(&pool, &unique_email("avatar"), "Avatar").await;
+
+        let (status, body) = upload(app(pool.clone(), DEFAULT_MAX_AVATAR_BYTES), user_id, "image/png", PNG).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["content_type"], "image/png");
+        assert_eq!(body["size_bytes"], PNG.len());
+        let url = body["avatar_url"].as_str().unwrap().to_string();
+        assert!(url.starts_with(&format!("/api/v1/users/{}/avatar?v=", user_id)));
+
+        let token = access_token(user_id);
+        let req = Request::builder().uri(&url).header("authorization", format!("Bearer {}", token)).body(Body::empty()).unwrap();
+        let res = app(pool.clone(), DEFAULT_MAX_AVATAR_BYTES).oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()[CONTENT_TYPE], "image/png");
+        assert_eq!(&axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap()[..], PNG);
+
+        let user = crate::infrastructure::database::PgCrud::<crate::core::user::User>::new(pool.clone(), "users");
+        let user = crate::infrastructure::database::Crud::read(&user, user_id).await.unwrap().unwrap();
+        let mut users = vec![PublicUser::from(&user)];
+        attach_avatar_urls(&pool, &mut users).await.unwrap();
+        assert_eq!(users[0].avatar_url.as_deref(), Some(url.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_oversized_avatar_rejected_with_413() {
+        let pool = test_pool().await;
+        let user_id = insert_user(&pool, &unique_email("avatar"), "Avatar").await;
+
+        let mut large = PNG.to_vec();
+        large.resize(65, 0);
+        let (status, body) = upload(app(pool.clone(), 64), user_id, "image/png", &large).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["code"], "PAYLOAD_TOO_LARGE");
+
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM user_avatars WHERE user_id = $1").bind(user_id).fetch_one(&pool).await.unwrap();
+        assert_eq!(stored, 0);
+    }
+
+    #[tokio::test]
+    async fn test_wrong_avatar_type_rejected_with_415() {
+        let pool = test_pool().await;
+        let user_id = insert_user(&pool, &unique_email("avatar"), "Avatar").await;
+
+        let (status, body) = upload(app(pool.clone(), DEFAULT_MAX_AVATAR_BYTES), user_id, "image/gif", b"GIF89a\x01\0\x01\0").await;
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(body["code"], "UNSUPPORTED_MEDIA_TYPE");
+
+        // Declaring an allowed type doesn't help if the bytes aren't that image
+        let (status, _) = upload(app(pool, DEFAULT_MAX_AVATAR_BYTES), user_id, "image/png", b"#!/bin/sh\necho hi\n").await;
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+}
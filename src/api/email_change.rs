@@ -0,0 +1,487 @@
+//! Verified email address changes.
+//!
+//! The email is the login identity, so it is never overwritten directly.
+//! `POST /api/v1/auth/change-email` stores the new address as pending and mails
+//! a one-time token to it; the account keeps logging in with its current email
+//! until `POST /api/v1/auth/verify-email` redeems that token. Only a SHA-256
+//! hash of the token is stored.
+
+use axum::{Extension, Json, extract::State, http::StatusCode, response::IntoResponse};
+use chrono::{DateTime, Duration, Utc};
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgPool};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::{Validate, ValidationErrors};
+use crate::api::auth::{database_error_response, ErrorCode, ErrorResponse};
+use crate::api::pagination::invalid;
+use crate::api::user::user_changed;
+use crate::config::settings::AppSettings;
+use crate::core::auth::verify_password;
+use crate::core::user::User;
+use crate::infrastructure::audit::{self, actions};
+use crate::infrastructure::database::{is_unique_violation, Crud, PgCrud};
+use crate::infrastructure::mail::{self, OutgoingEmail};
+use crate::middleware::auth::AuthenticatedUser;
+use crate::middleware::validation::{InputSanitizer, ValidatedRequest, ValidationErrorResponse};
+
+/// How long a verification token stays redeemable
+pub const EMAIL_CHANGE_TTL_HOURS: i64 = 24;
+
+/// Request to move the account to a new email address
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct ChangeEmailRequest {
+    #[validate(email(message = "Invalid email format"))]
+    pub new_email: String,
+    #[validate(length(min = 1, message = "Current password is required"))]
+    pub current_password: String,
+}
+
+impl ValidatedRequest for ChangeEmailRequest {
+    fn password_fields(&self) -> Vec<(&'static str, &str)> {
+        vec![("current_password", &self.current_password)]
+    }
+}
+
+/// A change waiting for its verification token
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct EmailChangePending {
+    pub new_email: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Token mailed to the new address
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct VerifyEmailChangeRequest {
+    #[validate(length(min = 1, message = "Token is required"))]
+    pub token: String,
+}
+
+impl ValidatedRequest for VerifyEmailChangeRequest {}
+
+#[derive(Debug, FromRow)]
+struct RedeemedEmailChange {
+    user_id: Uuid,
+    new_email: String,
+    expires_at: DateTime<Utc>,
+}
+
+fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// What is stored for, and looked up by, a token; the token itself is only
+/// ever in the email
+fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn verification_email(to: &str, token: &str) -> OutgoingEmail {
+    OutgoingEmail {
+        to: to.to_string(),
+        subject: "Confirm your new email address".to_string(),
+        body: format!(
+            "Use this code to confirm your new email address: {}\n\nIt expires in {} hours. If you did not ask for this change, ignore this message.",
+            token, EMAIL_CHANGE_TTL_HOURS
+        ),
+    }
+}
+
+fn email_taken_response() -> axum::response::Response {
+    (StatusCode::CONFLICT, Json(ErrorResponse::coded(ErrorCode::UserExists, "User already exists", Some("Email already exists".to_string())))).into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/change-email",
+    request_body = ChangeEmailRequest,
+    responses(
+        (status = 202, description = "Verification token sent to the new address; the current email stays active until it is redeemed - Rate limit: 5 req/min with 2 burst allowance", body = EmailChangePending),
+        (status = 400, description = "Malformed JSON body", body = ValidationErrorResponse),
+        (status = 422, description = "Invalid email, or the same as the current one", body = ValidationErrorResponse),
+        (status = 401, description = "Invalid token or current password", body = ErrorResponse),
+        (status = 404, description = "Kitchen staff member not found", body = ErrorResponse),
+        (status = 409, description = "Email already belongs to another user", body = ErrorResponse),
+        (status = 500, description = "Database or email delivery error; no pending change is kept", body = ErrorResponse)
+    ),
+    tag = "Kitchen Staff Authentication",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn request_email_change(AuthenticatedUser(user_id): AuthenticatedUser, State(pool): State<PgPool>, Extension(settings): Extension<Arc<AppSettings>>, Json(mut payload): Json<ChangeEmailRequest>) -> impl IntoResponse {
+    info!(user_id = %user_id, "Email change requested");
+
+    if let Err(validation_errors) = payload.validate_with(&settings) {
+        warn!(user_id = %user_id, "Email change validation failed");
+        return ValidationErrorResponse::new(validation_errors).into_response();
+    }
+    payload.new_email = InputSanitizer::sanitize_email(&payload.new_email);
+
+    let crud: PgCrud<User> = PgCrud::new(pool.clone(), "users");
+    let user = match crud.read(user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            warn!(user_id = %user_id, "User not found for email change");
+            return (StatusCode::NOT_FOUND, ErrorResponse::not_found("user")).into_response();
+        },
+        Err(e) => {
+            error!(user_id = %user_id, error = %e, "Failed to load user for email change");
+            return database_error_response(&e);
+        },
+    };
+
+    if !verify_password(&payload.current_password, &user.password_hash) {
+        warn!(user_id = %user_id, "Current password mismatch during email change");
+        return (StatusCode::UNAUTHORIZED, Json(ErrorResponse::coded(ErrorCode::InvalidCredentials, "Invalid credentials", Some("Current password is incorrect".to_string())))).into_response();
+    }
+    if user.email == payload.new_email.as_str() {
+        let mut errors = ValidationErrors::new();
+        errors.add("new_email", invalid("email_unchanged", "New email must differ from the current one".to_string()));
+        return ValidationErrorResponse::new(errors).into_response();
+    }
+
+    let token = generate_token();
+    let token_hash = hash_token(&token);
+    let expires_at = Utc::now() + Duration::hours(EMAIL_CHANGE_TTL_HOURS);
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let taken: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE email = $1)")
+            .bind(&payload.new_email)
+            .fetch_one(&mut *tx)
+            .await?;
+        if taken {
+            return Ok(false);
+        }
+        // Asking again replaces the previous pending change and its token
+        sqlx::query(
+            "INSERT INTO email_change_requests (user_id, new_email, token_hash, expires_at) VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (user_id) DO UPDATE SET new_email = EXCLUDED.new_email, token_hash = EXCLUDED.token_hash, \
+             expires_at = EXCLUDED.expires_at, created_at = NOW()",
+        )
+        .bind(user_id)
+        .bind(&payload.new_email)
+        .bind(&token_hash)
+        .bind(expires_at)
+        .execute(&mut *tx)
+        .await?;
+        audit::record(&mut *tx, Some(user_id), actions::EMAIL_CHANGE_REQUESTED, None).await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(true)
+    }
+    .await;
+
+    match result {
+        Ok(true) => {},
+        Ok(false) => {
+            warn!(user_id = %user_id, "Email change rejected: email already registered");
+            return email_taken_response();
+        },
+        Err(e) => {
+            error!(user_id = %user_id, error = %e, "Failed to store pending email change");
+            return database_error_response(&e);
+        },
+    }
+
+    // Mail is sent outside the transaction so a slow SMTP server can't hold
+    // database locks; a change whose token never went out is withdrawn
+    if let Err(e) = mail::send(&verification_email(&payload.new_email, &token)).await {
+        error!(user_id = %user_id, error = %e, "Failed to send email change verification");
+        if let Err(e) = sqlx::query("DELETE FROM email_change_requests WHERE user_id = $1 AND token_hash = $2")
+            .bind(user_id)
+            .bind(&token_hash)
+            .execute(&pool)
+            .await
+        {
+            error!(user_id = %user_id, error = %e, "Failed to withdraw undelivered email change");
+        }
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse::coded(ErrorCode::InternalError, "Email delivery failed", Some("Could not send the verification email; please retry".to_string())))).into_response();
+    }
+
+    info!(user_id = %user_id, "Email change verification sent");
+    (StatusCode::ACCEPTED, Json(EmailChangePending { new_email: payload.new_email, expires_at })).into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/verify-email",
+    request_body = VerifyEmailChangeRequest,
+    responses(
+        (status = 204, description = "Email changed; the new address is now used to log in - Rate limit: 5 req/min with 2 burst allowance"),
+        (status = 400, description = "Unknown, used or expired token", body = ErrorResponse),
+        (status = 409, description = "The new email was registered by another user in the meantime", body = ErrorResponse),
+        (status = 422, description = "Empty token", body = ValidationErrorResponse),
+        (status = 500, description = "Database error; the email was not changed", body = ErrorResponse)
+    ),
+    tag = "Kitchen Staff Authentication"
+)]
+pub async fn verify_email_change(State(pool): State<PgPool>, Json(payload): Json<VerifyEmailChangeRequest>) -> impl IntoResponse {
+    if let Err(validation_errors) = payload.validate() {
+        return ValidationErrorResponse::new(validation_errors).into_response();
+    }
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        // Tokens are single use: redeeming, and finding one expired, both remove it
+        let redeemed = sqlx::query_as::<_, RedeemedEmailChange>(
+            "DELETE FROM email_change_requests WHERE token_hash = $1 RETURNING user_id, new_email, expires_at",
+        )
+        .bind(hash_token(&payload.token))
+        .fetch_optional(&mut *tx)
+        .await?;
+        let change = match redeemed {
+            Some(change) if change.expires_at > Utc::now() => change,
+            expired => {
+                tx.commit().await?;
+                return Ok(Err(expired.map(|change| change.user_id)));
+            },
+        };
+        sqlx::query("UPDATE users SET email = $1, updated_at = NOW() WHERE id = $2")
+            .bind(&change.new_email)
+            .bind(change.user_id)
+            .execute(&mut *tx)
+            .await?;
+        audit::record(&mut *tx, Some(change.user_id), actions::EMAIL_CHANGED, Some(json!({ "verified": true }))).await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Ok(change.user_id))
+    }
+    .await;
+
+    match result {
+        Ok(Ok(user_id)) => {
+            info!(user_id = %user_id, "Email change verified");
+            user_changed(&pool, user_id).await;
+            StatusCode::NO_CONTENT.into_response()
+        },
+        Ok(Err(expired_for)) => {
+            warn!(expired_for = ?expired_for, "Email change verification with an unknown or expired token");
+            (StatusCode::BAD_REQUEST, Json(ErrorResponse::coded(ErrorCode::InvalidToken, "Invalid token", Some("The verification token is invalid or has expired".to_string())))).into_response()
+        },
+        Err(e) if is_unique_violation(&e) => {
+            warn!("Email change verification rejected: email registered in the meantime");
+            email_taken_response()
+        },
+        Err(e) => {
+            error!(error = %e, "Failed to apply verified email change");
+            database_error_response(&e)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::AppSettings;
+    use crate::test_support::{access_token, insert_user_with_password, test_pool, unique_email};
+    use async_trait::async_trait;
+    use axum::{body::Body, http::Request, routing::post, Router};
+    use std::sync::{Arc, Mutex};
+    use tower::ServiceExt;
+
+    const PASSWORD: &str = "SecurePass123!";
+
+    #[derive(Default)]
+    struct RecordingMailer(Mutex<Vec<OutgoingEmail>>);
+
+    #[async_trait]
+    impl mail::Mailer for RecordingMailer {
+        async fn send(&self, email: &OutgoingEmail) -> anyhow::Result<()> {
+            self.0.lock().unwrap().push(email.clone());
+            Ok(())
+        }
+    }
+
+    impl RecordingMailer {
+        /// Token from the last verification email
+        fn last_token(&self) -> String {
+            let sent = self.0.lock().unwrap();
+            let body = &sent.last().expect("verification email sent").body;
+            body.split_once(": ").unwrap().1.lines().next().unwrap().to_string()
+        }
+    }
+
+    struct FailingMailer;
+
+    #[async_trait]
+    impl mail::Mailer for FailingMailer {
+        async fn send(&self, _email: &OutgoingEmail) -> anyhow::Result<()> {
+            anyhow::bail!("SMTP server unavailable")
+        }
+    }
+
+    async fn post_json(pool: PgPool, uri: &str, bearer: Option<Uuid>, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+        let app = Router::new()
+            .route("/api/v1/auth/change-email", post(request_email_change))
+            .route("/api/v1/auth/verify-email", post(verify_email_change))
+            .route("/api/v1/auth/login", post(crate::api::auth::login))
+            .with_state(pool)
+            .layer(AppSettings::default().layer());
+        let mut req = Request::builder().method("POST").uri(uri).header("content-type", "application/json");
+        if let Some(user_id) = bearer {
+            req = req.header("authorization", format!("Bearer {}", access_token(user_id)));
+        }
+        let res = app.oneshot(req.body(Body::from(body.to_string())).unwrap()).await.unwrap();
+        let status = res.status();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null))
+    }
+
+    async fn login(pool: &PgPool, email: &str) -> StatusCode {
+        post_json(pool.clone(), "/api/v1/auth/login", None, json!({ "email": email, "password": PASSWORD })).await.0
+    }
+
+    async fn pending_token_hash(pool: &PgPool, user_id: Uuid) -> Option<String> {
+        sqlx::query_scalar("SELECT token_hash FROM email_change_requests WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_request_keeps_old_email_until_verified() {
+        let pool = test_pool().await;
+        let old_email = unique_email("email-old");
+        let new_email = unique_email("email-new");
+        let user_id = insert_user_with_password(&pool, &old_email, PASSWORD).await;
+        let mailer = Arc::new(RecordingMailer::default());
+        mail::set_mailer(mailer.clone());
+
+        let (status, body) = post_json(
+            pool.clone(),
+            "/api/v1/auth/change-email",
+            Some(user_id),
+            json!({ "new_email": new_email.to_uppercase(), "current_password": PASSWORD }),
+        )
+        .await;
+        mail::set_mailer(Arc::new(mail::LogMailer));
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(body["new_email"], new_email);
+
+        let stored = pending_token_hash(&pool, user_id).await.expect("pending change stored");
+        let sent = mailer.0.lock().unwrap().clone();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].to, new_email);
+        // Only the hash of the mailed token is stored
+        let token = mailer.last_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(stored, token);
+        assert_eq!(stored, hash_token(&token));
+
+        assert_eq!(login(&pool, &old_email).await, StatusCode::OK);
+        assert_eq!(login(&pool, &new_email).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_verify_applies_new_email() {
+        let pool = test_pool().await;
+        let old_email = unique_email("email-old");
+        let new_email = unique_email("email-new");
+        let user_id = insert_user_with_password(&pool, &old_email, PASSWORD).await;
+        let mailer = Arc::new(RecordingMailer::default());
+        mail::set_mailer(mailer.clone());
+        let (status, _) = post_json(
+            pool.clone(),
+            "/api/v1/auth/change-email",
+            Some(user_id),
+            json!({ "new_email": new_email, "current_password": PASSWORD }),
+        )
+        .await;
+        mail::set_mailer(Arc::new(mail::LogMailer));
+        assert_eq!(status, StatusCode::ACCEPTED);
+        let token = mailer.last_token();
+
+        // The stored hash doesn't redeem the change
+        let stored = pending_token_hash(&pool, user_id).await.unwrap();
+        let (status, _) = post_json(pool.clone(), "/api/v1/auth/verify-email", None, json!({ "token": stored })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(pending_token_hash(&pool, user_id).await, Some(stored));
+
+        let (status, _) = post_json(pool.clone(), "/api/v1/auth/verify-email", None, json!({ "token": token })).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert_eq!(pending_token_hash(&pool, user_id).await, None);
+        assert_eq!(login(&pool, &new_email).await, StatusCode::OK);
+        assert_eq!(login(&pool, &old_email).await, StatusCode::UNAUTHORIZED);
+
+        // Tokens are single use
+        let (status, body) = post_json(pool.clone(), "/api/v1/auth/verify-email", None, json!({ "token": token })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Invalid token");
+    }
+
+    #[tokio::test]
+    async fn test_expired_token_is_rejected() {
+        let pool = test_pool().await;
+        let old_email = unique_email("email-old");
+        let user_id = insert_user_with_password(&pool, &old_email, PASSWORD).await;
+        let token = generate_token();
+        sqlx::query("INSERT INTO email_change_requests (user_id, new_email, token_hash, expires_at) VALUES ($1, $2, $3, NOW() - INTERVAL '1 minute')")
+            .bind(user_id)
+            .bind(unique_email("email-late"))
+            .bind(hash_token(&token))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let (status, _) = post_json(pool.clone(), "/api/v1/auth/verify-email", None, json!({ "token": token })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(login(&pool, &old_email).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_request_rejects_wrong_password_and_taken_email() {
+        let pool = test_pool().await;
+        let user_id = insert_user_with_password(&pool, &unique_email("email-old"), PASSWORD).await;
+        let taken = unique_email("email-taken");
+        insert_user_with_password(&pool, &taken, PASSWORD).await;
+
+        let (status, _) = post_json(
+            pool.clone(),
+            "/api/v1/auth/change-email",
+            Some(user_id),
+            json!({ "new_email": unique_email("email-new"), "current_password": "WrongPass123!" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, _) = post_json(
+            pool.clone(),
+            "/api/v1/auth/change-email",
+            Some(user_id),
+            json!({ "new_email": taken, "current_password": PASSWORD }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(pending_token_hash(&pool, user_id).await, None);
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_undelivered_change_is_withdrawn() {
+        let pool = test_pool().await;
+        let old_email = unique_email("email-old");
+        let user_id = insert_user_with_password(&pool, &old_email, PASSWORD).await;
+        mail::set_mailer(Arc::new(FailingMailer));
+
+        let (status, body) = post_json(
+            pool.clone(),
+            "/api/v1/auth/change-email",
+            Some(user_id),
+            json!({ "new_email": unique_email("email-new"), "current_password": PASSWORD }),
+        )
+        .await;
+        mail::set_mailer(Arc::new(mail::LogMailer));
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body["error"], "Email delivery failed");
+        assert_eq!(pending_token_hash(&pool, user_id).await, None);
+    }
+}
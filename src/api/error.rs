@@ -0,0 +1,174 @@
+//! Error type shared by REST handlers.
+//!
+//! Handlers return [`ApiResult`] and lean on the `From` conversions below,
+//! so `?` works directly on validation errors, JSON rejections, database
+//! errors and [`ErrorResponse`]s without a `map_err` at each call site.
+
+use axum::extract::rejection::JsonRejection;
+use axum::response::IntoResponse;
+use axum::Json;
+use tracing::warn;
+use validator::ValidationErrors;
+
+use crate::api::auth::{pool_exhausted_error, pool_exhausted_response, ErrorCode, ErrorResponse};
+use crate::middleware::validation::ValidationErrorResponse;
+
+/// What a handler returning JSON on success gives back
+pub type ApiResult<T> = Result<Json<T>, AppError>;
+
+/// Combined error type for handlers that covers validation and standard errors.
+///
+/// # Variants
+///
+/// * `Validation` - Input validation errors with field-specific messages
+/// * `Standard` - General failures (credentials, missing records, server errors)
+#[derive(Debug)]
+pub enum AppError {
+    Validation(ValidationErrorResponse),
+    Standard(ErrorResponse),
+    /// 401 carrying a `WWW-Authenticate` challenge
+    Challenge {
+        error: ErrorResponse,
+        www_authenticate: String,
+    },
+    /// 503 with `Retry-After`, e.g. when the database pool is exhausted
+    Unavailable(ErrorResponse),
+    /// Body that could not be deserialized, answered with axum's own rejection
+    Rejected(JsonRejection),
+}
+
+impl AppError {
+    /// `Unavailable` for pool timeouts, otherwise `Standard(fallback)`
+    pub fn database(e: &sqlx::Error, fallback: ErrorResponse) -> Self {
+        match e {
+            sqlx::Error::PoolTimedOut => AppError::Unavailable(pool_exhausted_error()),
+            _ => AppError::Standard(fallback),
+        }
+    }
+
+    /// 401 "Authentication required" with the given challenge header value
+    pub(crate) fn challenge(details: &str, www_authenticate: String) -> Self {
+        AppError::Challenge {
+            error: ErrorResponse::coded(ErrorCode::AuthenticationRequired, "Authentication required", Some(details.to_string())),
+            www_authenticate,
+        }
+    }
+}
+
+impl From<ErrorResponse> for AppError {
+    fn from(error: ErrorResponse) -> Self {
+        AppError::Standard(error)
+    }
+}
+
+impl From<ValidationErrorResponse> for AppError {
+    fn from(error: ValidationErrorResponse) -> Self {
+        AppError::Validation(error)
+    }
+}
+
+impl From<ValidationErrors> for AppError {
+    fn from(errors: ValidationErrors) -> Self {
+        AppError::Validation(ValidationErrorResponse::new(errors))
+    }
+}
+
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        AppError::Rejected(rejection)
+    }
+}
+
+/// Same mapping as [`database_error_response`](crate::api::auth::database_error_response):
+/// 503 on pool exhaustion, otherwise 500 "Database error". Handlers with a
+/// more specific message keep using [`AppError::database`].
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        warn!(error = %e, "Database error");
+        AppError::database(&e, ErrorResponse::coded(ErrorCode::InternalError, "Database error", Some(e.to_string())))
+    }
+}
+
+/// Converts `AppError` into an HTTP response, delegating to the wrapped error.
+///
+/// # Error Response Mapping
+///
+/// - `Validation` errors → 422 Unprocessable Entity with field-specific error
+///   details, or 400 Bad Request for bodies that aren't well-formed JSON
+/// - `Standard` errors → Various status codes based on error type
+/// - `Challenge` errors → 401 Unauthorized with a `WWW-Authenticate` header
+/// - `Unavailable` errors → 503 Service Unavailable with a `Retry-After` header
+/// - `Rejected` errors → axum's JSON rejection (usually 422)
+impl IntoResponse for AppError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            AppError::Validation(err) => err.into_response(),
+            AppError::Standard(err) => err.into_response(),
+            AppError::Challenge { error, www_authenticate } => (
+                axum::http::StatusCode::UNAUTHORIZED,
+                [(axum::http::header::WWW_AUTHENTICATE, www_authenticate)],
+                Json(error),
+            ).into_response(),
+            AppError::Unavailable(err) => pool_exhausted_response(err),
+            AppError::Rejected(rejection) => rejection.into_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::auth::database_error_response;
+    use axum::body::to_bytes;
+    use axum::http::{header, StatusCode};
+    use axum::response::Response;
+    use validator::Validate;
+
+    #[derive(Validate)]
+    struct Named {
+        #[validate(length(min = 1))]
+        name: String,
+    }
+
+    async fn parts(response: Response) -> (StatusCode, Option<String>, serde_json::Value) {
+        let status = response.status();
+        let retry_after = response.headers().get(header::RETRY_AFTER).map(|v| v.to_str().unwrap().to_string());
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, retry_after, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn validated(named: &Named) -> Result<(), AppError> {
+        named.validate()?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_validation_errors_convert_like_explicit_variant() {
+        let named = Named { name: String::new() };
+        let explicit = AppError::Validation(ValidationErrorResponse::new(named.validate().unwrap_err()));
+        let converted = validated(&named).unwrap_err();
+
+        let (status, _, body) = parts(converted.into_response()).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!((status, None, body), parts(explicit.into_response()).await);
+        assert!(validated(&Named { name: "Chef".to_string() }).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_error_response_converts_to_standard() {
+        let converted: AppError = ErrorResponse::not_found("user").into();
+        let (status, _, body) = parts(converted.into_response()).await;
+        assert_eq!((status, None, body), parts(ErrorResponse::not_found("user").into_response()).await);
+    }
+
+    #[tokio::test]
+    async fn test_database_errors_match_database_error_response() {
+        for make in [|| sqlx::Error::PoolTimedOut, || sqlx::Error::RowNotFound] {
+            let converted = parts(AppError::from(make()).into_response()).await;
+            assert_eq!(converted, parts(database_error_response(&make())).await);
+        }
+        let (status, retry_after, _) = parts(AppError::from(sqlx::Error::PoolTimedOut).into_response()).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(retry_after.is_some());
+    }
+}
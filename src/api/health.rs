@@ -10,6 +10,10 @@
 //!
 //! - `/health/live` - Liveness probe (application is running)
 //! - `/health/ready` - Readiness probe (application can serve traffic)
+//! - `/health/info` - Build and version information of the running binary
+//!
+//! The probes answer JSON by default. Send `Accept: text/plain` to get just
+//! the status word (e.g. `ok`) for probes that only compare the body.
 //!
 //! # Examples
 //!
@@ -26,7 +30,8 @@
 //!     .await?;
 //!
 //! assert_eq!(response.status(), 200);
-//! assert_eq!(response.text().await?, "live");
+//! let body: serde_json::Value = response.json().await?;
+//! assert_eq!(body["status"], "ok");
 //! # Ok(())
 //! # }
 //! ```
@@ -50,12 +55,50 @@
 //! # }
 //! ```
 
-use axum::{Json, extract::State, response::IntoResponse};
+use axum::{Json, Extension, extract::State, http::{header::ACCEPT, HeaderMap, StatusCode, Uri}, response::{IntoResponse, Response}};
 use sqlx::PgPool;
 use serde::Serialize;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
 use tracing::error;
 use utoipa::ToSchema;
 
+use crate::config::settings::AppSettings;
+
+/// Body of the liveness and startup probes
+#[derive(Serialize, ToSchema)]
+pub struct ProbeStatus {
+    #[schema(example = "ok")]
+    pub status: &'static str,
+}
+
+/// Whether the client prefers `text/plain` over JSON.
+///
+/// Whichever of `text/plain` and `application/json` is listed first in
+/// `Accept` wins; quality values are not weighed. Anything else gets JSON.
+fn wants_plain_text(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|range| range.split(';').next().unwrap_or_default().trim().to_ascii_lowercase())
+        .find(|media| media == "text/plain" || media == "application/json")
+        .is_some_and(|media| media == "text/plain")
+}
+
+/// Respond with `body` as JSON, or only `status` as plain text when asked for
+fn probe_response<T: Serialize>(headers: &HeaderMap, code: StatusCode, status: &'static str, body: T) -> Response {
+    if wants_plain_text(headers) {
+        (code, status).into_response()
+    } else {
+        (code, Json(body)).into_response()
+    }
+}
+
 /// Health status response structure containing system and dependency status information.
 ///
 /// This structure provides detailed health information for monitoring systems
@@ -64,8 +107,9 @@ use utoipa::ToSchema;
 /// # Fields
 ///
 /// * `status` - Overall system status: "ok", "degraded", or "error"
-/// * `database` - Database connection status: "ok" or "error"
+/// * `database` - Database connection status: "ok", "error" or "timeout"
 /// * `error` - Optional error message when status is not "ok"
+/// * `checks` - Result of every dependency check that ran
 ///
 /// # Status Meanings
 ///
@@ -83,13 +127,15 @@ use utoipa::ToSchema;
 ///     status: "ok",
 ///     database: "ok",
 ///     error: None,
+///     checks: vec![],
 /// };
 ///
 /// // System with database issues
 /// let degraded = HealthStatus {
 ///     status: "degraded",
 ///     database: "error",
-///     error: Some("Connection timeout".to_string()),
+///     error: Some("database: Connection timeout".to_string()),
+///     checks: vec![],
 /// };
 /// ```
 #[derive(Serialize, ToSchema)]
@@ -97,6 +143,202 @@ pub struct HealthStatus {
     pub status: &'static str,
     pub database: &'static str,
     pub error: Option<String>,
+    pub checks: Vec<DependencyCheck>,
+    /// Applied schema version; absent when it couldn't be read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub migrations: Option<MigrationStatus>,
+}
+
+/// Newest migration this binary ships, from the `migrations/` file names
+pub fn expected_schema_version() -> i64 {
+    env!("BUILD_SCHEMA_VERSION").parse().unwrap_or(0)
+}
+
+/// Schema version applied to the database against the one the binary expects
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct MigrationStatus {
+    #[schema(example = 20240809)]
+    pub expected_version: i64,
+    /// Highest successful version in `_sqlx_migrations`; absent when the
+    /// table doesn't exist because migrations are applied by other tooling
+    pub applied_version: Option<i64>,
+    /// "current", "behind" (the binary expects a newer schema), "ahead"
+    /// (e.g. an older pod during a rollout) or "untracked"
+    #[schema(example = "current")]
+    pub status: &'static str,
+}
+
+impl MigrationStatus {
+    pub fn new(expected_version: i64, applied_version: Option<i64>) -> Self {
+        let status = match applied_version {
+            None => "untracked",
+            Some(applied) if applied < expected_version => "behind",
+            Some(applied) if applied > expected_version => "ahead",
+            Some(_) => "current",
+        };
+        Self { expected_version, applied_version, status }
+    }
+
+    /// Whether the running code may depend on tables the database lacks
+    pub fn is_behind(&self) -> bool {
+        self.status == "behind"
+    }
+}
+
+/// Outcome of a single readiness check
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DependencyCheck {
+    #[schema(example = "database")]
+    pub name: &'static str,
+    /// "ok", "error" or "timeout"
+    #[schema(example = "ok")]
+    pub status: &'static str,
+    pub elapsed_ms: u64,
+    pub error: Option<String>,
+}
+
+impl DependencyCheck {
+    fn is_ok(&self) -> bool {
+        self.status == "ok"
+    }
+}
+
+/// Budget for each readiness check unless configured otherwise
+pub const DEFAULT_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Optional dependencies probed by `/health/ready` alongside the database
+#[derive(Debug, Clone)]
+pub struct ReadinessTargets {
+    /// gRPC upstream URL, checked with a TCP connect
+    pub grpc_upstream: Option<String>,
+    /// Redis URL, checked with `PING`
+    pub redis_url: Option<String>,
+    /// Time each check may take before it counts as failed
+    pub check_timeout: Duration,
+    /// Fail readiness while the applied schema is older than the binary expects
+    pub require_current_schema: bool,
+}
+
+impl Default for ReadinessTargets {
+    fn default() -> Self {
+        Self { grpc_upstream: None, redis_url: None, check_timeout: DEFAULT_CHECK_TIMEOUT, require_current_schema: true }
+    }
+}
+
+/// Run one check under `timeout`, recording how long it took
+async fn timed_check<F>(name: &'static str, timeout: Duration, check: F) -> DependencyCheck
+where
+    F: Future<Output = Result<(), String>>,
+{
+    let started = Instant::now();
+    let result = tokio::time::timeout(timeout, check).await;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    let (status, error) = match result {
+        Ok(Ok(())) => ("ok", None),
+        Ok(Err(e)) => ("error", Some(e)),
+        Err(_) => ("timeout", Some(format!("timed out after {}ms", timeout.as_millis()))),
+    };
+    if let Some(e) = &error {
+        error!(check = name, status, elapsed_ms, error = %e, "Readiness check failed");
+    }
+    DependencyCheck { name, status, elapsed_ms, error }
+}
+
+/// Run `check` only when a target is configured
+async fn optional_check<F>(name: &'static str, timeout: Duration, check: Option<F>) -> Option<DependencyCheck>
+where
+    F: Future<Output = Result<(), String>>,
+{
+    match check {
+        Some(check) => Some(timed_check(name, timeout, check).await),
+        None => None,
+    }
+}
+
+async fn check_database(pool: &PgPool) -> Result<(), String> {
+    sqlx::query_scalar::<_, i32>("SELECT 1")
+        .fetch_one(pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// SQLSTATE Postgres reports for a missing table
+const UNDEFINED_TABLE: &str = "42P01";
+
+/// Compare the applied schema version with `expected`
+async fn migration_status(pool: &PgPool, expected: i64) -> Result<MigrationStatus, sqlx::Error> {
+    match sqlx::query_scalar::<_, Option<i64>>("SELECT MAX(version) FROM _sqlx_migrations WHERE success")
+        .fetch_one(pool)
+        .await
+    {
+        Ok(applied) => Ok(MigrationStatus::new(expected, Some(applied.unwrap_or(0)))),
+        Err(e) if e.as_database_error().and_then(|db| db.code()).is_some_and(|code| code == UNDEFINED_TABLE) => {
+            Ok(MigrationStatus::new(expected, None))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Record the migration status in `slot`, failing only when the schema is
+/// behind and `required` is set
+async fn check_migrations(pool: &PgPool, expected: i64, required: bool, slot: &mut Option<MigrationStatus>) -> Result<(), String> {
+    let status = migration_status(pool, expected).await.map_err(|e| e.to_string())?;
+    let behind = status.is_behind();
+    let applied = status.applied_version.unwrap_or(0);
+    *slot = Some(status);
+    if behind && required {
+        Err(format!("database schema is at version {}, binary expects {}", applied, expected))
+    } else {
+        Ok(())
+    }
+}
+
+/// `host:port` for a URL, filling in the scheme's default port
+fn socket_address(url: &str, default_port: u16) -> Result<String, String> {
+    let uri: Uri = url.parse().map_err(|e| format!("invalid URL: {}", e))?;
+    let host = uri.host().ok_or_else(|| "URL has no host".to_string())?;
+    let port = uri.port_u16().unwrap_or(match uri.scheme_str() {
+        Some("https") => 443,
+        Some("http") => 80,
+        _ => default_port,
+    });
+    Ok(format!("{}:{}", host, port))
+}
+
+async fn check_tcp(url: String) -> Result<(), String> {
+    TcpStream::connect(socket_address(&url, 80)?).await.map(|_| ()).map_err(|e| e.to_string())
+}
+
+/// Redis is up when it answers `PING`. `-NOAUTH` also counts: the server is
+/// serving requests, it just wants credentials this probe doesn't send.
+async fn check_redis(url: String) -> Result<(), String> {
+    let mut stream = TcpStream::connect(socket_address(&url, 6379)?).await.map_err(|e| e.to_string())?;
+    stream.write_all(b"PING\r\n").await.map_err(|e| e.to_string())?;
+    let mut reply = [0u8; 64];
+    let n = stream.read(&mut reply).await.map_err(|e| e.to_string())?;
+    let reply = String::from_utf8_lossy(&reply[..n]);
+    if reply.starts_with("+PONG") || reply.starts_with("-NOAUTH") {
+        Ok(())
+    } else {
+        Err(format!("unexpected PING reply: {}", reply.trim_end()))
+    }
+}
+
+/// Combine check results into the probe response; any failure is a 503
+fn aggregate(checks: Vec<DependencyCheck>) -> (StatusCode, HealthStatus) {
+    let database = checks.iter().find(|c| c.name == "database").map_or("error", |c| c.status);
+    let failures: Vec<String> = checks
+        .iter()
+        .filter(|c| !c.is_ok())
+        .map(|c| format!("{}: {}", c.name, c.error.as_deref().unwrap_or(c.status)))
+        .collect();
+    let (code, status, error) = if failures.is_empty() {
+        (StatusCode::OK, "ok", None)
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "degraded", Some(failures.join("; ")))
+    };
+    (code, HealthStatus { status, database, error, checks, migrations: None })
 }
 
 /// Liveness probe endpoint that indicates if the application is running.
@@ -107,7 +349,7 @@ pub struct HealthStatus {
 ///
 /// # Returns
 ///
-/// * `200 OK` with "live" text - Application is running
+/// * `200 OK` with `{"status": "ok"}`, or `ok` for `Accept: text/plain` - Application is running
 ///
 /// # Usage in Kubernetes
 ///
@@ -149,7 +391,8 @@ pub struct HealthStatus {
 ///     .await?;
 ///
 /// assert_eq!(response.status(), 200);
-/// assert_eq!(response.text().await?, "live");
+/// let body: serde_json::Value = response.json().await?;
+/// assert_eq!(body["status"], "ok");
 /// println!("Application is alive and running");
 /// # Ok(())
 /// # }
@@ -158,12 +401,41 @@ pub struct HealthStatus {
     get,
     path = "/health/live",
     responses(
-        (status = 200, description = "Kitchen management system is alive and operational - Rate limit: 300 req/min with 50 burst allowance")
+        (status = 200, description = "Kitchen management system is alive and operational - Rate limit: 300 req/min with 50 burst allowance", body = ProbeStatus,
+            content_type = ["application/json", "text/plain"])
     ),
     tag = "System Health & Monitoring"
 )]
-pub async fn live() -> impl IntoResponse {
-    (axum::http::StatusCode::OK, "live")
+pub async fn live(headers: HeaderMap) -> Response {
+    probe_response(&headers, StatusCode::OK, "ok", ProbeStatus { status: "ok" })
+}
+
+/// Startup probe endpoint for orchestrators such as Kubernetes `startupProbe`.
+///
+/// Returns `200` with status `started` once boot has finished (the database
+/// answered), and `503` with status `starting` and `Retry-After` before that.
+/// Data endpoints answer 503 for the same window.
+#[utoipa::path(
+    get,
+    path = "/health/startup",
+    responses(
+        (status = 200, description = "Startup complete - Rate limit: 300 req/min with 50 burst allowance", body = ProbeStatus,
+            content_type = ["application/json", "text/plain"]),
+        (status = 503, description = "Still starting up; retry after the `Retry-After` delay", body = ProbeStatus,
+            content_type = ["application/json", "text/plain"])
+    ),
+    tag = "System Health & Monitoring"
+)]
+pub async fn startup(headers: HeaderMap) -> Response {
+    use crate::middleware::startup::{is_started, STARTUP_RETRY_AFTER_SECS};
+
+    if is_started() {
+        probe_response(&headers, StatusCode::OK, "started", ProbeStatus { status: "started" })
+    } else {
+        let mut response = probe_response(&headers, StatusCode::SERVICE_UNAVAILABLE, "starting", ProbeStatus { status: "starting" });
+        response.headers_mut().insert(axum::http::header::RETRY_AFTER, STARTUP_RETRY_AFTER_SECS.into());
+        response
+    }
 }
 
 /// Readiness probe endpoint that indicates if the application can serve traffic.
@@ -180,12 +452,21 @@ pub async fn live() -> impl IntoResponse {
 /// # Returns
 ///
 /// * `200 OK` with health status JSON - All dependencies healthy
-/// * `500 Internal Server Error` with error details - Dependencies unhealthy
+/// * `503 Service Unavailable` with error details - Dependencies unhealthy
+///
+/// With `Accept: text/plain` only the overall status (`ok` or `degraded`) is sent.
 ///
 /// # Health Checks Performed
 ///
+/// Checks run concurrently, each with its own timeout (see
+/// [`ReadinessTargets`]), so one slow dependency can't stall the probe.
+///
 /// 1. **Database Connectivity** - Executes `SELECT 1` query to verify connection
-/// 2. **Connection Pool Status** - Ensures database pool is available
+/// 2. **gRPC Upstream** - TCP connect, when the gRPC server is enabled
+/// 3. **Redis** - `PING`, when `APP_REDIS__URL` is set
+/// 4. **Migrations** - Highest version in `_sqlx_migrations` against the one
+///    the binary was built with, reported under `migrations`. A schema that
+///    is behind fails the probe unless `READINESS_REQUIRE_CURRENT_SCHEMA=false`
 ///
 /// # Usage in Kubernetes
 ///
@@ -242,7 +523,12 @@ pub async fn live() -> impl IntoResponse {
 /// {
 ///   "status": "ok",
 ///   "database": "ok",
-///   "error": null
+///   "error": null,
+///   "checks": [
+///     { "name": "database", "status": "ok", "elapsed_ms": 2, "error": null },
+///     { "name": "migrations", "status": "ok", "elapsed_ms": 3, "error": null }
+///   ],
+///   "migrations": { "expected_version": 20240809, "applied_version": 20240809, "status": "current" }
 /// }
 /// ```
 ///
@@ -250,33 +536,340 @@ pub async fn live() -> impl IntoResponse {
 /// ```json
 /// {
 ///   "status": "degraded",
-///   "database": "error",
-///   "error": "connection refused"
+///   "database": "ok",
+///   "error": "redis: timed out after 2000ms",
+///   "checks": [
+///     { "name": "database", "status": "ok", "elapsed_ms": 2, "error": null },
+///     { "name": "redis", "status": "timeout", "elapsed_ms": 2001, "error": "timed out after 2000ms" }
+///   ]
 /// }
 /// ```
 #[utoipa::path(
     get,
     path = "/health/ready",
     responses(
-        (status = 200, description = "Kitchen management system is ready to serve traffic - Rate limit: 300 req/min with 50 burst allowance", body = HealthStatus),
-        (status = 500, description = "Kitchen management system is not ready - dependencies unavailable", body = HealthStatus)
+        (status = 200, description = "Kitchen management system is ready to serve traffic - Rate limit: 300 req/min with 50 burst allowance", body = HealthStatus,
+            content_type = ["application/json", "text/plain"]),
+        (status = 503, description = "Kitchen management system is not ready - dependencies unavailable", body = HealthStatus,
+            content_type = ["application/json", "text/plain"])
     ),
     tag = "System Health & Monitoring"
 )]
-pub async fn ready(State(pool): State<PgPool>) -> impl IntoResponse {
-    let (db_status, db_error) = match sqlx::query_scalar::<_, i32>("SELECT 1").fetch_one(&pool).await {
-        Ok(_) => ("ok", None),
-        Err(e) => {
-            error!(error = %e, "Database health check failed");
-            ("error", Some(e.to_string()))
-        }
-    };
-    let status = if db_status == "ok" { "ok" } else { "degraded" };
-    let health = HealthStatus {
-        status,
-        database: db_status,
-        error: db_error,
+pub async fn ready(State(pool): State<PgPool>, Extension(settings): Extension<Arc<AppSettings>>, headers: HeaderMap) -> Response {
+    let targets = settings.readiness_targets.clone();
+    let timeout = targets.check_timeout;
+    let mut schema = None;
+    let (database, grpc_upstream, redis, migrations) = tokio::join!(
+        timed_check("database", timeout, check_database(&pool)),
+        optional_check("grpc_upstream", timeout, targets.grpc_upstream.map(check_tcp)),
+        optional_check("redis", timeout, targets.redis_url.map(check_redis)),
+        timed_check("migrations", timeout, check_migrations(&pool, expected_schema_version(), targets.require_current_schema, &mut schema)),
+    );
+
+    let checks = std::iter::once(database).chain(grpc_upstream).chain(redis).chain(std::iter::once(migrations)).collect();
+    let (code, mut health) = aggregate(checks);
+    health.migrations = schema;
+    probe_response(&headers, code, health.status, health)
+}
+
+/// Build metadata for the running binary.
+///
+/// All values are fixed at compile time by `build.rs`; fields that could not
+/// be determined (e.g. building outside a git checkout) read `"unknown"`.
+#[derive(Serialize, ToSchema)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    pub git_sha: &'static str,
+    pub build_time: &'static str,
+    pub rust_version: &'static str,
+}
+
+impl BuildInfo {
+    /// Build information baked into this binary
+    pub const CURRENT: BuildInfo = BuildInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        git_sha: env!("BUILD_GIT_SHA"),
+        build_time: env!("BUILD_TIME"),
+        rust_version: env!("BUILD_RUST_VERSION"),
     };
-    let code = if db_status == "ok" { axum::http::StatusCode::OK } else { axum::http::StatusCode::INTERNAL_SERVER_ERROR };
-    (code, Json(health))
-} 
\ No newline at end of file
+}
+
+/// Build information endpoint so operators can confirm which build is deployed.
+///
+/// # Returns
+///
+/// * `200 OK` with build information JSON
+///
+/// # Response Example
+///
+/// ```json
+/// {
+///   "version": "0.1.0",
+///   "git_sha": "5a3ab6f",
+///   "build_time": "2024-07-23T10:15:00Z",
+///   "rust_version": "rustc 1.80.0 (051478957 2024-07-21)"
+/// }
+/// ```
+#[utoipa::path(
+    get,
+    path = "/health/info",
+    responses(
+        (status = 200, description = "Build and version information - Rate limit: 300 req/min with 50 burst allowance", body = BuildInfo)
+    ),
+    tag = "System Health & Monitoring"
+)]
+pub async fn info() -> impl IntoResponse {
+    Json(BuildInfo::CURRENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request, routing::get, Router};
+    use crate::test_support::database_url;
+    use sqlx::postgres::PgPoolOptions;
+    use tokio::net::TcpListener;
+    use tower::ServiceExt;
+
+    async fn slow_check(delay: Duration) -> Result<(), String> {
+        tokio::time::sleep(delay).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_checks_run_concurrently_with_individual_timeouts() {
+        let timeout = Duration::from_millis(100);
+        let started = Instant::now();
+        let (database, grpc, redis) = tokio::join!(
+            timed_check("database", timeout, slow_check(Duration::from_millis(10))),
+            timed_check("grpc_upstream", timeout, slow_check(Duration::from_secs(5))),
+            timed_check("redis", timeout, async { Ok(()) }),
+        );
+        // The slow check is cut off at its own timeout without delaying the others
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!(database.status, "ok");
+        assert_eq!(grpc.status, "timeout");
+        assert_eq!(redis.status, "ok");
+
+        let (code, health) = aggregate(vec![database, grpc, redis]);
+        assert_eq!(code, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(health.status, "degraded");
+        assert_eq!(health.database, "ok");
+        assert_eq!(health.error.as_deref(), Some("grpc_upstream: timed out after 100ms"));
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_all_ok() {
+        let timeout = Duration::from_millis(100);
+        let database = timed_check("database", timeout, async { Ok(()) }).await;
+        let failed = timed_check("redis", timeout, async { Err("connection refused".to_string()) }).await;
+        assert_eq!(failed.status, "error");
+
+        let (code, health) = aggregate(vec![database]);
+        assert_eq!(code, StatusCode::OK);
+        assert_eq!(health.status, "ok");
+        assert!(health.error.is_none());
+    }
+
+    #[test]
+    fn test_socket_address_defaults() {
+        assert_eq!(socket_address("http://127.0.0.1:3001", 80).unwrap(), "127.0.0.1:3001");
+        assert_eq!(socket_address("https://grpc.internal", 80).unwrap(), "grpc.internal:443");
+        assert_eq!(socket_address("redis://:secret@cache.internal/0", 6379).unwrap(), "cache.internal:6379");
+        assert!(socket_address("not a url", 80).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ready_returns_503_when_redis_hangs() {
+        // Accepts connections but never answers PING
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hold = tokio::spawn(async move {
+            let mut open = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                open.push(socket);
+            }
+        });
+
+        let targets = ReadinessTargets {
+            grpc_upstream: Some(format!("http://{}", addr)),
+            redis_url: Some(format!("redis://{}", addr)),
+            check_timeout: Duration::from_millis(200),
+            ..ReadinessTargets::default()
+        };
+
+        let pool = PgPoolOptions::new().max_connections(1).connect(&database_url()).await.unwrap();
+        let app = Router::new()
+            .route("/health/ready", get(ready))
+            .with_state(pool)
+            .layer(AppSettings { readiness_targets: targets, ..AppSettings::default() }.layer());
+        let response = app
+            .oneshot(Request::builder().uri("/health/ready").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        hold.abort();
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["status"], "degraded");
+        assert_eq!(json["database"], "ok");
+        let checks = json["checks"].as_array().unwrap();
+        assert_eq!(checks[1]["name"], "grpc_upstream");
+        assert_eq!(checks[1]["status"], "ok");
+        assert_eq!(checks[2]["name"], "redis");
+        assert_eq!(checks[2]["status"], "timeout");
+    }
+
+    #[test]
+    fn test_migration_status_compares_versions() {
+        assert_eq!(MigrationStatus::new(20240809, Some(20240809)).status, "current");
+        assert_eq!(MigrationStatus::new(20240809, Some(20240808)).status, "behind");
+        assert_eq!(MigrationStatus::new(20240808, Some(20240809)).status, "ahead");
+        assert_eq!(MigrationStatus::new(20240809, None).status, "untracked");
+        assert!(MigrationStatus::new(20240809, Some(20240808)).is_behind());
+        assert!(!MigrationStatus::new(20240809, None).is_behind());
+        assert!(expected_schema_version() > 0);
+    }
+
+    /// Single-connection pool with a temporary `_sqlx_migrations` at `applied`,
+    /// shadowing any real one for this connection only
+    async fn pool_with_applied_version(applied: i64) -> PgPool {
+        let pool = PgPoolOptions::new().max_connections(1).connect(&database_url()).await.unwrap();
+        sqlx::query("CREATE TEMP TABLE _sqlx_migrations (version BIGINT PRIMARY KEY, success BOOLEAN NOT NULL)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO _sqlx_migrations (version, success) VALUES ($1, TRUE), ($1 + 1, FALSE)")
+            .bind(applied)
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool
+    }
+
+    async fn ready_json(pool: PgPool, readiness_targets: ReadinessTargets) -> (StatusCode, serde_json::Value) {
+        let app = Router::new()
+            .route("/health/ready", get(ready))
+            .with_state(pool)
+            .layer(AppSettings { readiness_targets, ..AppSettings::default() }.layer());
+        let (status, _, body) = probe(app, "/health/ready", None).await;
+        (status, serde_json::from_str(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_ready_with_matching_migrations() {
+        let expected = expected_schema_version();
+        let (status, json) = ready_json(pool_with_applied_version(expected).await, ReadinessTargets::default()).await;
+        assert_eq!(status, StatusCode::OK);
+        // Failed migrations don't count as applied
+        assert_eq!(json["migrations"], serde_json::json!({"expected_version": expected, "applied_version": expected, "status": "current"}));
+        assert_eq!(json["checks"].as_array().unwrap().last().unwrap()["name"], "migrations");
+    }
+
+    #[tokio::test]
+    async fn test_ready_with_pending_migrations() {
+        let expected = expected_schema_version();
+        let (status, json) = ready_json(pool_with_applied_version(expected - 1).await, ReadinessTargets::default()).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(json["migrations"]["status"], "behind");
+        assert_eq!(json["migrations"]["applied_version"], expected - 1);
+        assert_eq!(
+            json["error"].as_str().unwrap(),
+            format!("migrations: database schema is at version {}, binary expects {}", expected - 1, expected)
+        );
+
+        // Reported but not enforced when the requirement is switched off
+        let targets = ReadinessTargets { require_current_schema: false, ..ReadinessTargets::default() };
+        let (status, json) = ready_json(pool_with_applied_version(expected - 1).await, targets).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["migrations"]["status"], "behind");
+    }
+
+    #[test]
+    fn test_wants_plain_text() {
+        let accept = |value: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(ACCEPT, value.parse().unwrap());
+            wants_plain_text(&headers)
+        };
+        assert!(!wants_plain_text(&HeaderMap::new()));
+        assert!(accept("text/plain"));
+        assert!(accept("TEXT/PLAIN; charset=utf-8"));
+        assert!(accept("text/plain, application/json"));
+        assert!(!accept("application/json, text/plain"));
+        assert!(!accept("*/*"));
+    }
+
+    async fn probe(app: Router, uri: &str, accept: Option<&str>) -> (StatusCode, Option<String>, String) {
+        let mut request = Request::builder().uri(uri);
+        if let Some(accept) = accept {
+            request = request.header(ACCEPT, accept);
+        }
+        let response = app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(axum::http::header::CONTENT_TYPE)
+            .map(|v| v.to_str().unwrap().to_string());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, content_type, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_live_formats() {
+        let app = Router::new().route("/health/live", get(live));
+
+        for accept in [None, Some("application/json")] {
+            let (status, content_type, body) = probe(app.clone(), "/health/live", accept).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(content_type.as_deref(), Some("application/json"));
+            assert_eq!(serde_json::from_str::<serde_json::Value>(&body).unwrap(), serde_json::json!({"status": "ok"}));
+        }
+
+        let (status, content_type, body) = probe(app, "/health/live", Some("text/plain")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(content_type.unwrap().starts_with("text/plain"));
+        assert_eq!(body, "ok");
+    }
+
+    #[tokio::test]
+    async fn test_ready_formats() {
+        let pool = PgPoolOptions::new().max_connections(1).connect(&database_url()).await.unwrap();
+        let app = Router::new()
+            .route("/health/ready", get(ready))
+            .with_state(pool)
+            .layer(AppSettings::default().layer());
+
+        for accept in [None, Some("application/json")] {
+            let (status, content_type, body) = probe(app.clone(), "/health/ready", accept).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(content_type.as_deref(), Some("application/json"));
+            let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+            assert_eq!(json["status"], "ok");
+            assert_eq!(json["database"], "ok");
+        }
+
+        let (status, content_type, body) = probe(app, "/health/ready", Some("text/plain")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(content_type.unwrap().starts_with("text/plain"));
+        assert_eq!(body, "ok");
+    }
+
+    #[tokio::test]
+    async fn test_info_returns_version() {
+        let app = Router::new().route("/health/info", get(info));
+        let response = app
+            .oneshot(Request::builder().uri("/health/info").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(!json["version"].as_str().unwrap().is_empty());
+        assert!(json["git_sha"].is_string());
+        assert!(json["build_time"].is_string());
+        assert!(json["rust_version"].is_string());
+    }
+}
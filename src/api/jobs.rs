@@ -0,0 +1,59 @@
+use axum::{Json, extract::{Path, State}, response::IntoResponse};
+use axum::http::{HeaderMap, StatusCode, header::RETRY_AFTER};
+use sqlx::PgPool;
+use tracing::error;
+use uuid::Uuid;
+use crate::api::auth::{database_error_response, ErrorResponse};
+use crate::api::links::absolute_url;
+use crate::config::settings::AppSettings;
+use crate::infrastructure::jobs;
+use crate::middleware::auth::{AuthenticatedUser, require_admin};
+
+/// Seconds clients are asked to wait between polls of an unfinished job
+pub const JOB_POLL_RETRY_AFTER_SECS: u64 = 1;
+
+/// Status URL of a job, returned in `Location` when it is accepted
+pub fn job_location(settings: &AppSettings, headers: &HeaderMap, id: Uuid) -> String {
+    absolute_url(settings, headers, &format!("/api/v1/jobs/{}", id))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/jobs/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Job ID from the Location header of a 202 response")
+    ),
+    responses(
+        (status = 200, description = "Job state; unfinished jobs include Retry-After - Rate limit: 100 req/min with 10 burst allowance", body = Job),
+        (status = 401, description = "Kitchen authentication required"),
+        (status = 403, description = "Forbidden - only the requester or an admin may view a job", body = ErrorResponse),
+        (status = 404, description = "Job not found", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    ),
+    tag = "System Health & Monitoring",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_job(AuthenticatedUser(user_id): AuthenticatedUser, State(pool): State<PgPool>, Path(id): Path<Uuid>) -> impl IntoResponse {
+    let job = match jobs::find(&pool, id).await {
+        Ok(Some(job)) => job,
+        Ok(None) => return (StatusCode::NOT_FOUND, ErrorResponse::not_found("job")).into_response(),
+        Err(e) => {
+            error!(job_id = %id, error = %e, "Failed to read job");
+            return database_error_response(&e);
+        },
+    };
+
+    if job.requested_by != Some(user_id) {
+        if let Err(response) = require_admin(&pool, user_id).await {
+            return response;
+        }
+    }
+
+    if job.is_finished() {
+        (StatusCode::OK, Json(job)).into_response()
+    } else {
+        (StatusCode::OK, [(RETRY_AFTER, JOB_POLL_RETRY_AFTER_SECS.to_string())], Json(job)).into_response()
+    }
+}
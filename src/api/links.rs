@@ -0,0 +1,110 @@
+//! Absolute links for `Location` and `Link` headers.
+//!
+//! Behind a proxy the server can't tell which scheme and host clients used,
+//! so `PUBLIC_BASE_URL` names it explicitly. Without it the base comes from
+//! the request: `Host`, or `X-Forwarded-Host`/`X-Forwarded-Proto` when
+//! `TRUSTED_PROXY_HOPS` says a proxy sets them, read from the entry that
+//! proxy wrote. With neither, links stay relative as before.
+
+use axum::http::{header::HOST, HeaderMap};
+
+use crate::config::settings::AppSettings;
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok()).map(str::trim).filter(|value| !value.is_empty())
+}
+
+/// The entry our outermost trusted proxy wrote to a forwarded header.
+///
+/// Like `X-Forwarded-For`, each proxy appends, so anything left of the
+/// `trusted_hops`-th entry from the right came from the client. `None` when
+/// the chain is shorter than that, i.e. the request skipped our proxies.
+fn forwarded_header<'a>(headers: &'a HeaderMap, name: &str, trusted_hops: usize) -> Option<&'a str> {
+    let chain: Vec<&str> = headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .collect();
+    chain.len().checked_sub(trusted_hops).map(|index| chain[index])
+}
+
+/// Scheme and host clients reach this server on, e.g. `https://api.example.com`.
+///
+/// `None` when it is neither configured nor derivable from the request.
+pub fn base_url(settings: &AppSettings, headers: &HeaderMap) -> Option<String> {
+    if let Some(url) = &settings.public_base_url {
+        return Some(url.clone());
+    }
+    let hops = settings.trusted_proxy_hops;
+    let forwarded = |name| if hops > 0 { forwarded_header(headers, name, hops) } else { None };
+    let host = forwarded("x-forwarded-host").or_else(|| header(headers, HOST.as_str()))?;
+    let scheme = match forwarded("x-forwarded-proto") {
+        Some(scheme) if scheme.eq_ignore_ascii_case("https") => "https",
+        _ => "http",
+    };
+    Some(format!("{}://{}", scheme, host))
+}
+
+/// `path` made absolute against [`base_url`], or left as is when there is no base
+pub fn absolute_url(settings: &AppSettings, headers: &HeaderMap, path: &str) -> String {
+    match base_url(settings, headers) {
+        Some(base) => format!("{}{}", base, path),
+        None => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn test_configured_base_wins_over_request() {
+        let settings = AppSettings { public_base_url: Some("https://api.kitchen.example.com".to_string()), ..AppSettings::default() };
+        let url = absolute_url(&settings, &headers(&[("host", "10.0.0.5:8080")]), "/api/v1/users/1");
+        assert_eq!(url, "https://api.kitchen.example.com/api/v1/users/1");
+    }
+
+    #[test]
+    fn test_falls_back_to_request_host() {
+        let direct = AppSettings::default();
+        let forwarded = headers(&[("host", "10.0.0.5:8080"), ("x-forwarded-host", "api.kitchen.example.com"), ("x-forwarded-proto", "https")]);
+        assert_eq!(absolute_url(&direct, &forwarded, "/a"), "http://10.0.0.5:8080/a");
+
+        // Forwarded headers only count when a trusted proxy sets them
+        let proxied = AppSettings { trusted_proxy_hops: 1, ..AppSettings::default() };
+        assert_eq!(absolute_url(&proxied, &forwarded, "/a"), "https://api.kitchen.example.com/a");
+
+        assert_eq!(absolute_url(&direct, &HeaderMap::new(), "/a"), "/a");
+    }
+
+    #[test]
+    fn test_ignores_client_supplied_forwarded_entries() {
+        // The client's own values sit left of what our proxies appended
+        let spoofed = headers(&[
+            ("host", "10.0.0.5:8080"),
+            ("x-forwarded-host", "evil.test, api.kitchen.example.com, lb.internal"),
+            ("x-forwarded-proto", "http, https, http"),
+        ]);
+        let one_hop = AppSettings { trusted_proxy_hops: 1, ..AppSettings::default() };
+        assert_eq!(absolute_url(&one_hop, &spoofed, "/a"), "http://lb.internal/a");
+
+        let two_hops = AppSettings { trusted_proxy_hops: 2, ..AppSettings::default() };
+        assert_eq!(absolute_url(&two_hops, &spoofed, "/a"), "https://api.kitchen.example.com/a");
+
+        // A chain shorter than the trusted hops didn't come through our proxies
+        let four_hops = AppSettings { trusted_proxy_hops: 4, ..AppSettings::default() };
+        assert_eq!(absolute_url(&four_hops, &spoofed, "/a"), "http://10.0.0.5:8080/a");
+    }
+}
@@ -1,4 +1,13 @@
+pub mod admin;
+pub mod avatar;
 pub mod health;
+pub mod jobs;
+pub mod links;
 pub mod auth;
+pub mod email_change;
+pub mod error;
+pub mod oauth;
+pub mod pagination;
 pub mod refresh_token;
-pub mod user; 
\ No newline at end of file
+pub mod sessions;
+pub mod user;
\ No newline at end of file
@@ -0,0 +1,393 @@
+//! OAuth2-compatible token endpoint.
+//!
+//! Implements the resource owner password credentials and refresh token grants
+//! from RFC 6749 on top of the regular login flow, for client libraries that
+//! only speak OAuth2. Access tokens are the same JWTs issued by
+//! `/api/v1/auth/login`; refresh tokens are opaque, stored in `refresh_tokens`
+//! and rotated on every use.
+
+use axum::{Extension, Form, Json, extract::{State, rejection::FormRejection}, response::IntoResponse};
+use axum::http::{StatusCode, header};
+use chrono::Utc;
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use crate::api::auth::{authenticate, login_jwt, reissue_jwt, AuthError, ErrorCode, POOL_EXHAUSTED_RETRY_AFTER_SECS};
+use crate::config::settings::AppSettings;
+use crate::core::auth::{LoginRequest, JWT_TTL_SECS};
+use crate::core::email::Email;
+use crate::core::refresh_token::{is_idle_expired, RefreshToken};
+use crate::middleware::client_context::SessionClient;
+
+/// Form body of `POST /api/v1/auth/token`
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TokenRequest {
+    /// `password` or `refresh_token`
+    pub grant_type: String,
+    /// Email address (password grant)
+    pub username: Option<String>,
+    /// Password (password grant)
+    pub password: Option<String>,
+    /// Previously issued refresh token (refresh_token grant)
+    pub refresh_token: Option<String>,
+}
+
+/// Successful token response (RFC 6749 section 5.1)
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OAuthTokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    /// Access token lifetime in seconds
+    pub expires_in: i64,
+    pub refresh_token: String,
+}
+
+/// Error response (RFC 6749 section 5.2)
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OAuthError {
+    pub error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_description: Option<String>,
+}
+
+impl OAuthError {
+    fn new(error: &str, description: impl Into<String>) -> Self {
+        Self { error: error.to_string(), error_description: Some(description.into()) }
+    }
+
+    fn invalid_request(description: impl Into<String>) -> Self {
+        Self::new("invalid_request", description)
+    }
+
+    fn invalid_grant(description: impl Into<String>) -> Self {
+        Self::new("invalid_grant", description)
+    }
+
+    fn server_error() -> Self {
+        Self::new("server_error", "An error occurred while processing your request")
+    }
+
+    fn temporarily_unavailable() -> Self {
+        Self::new("temporarily_unavailable", "The server is overloaded; please retry shortly")
+    }
+
+    fn database(e: &sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::PoolTimedOut => Self::temporarily_unavailable(),
+            _ => Self::server_error(),
+        }
+    }
+}
+
+impl From<AuthError> for OAuthError {
+    fn from(err: AuthError) -> Self {
+        match err {
+            AuthError::Validation(_) | AuthError::Rejected(_) => OAuthError::invalid_request("username and password must be a valid email and password"),
+            AuthError::Standard(e) if e.code() == ErrorCode::InvalidCredentials => OAuthError::invalid_grant("Invalid email or password"),
+            AuthError::Challenge { .. } => OAuthError::invalid_grant("Invalid or expired token"),
+            AuthError::Unavailable(_) => OAuthError::temporarily_unavailable(),
+            AuthError::Standard(_) => OAuthError::server_error(),
+        }
+    }
+}
+
+impl IntoResponse for OAuthError {
+    fn into_response(self) -> axum::response::Response {
+        let status = match self.error.as_str() {
+            "server_error" => StatusCode::INTERNAL_SERVER_ERROR,
+            "temporarily_unavailable" => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::BAD_REQUEST,
+        };
+        let mut response = (status, no_store_headers(), Json(self)).into_response();
+        if status == StatusCode::SERVICE_UNAVAILABLE {
+            response.headers_mut().insert(header::RETRY_AFTER, POOL_EXHAUSTED_RETRY_AFTER_SECS.into());
+        }
+        response
+    }
+}
+
+/// Token responses must never be cached (RFC 6749 section 5.1)
+fn no_store_headers() -> [(header::HeaderName, &'static str); 2] {
+    [(header::CACHE_CONTROL, "no-store"), (header::PRAGMA, "no-cache")]
+}
+
+/// 32 random bytes, hex encoded
+fn generate_refresh_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Stores a new refresh token for `user_id`, issued to `client`, and returns it
+pub(crate) async fn issue_refresh_token<'e, E>(executor: E, user_id: Uuid, client: &SessionClient) -> Result<RefreshToken, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+{
+    let token = RefreshToken::new(user_id, generate_refresh_token());
+    sqlx::query("INSERT INTO refresh_tokens (id, user_id, token, expires_at, created_at, last_used_at, ip_address, user_agent) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)")
+        .bind(token.id)
+        .bind(token.user_id)
+        .bind(&token.token)
+        .bind(token.expires_at)
+        .bind(token.created_at)
+        .bind(token.last_used_at)
+        .bind(client.ip.map(|ip| ip.to_string()))
+        .bind(client.user_agent.as_deref())
+        .execute(executor)
+        .await?;
+    Ok(token)
+}
+
+fn token_response(access_token: String, refresh_token: String) -> axum::response::Response {
+    let body = OAuthTokenResponse {
+        access_token,
+        token_type: "Bearer".to_string(),
+        expires_in: JWT_TTL_SECS,
+        refresh_token,
+    };
+    (StatusCode::OK, no_store_headers(), Json(body)).into_response()
+}
+
+async fn password_grant(pool: &PgPool, client: &SessionClient, settings: &AppSettings, username: Option<String>, password: Option<String>) -> Result<axum::response::Response, OAuthError> {
+    let (Some(username), Some(password)) = (username, password) else {
+        return Err(OAuthError::invalid_request("username and password are required for the password grant"));
+    };
+    let email = Email::parse(&username).map_err(|_| OAuthError::invalid_request("username must be a valid email"))?;
+
+    let user_id = authenticate(pool, LoginRequest { email, password }, settings).await?;
+    let refresh_token = issue_refresh_token(pool, user_id, client).await.map_err(|e| {
+        error!(user_id = %user_id, error = %e, "Failed to store refresh token");
+        OAuthError::database(&e)
+    })?;
+    let access_token = login_jwt(user_id, Some(refresh_token.id), settings)?;
+
+    info!(user_id = %user_id, "OAuth2 password grant succeeded");
+    Ok(token_response(access_token, refresh_token.token))
+}
+
+async fn refresh_token_grant(pool: &PgPool, client: &SessionClient, settings: &AppSettings, refresh_token: Option<String>) -> Result<axum::response::Response, OAuthError> {
+    let Some(refresh_token) = refresh_token.filter(|t| !t.is_empty()) else {
+        return Err(OAuthError::invalid_request("refresh_token is required for the refresh_token grant"));
+    };
+
+    let db_error = |e: sqlx::Error| {
+        error!(error = %e, "Database error during refresh token grant");
+        OAuthError::database(&e)
+    };
+
+    // Deleting the presented token makes it single-use even under concurrent
+    // requests; only one of them gets the row back.
+    let mut tx = pool.begin().await.map_err(db_error)?;
+    let consumed = sqlx::query_as::<_, (Uuid, chrono::DateTime<Utc>, chrono::DateTime<Utc>)>(
+        "DELETE FROM refresh_tokens WHERE token = $1 RETURNING user_id, expires_at, last_used_at",
+    )
+    .bind(&refresh_token)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    // The replacement token starts a fresh idle window, so an active session
+    // keeps rotating while an abandoned one lapses after IDLE_TIMEOUT_DAYS.
+    let now = Utc::now();
+    let user_id = match consumed {
+        Some((user_id, expires_at, _)) if expires_at <= now => {
+            tx.commit().await.map_err(db_error)?;
+            warn!(user_id = %user_id, "Expired refresh token presented");
+            return Err(OAuthError::invalid_grant("Refresh token has expired"));
+        },
+        Some((user_id, _, last_used_at)) if is_idle_expired(last_used_at, now) => {
+            tx.commit().await.map_err(db_error)?;
+            warn!(user_id = %user_id, last_used_at = %last_used_at, "Idle refresh token presented");
+            return Err(OAuthError::invalid_grant("Refresh token has expired due to inactivity"));
+        },
+        Some((user_id, _, _)) => user_id,
+        None => {
+            warn!("Unknown refresh token presented");
+            return Err(OAuthError::invalid_grant("Refresh token is invalid or has been revoked"));
+        },
+    };
+
+    let new_refresh_token = issue_refresh_token(&mut *tx, user_id, client).await.map_err(db_error)?;
+    let access_token = reissue_jwt(user_id, Some(new_refresh_token.id), settings)?;
+    tx.commit().await.map_err(db_error)?;
+
+    info!(user_id = %user_id, "OAuth2 refresh token grant succeeded");
+    Ok(token_response(access_token, new_refresh_token.token))
+}
+
+/// OAuth2 token endpoint supporting the `password` and `refresh_token` grants.
+///
+/// Accepts `application/x-www-form-urlencoded` bodies as required by RFC 6749.
+/// Errors use the OAuth2 `{error, error_description}` shape rather than
+/// `ErrorResponse` so standard client libraries can interpret them.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/token",
+    request_body(content = TokenRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Tokens issued - Rate limit: 10 req/min with 2 burst allowance", body = OAuthTokenResponse),
+        (status = 400, description = "invalid_request, invalid_grant or unsupported_grant_type", body = OAuthError),
+        (status = 500, description = "server_error", body = OAuthError)
+    ),
+    tag = "Kitchen Staff Authentication"
+)]
+pub async fn token(State(pool): State<PgPool>, Extension(settings): Extension<Arc<AppSettings>>, client: SessionClient, form: Result<Form<TokenRequest>, FormRejection>) -> impl IntoResponse {
+    let Form(request) = match form {
+        Ok(form) => form,
+        Err(rejection) => {
+            warn!(error = %rejection, "Malformed token request");
+            return OAuthError::invalid_request(rejection.body_text()).into_response();
+        },
+    };
+
+    info!(grant_type = %request.grant_type, "OAuth2 token request");
+    let result = match request.grant_type.as_str() {
+        "password" => password_grant(&pool, &client, &settings, request.username, request.password).await,
+        "refresh_token" => refresh_token_grant(&pool, &client, &settings, request.refresh_token).await,
+        other => {
+            warn!(grant_type = %other, "Unsupported OAuth2 grant type");
+            Err(OAuthError::new("unsupported_grant_type", format!("Grant type '{}' is not supported", other)))
+        },
+    };
+
+    result.unwrap_or_else(IntoResponse::into_response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::refresh_token::IDLE_TIMEOUT_DAYS;
+    use crate::test_support::{insert_user_with_password, test_pool, unique_email, verify_token};
+    use axum::{Router, body::Body, http::Request, routing::post};
+    use tower::ServiceExt;
+
+    const PASSWORD: &str = "OauthSecret123!";
+
+    async fn post_token(pool: PgPool, form: &[(&str, &str)]) -> (StatusCode, serde_json::Value) {
+        // Test values contain no characters that need percent-encoding
+        let body = form.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("&");
+        let app = Router::new().route("/token", post(token)).with_state(pool).layer(AppSettings::default().layer());
+        let req = Request::builder()
+            .method("POST")
+            .uri("/token")
+            .header("content-type", "application/x-www-form-urlencoded")
+            .body(Body::from(body))
+            .unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        let status = res.status();
+        assert_eq!(res.headers()[header::CACHE_CONTROL], "no-store");
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_password_grant_issues_tokens() {
+        let pool = test_pool().await;
+        let email = unique_email("oauth");
+        let id = insert_user_with_password(&pool, &email, PASSWORD).await;
+
+        let (status, body) = post_token(pool.clone(), &[("grant_type", "password"), ("username", &email), ("password", PASSWORD)]).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["token_type"], "Bearer");
+        assert_eq!(body["expires_in"], JWT_TTL_SECS);
+        assert_eq!(verify_token(body["access_token"].as_str().unwrap()).unwrap().user_id, id);
+
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM refresh_tokens WHERE user_id = $1 AND token = $2")
+            .bind(id)
+            .bind(body["refresh_token"].as_str().unwrap())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(stored, 1);
+
+        let (status, body) = post_token(pool, &[("grant_type", "password"), ("username", &email), ("password", "WrongSecret123!")]).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "invalid_grant");
+    }
+
+    #[tokio::test]
+    async fn test_refresh_token_grant_rotates_token() {
+        let pool = test_pool().await;
+        let email = unique_email("oauth");
+        let id = insert_user_with_password(&pool, &email, PASSWORD).await;
+        let (_, issued) = post_token(pool.clone(), &[("grant_type", "password"), ("username", &email), ("password", PASSWORD)]).await;
+        let original = issued["refresh_token"].as_str().unwrap().to_string();
+
+        let (status, body) = post_token(pool.clone(), &[("grant_type", "refresh_token"), ("refresh_token", &original)]).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(verify_token(body["access_token"].as_str().unwrap()).unwrap().user_id, id);
+        assert_ne!(body["refresh_token"], original.as_str());
+        let session_id: Uuid = sqlx::query_scalar("SELECT id FROM refresh_tokens WHERE token = $1")
+            .bind(body["refresh_token"].as_str().unwrap())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let verified = verify_token(body["access_token"].as_str().unwrap()).unwrap();
+        assert_eq!(verified.session_id, Some(session_id), "the access token names the rotated session");
+
+        // The presented token is single-use
+        let (status, body) = post_token(pool, &[("grant_type", "refresh_token"), ("refresh_token", &original)]).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "invalid_grant");
+    }
+
+    async fn issue_with_last_used(pool: &PgPool, user_id: Uuid, idle_days: i64) -> String {
+        let token = issue_refresh_token(pool, user_id, &SessionClient::default()).await.unwrap().token;
+        sqlx::query("UPDATE refresh_tokens SET last_used_at = NOW() - make_interval(days => $1) WHERE token = $2")
+            .bind(idle_days as i32)
+            .bind(&token)
+            .execute(pool)
+            .await
+            .unwrap();
+        token
+    }
+
+    #[tokio::test]
+    async fn test_refresh_token_grant_rejects_idle_token() {
+        let pool = test_pool().await;
+        let id = insert_user_with_password(&pool, &unique_email("oauth"), PASSWORD).await;
+        let idle = issue_with_last_used(&pool, id, IDLE_TIMEOUT_DAYS + 1).await;
+
+        let (status, body) = post_token(pool.clone(), &[("grant_type", "refresh_token"), ("refresh_token", &idle)]).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "invalid_grant");
+        assert!(body["error_description"].as_str().unwrap().contains("inactivity"));
+
+        // The idle token is consumed rather than left for another attempt
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM refresh_tokens WHERE token = $1")
+            .bind(&idle)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(remaining, 0);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_token_grant_resets_idle_window() {
+        let pool = test_pool().await;
+        let id = insert_user_with_password(&pool, &unique_email("oauth"), PASSWORD).await;
+        let recent = issue_with_last_used(&pool, id, IDLE_TIMEOUT_DAYS - 1).await;
+
+        let (status, body) = post_token(pool.clone(), &[("grant_type", "refresh_token"), ("refresh_token", &recent)]).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let last_used_at: chrono::DateTime<Utc> = sqlx::query_scalar("SELECT last_used_at FROM refresh_tokens WHERE token = $1")
+            .bind(body["refresh_token"].as_str().unwrap())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(Utc::now() - last_used_at < chrono::Duration::minutes(1));
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_grant_type() {
+        let pool = test_pool().await;
+        let (status, body) = post_token(pool, &[("grant_type", "client_credentials")]).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "unsupported_grant_type");
+    }
+}
@@ -0,0 +1,447 @@
+use axum::http::{HeaderValue, Uri};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::{ValidationError, ValidationErrors};
+use crate::api::sessions::SessionSummary;
+use crate::api::user::PublicUser;
+use crate::middleware::validation::ValidationErrorResponse;
+
+/// Default number of rows returned when `limit` is omitted
+pub const DEFAULT_PAGE_SIZE: i64 = 20;
+/// Upper bound for `limit` to keep list queries cheap
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+/// Page size bounds applied to every list endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageLimits {
+    /// Rows returned when `limit` is omitted
+    pub default_size: i64,
+    /// Largest accepted `limit`
+    pub max_size: i64,
+    /// Clamp a `limit` above `max_size` down to it instead of rejecting it
+    pub clamp: bool,
+}
+
+impl PageLimits {
+    pub const fn new(default_size: i64, max_size: i64, clamp: bool) -> Self {
+        Self { default_size, max_size, clamp }
+    }
+}
+
+impl Default for PageLimits {
+    fn default() -> Self {
+        Self::new(DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE, false)
+    }
+}
+
+/// Sort direction for list endpoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    /// Parse an `order` query value (`asc` or `desc`, case-insensitive)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "asc" => Some(Self::Asc),
+            "desc" => Some(Self::Desc),
+            _ => None,
+        }
+    }
+
+    /// SQL keyword for this direction
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            Self::Asc => "ASC",
+            Self::Desc => "DESC",
+        }
+    }
+}
+
+/// Common query parameters accepted by list endpoints.
+///
+/// `sort` is kept as a raw string here; each endpoint maps it through its own
+/// column allowlist so arbitrary input never reaches the SQL text.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListParams {
+    /// Maximum number of rows to return (1 to the configured maximum, 100 by default; default 20)
+    pub limit: Option<i64>,
+    /// Number of rows to skip (default 0)
+    pub offset: Option<i64>,
+    /// Column to sort by
+    pub sort: Option<String>,
+    /// Sort direction: `asc` or `desc`
+    pub order: Option<String>,
+    /// Wrap the result as `{data, meta}` instead of a bare array (default false)
+    pub envelope: Option<bool>,
+}
+
+/// Validated pagination window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page {
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl ListParams {
+    /// Validate `limit`/`offset` against `limits` and fill in defaults
+    pub fn page(&self, limits: PageLimits) -> Result<Page, ValidationErrorResponse> {
+        let mut limit = self.limit.unwrap_or(limits.default_size);
+        let offset = self.offset.unwrap_or(0);
+        if limits.clamp && limit > limits.max_size {
+            limit = limits.max_size;
+        }
+
+        let mut errors = ValidationErrors::new();
+        if !(1..=limits.max_size).contains(&limit) {
+            errors.add("limit", invalid("limit_range", format!("Limit must be between 1 and {}", limits.max_size)));
+        }
+        if offset < 0 {
+            errors.add("offset", invalid("offset_range", "Offset must not be negative".to_string()));
+        }
+
+        if errors.is_empty() {
+            Ok(Page { limit, offset })
+        } else {
+            Err(ValidationErrorResponse::new(errors))
+        }
+    }
+
+    /// Resolve `sort`/`order` through an endpoint-specific column allowlist.
+    ///
+    /// `parse_column` returns `None` for unknown keys, which is reported as a
+    /// validation error rather than silently falling back to the default.
+    pub fn sort_by<C>(&self, parse_column: impl Fn(&str) -> Option<C>, default: (C, SortOrder)) -> Result<(C, SortOrder), ValidationErrorResponse> {
+        let mut errors = ValidationErrors::new();
+
+        let column = match self.sort.as_deref() {
+            None => Some(default.0),
+            Some(raw) => {
+                let parsed = parse_column(raw);
+                if parsed.is_none() {
+                    errors.add("sort", invalid("sort_column", format!("Unsupported sort column '{}'", raw)));
+                }
+                parsed
+            }
+        };
+
+        let order = match self.order.as_deref() {
+            None => Some(default.1),
+            Some(raw) => {
+                let parsed = SortOrder::parse(raw);
+                if parsed.is_none() {
+                    errors.add("order", invalid("sort_order", "Order must be 'asc' or 'desc'".to_string()));
+                }
+                parsed
+            }
+        };
+
+        match (column, order) {
+            (Some(column), Some(order)) if errors.is_empty() => Ok((column, order)),
+            _ => Err(ValidationErrorResponse::new(errors)),
+        }
+    }
+}
+
+/// Pagination metadata returned with enveloped list responses
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ListMeta {
+    /// Total number of matching rows, ignoring `limit`/`offset`
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+    /// Opaque token for the next page, absent on the last page. Pass it back
+    /// as `offset`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+impl ListMeta {
+    pub fn new(page: Page, total: i64) -> Self {
+        let next = page.offset + page.limit;
+        Self {
+            total,
+            limit: page.limit,
+            offset: page.offset,
+            next_cursor: (next < total).then(|| next.to_string()),
+        }
+    }
+}
+
+/// `{data, meta}` wrapper for list endpoints called with `envelope=true`
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[aliases(UserListEnvelope = Envelope<PublicUser>, SessionListEnvelope = Envelope<SessionSummary>)]
+pub struct Envelope<T> {
+    pub data: Vec<T>,
+    pub meta: ListMeta,
+}
+
+impl ListParams {
+    /// Whether the caller asked for an enveloped response
+    pub fn wants_envelope(&self) -> bool {
+        self.envelope.unwrap_or(false)
+    }
+}
+
+/// Build an RFC 5988 `Link` header value for a list page.
+///
+/// Links reuse the request path and every query parameter except
+/// `limit`/`offset`, so sort order and `envelope` carry over between pages.
+/// `prev` is omitted on the first page and `next` on the last. Each link is
+/// prefixed with `base` (see [`base_url`](crate::api::links::base_url)),
+/// which may be empty for relative links.
+pub fn link_header(base: &str, uri: &Uri, page: Page, total: i64) -> Option<HeaderValue> {
+    let preserved: Vec<&str> = uri
+        .query()
+        .unwrap_or("")
+        .split('&')
+        .filter(|pair| {
+            let key = pair.split('=').next().unwrap_or("");
+            !pair.is_empty() && key != "limit" && key != "offset"
+        })
+        .collect();
+    let link = |offset: i64, rel: &str| {
+        let mut query = preserved.clone();
+        let window = format!("limit={}&offset={}", page.limit, offset);
+        query.push(&window);
+        format!("<{}{}?{}>; rel=\"{}\"", base, uri.path(), query.join("&"), rel)
+    };
+
+    let last = ((total - 1).max(0) / page.limit) * page.limit;
+    let mut links = Vec::new();
+    if page.offset + page.limit < total {
+        links.push(link(page.offset + page.limit, "next"));
+    }
+    if page.offset > 0 {
+        links.push(link((page.offset - page.limit).max(0), "prev"));
+    }
+    links.push(link(0, "first"));
+    links.push(link(last, "last"));
+
+    HeaderValue::from_str(&links.join(", ")).ok()
+}
+
+/// `created_at` window for list endpoints, both ends inclusive
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CreatedRangeParams {
+    /// Only rows created at or after this time (RFC 3339)
+    pub created_from: Option<String>,
+    /// Only rows created at or before this time (RFC 3339)
+    pub created_to: Option<String>,
+}
+
+/// Validated `created_at` window; `None` leaves that end open
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CreatedRange {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl CreatedRangeParams {
+    /// Parse both bounds, rejecting malformed timestamps and `created_from > created_to`
+    pub fn range(&self) -> Result<CreatedRange, ValidationErrorResponse> {
+        let mut errors = ValidationErrors::new();
+        let from = parse_timestamp(&mut errors, "created_from", self.created_from.as_deref());
+        let to = parse_timestamp(&mut errors, "created_to", self.created_to.as_deref());
+        if let (Some(from), Some(to)) = (from, to) {
+            if from > to {
+                errors.add("created_to", invalid("date_range", "'created_to' must not be earlier than 'created_from'".to_string()));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(CreatedRange { from, to })
+        } else {
+            Err(ValidationErrorResponse::new(errors))
+        }
+    }
+}
+
+/// `created_at BETWEEN` condition with the bounds bound as `$from` and `$to`;
+/// a NULL bound leaves that end open
+pub fn created_between_sql(from: usize, to: usize) -> String {
+    format!(
+        "created_at BETWEEN COALESCE(${}::timestamptz, '-infinity') AND COALESCE(${}::timestamptz, 'infinity')",
+        from, to
+    )
+}
+
+/// RFC 3339 `raw` as UTC, recording a validation error under `field` when malformed
+pub(crate) fn parse_timestamp(errors: &mut ValidationErrors, field: &'static str, raw: Option<&str>) -> Option<DateTime<Utc>> {
+    let raw = raw?;
+    match DateTime::parse_from_rfc3339(raw) {
+        Ok(at) => Some(at.with_timezone(&Utc)),
+        Err(_) => {
+            errors.add(field, invalid("rfc3339", format!("'{}' is not a valid RFC 3339 timestamp", raw)));
+            None
+        }
+    }
+}
+
+pub(crate) fn invalid(code: &'static str, message: String) -> ValidationError {
+    let mut error = ValidationError::new(code);
+    error.message = Some(message.into());
+    error
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_defaults() {
+        let params = ListParams::default();
+        assert_eq!(params.page(PageLimits::default()).unwrap(), Page { limit: DEFAULT_PAGE_SIZE, offset: 0 });
+    }
+
+    #[test]
+    fn test_page_rejects_out_of_range_values() {
+        let params = ListParams { limit: Some(0), offset: Some(-1), ..Default::default() };
+        let err = params.page(PageLimits::default()).unwrap_err();
+        assert!(err.validation_errors.contains_key("limit"));
+        assert!(err.validation_errors.contains_key("offset"));
+
+        let params = ListParams { limit: Some(MAX_PAGE_SIZE + 1), ..Default::default() };
+        assert!(params.page(PageLimits::default()).is_err());
+    }
+
+    #[test]
+    fn test_page_with_configured_default() {
+        let limits = PageLimits::new(5, 50, false);
+        assert_eq!(ListParams::default().page(limits).unwrap(), Page { limit: 5, offset: 0 });
+    }
+
+    #[test]
+    fn test_page_over_max_rejected_in_reject_mode() {
+        let limits = PageLimits::new(5, 50, false);
+        let params = ListParams { limit: Some(51), ..Default::default() };
+        let err = params.page(limits).unwrap_err();
+        assert!(err.validation_errors.contains_key("limit"));
+
+        let params = ListParams { limit: Some(50), ..Default::default() };
+        assert_eq!(params.page(limits).unwrap().limit, 50);
+    }
+
+    #[test]
+    fn test_page_over_max_clamped_in_clamp_mode() {
+        let limits = PageLimits::new(5, 50, true);
+        let params = ListParams { limit: Some(500), offset: Some(10), ..Default::default() };
+        assert_eq!(params.page(limits).unwrap(), Page { limit: 50, offset: 10 });
+
+        // Clamping only applies to the upper bound
+        let params = ListParams { limit: Some(0), ..Default::default() };
+        assert!(params.page(limits).is_err());
+    }
+
+    #[test]
+    fn test_sort_order_parse() {
+        assert_eq!(SortOrder::parse("asc"), Some(SortOrder::Asc));
+        assert_eq!(SortOrder::parse("DESC"), Some(SortOrder::Desc));
+        assert_eq!(SortOrder::parse("sideways"), None);
+    }
+
+    #[test]
+    fn test_sort_by_unknown_values_rejected() {
+        let parse = |s: &str| if s == "name" { Some("name") } else { None };
+
+        let params = ListParams { sort: Some("password_hash".to_string()), ..Default::default() };
+        let err = params.sort_by(parse, ("name", SortOrder::Desc)).unwrap_err();
+        assert!(err.validation_errors.contains_key("sort"));
+
+        let params = ListParams { order: Some("up".to_string()), ..Default::default() };
+        let err = params.sort_by(parse, ("name", SortOrder::Desc)).unwrap_err();
+        assert!(err.validation_errors.contains_key("order"));
+
+        let params = ListParams::default();
+        assert_eq!(params.sort_by(parse, ("name", SortOrder::Desc)).unwrap(), ("name", SortOrder::Desc));
+    }
+
+    #[test]
+    fn test_list_meta_next_cursor() {
+        let page = Page { limit: 10, offset: 0 };
+        assert_eq!(ListMeta::new(page, 25).next_cursor.as_deref(), Some("10"));
+
+        let last = Page { limit: 10, offset: 20 };
+        assert_eq!(ListMeta::new(last, 25).next_cursor, None);
+        assert_eq!(ListMeta::new(Page { limit: 10, offset: 15 }, 25).next_cursor, None);
+    }
+
+    fn links(uri: &str, page: Page, total: i64) -> String {
+        link_header("", &uri.parse().unwrap(), page, total).unwrap().to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_link_header_first_page() {
+        assert_eq!(
+            links("/api/v1/users?limit=10", Page { limit: 10, offset: 0 }, 25),
+            concat!(
+                "</api/v1/users?limit=10&offset=10>; rel=\"next\", ",
+                "</api/v1/users?limit=10&offset=0>; rel=\"first\", ",
+                "</api/v1/users?limit=10&offset=20>; rel=\"last\""
+            )
+        );
+    }
+
+    #[test]
+    fn test_link_header_middle_page_keeps_other_params() {
+        assert_eq!(
+            links("/api/v1/users?sort=email&offset=10&limit=10&order=asc", Page { limit: 10, offset: 10 }, 25),
+            concat!(
+                "</api/v1/users?sort=email&order=asc&limit=10&offset=20>; rel=\"next\", ",
+                "</api/v1/users?sort=email&order=asc&limit=10&offset=0>; rel=\"prev\", ",
+                "</api/v1/users?sort=email&order=asc&limit=10&offset=0>; rel=\"first\", ",
+                "</api/v1/users?sort=email&order=asc&limit=10&offset=20>; rel=\"last\""
+            )
+        );
+    }
+
+    #[test]
+    fn test_link_header_last_page() {
+        assert_eq!(
+            links("/api/v1/users?limit=10&offset=20", Page { limit: 10, offset: 20 }, 25),
+            concat!(
+                "</api/v1/users?limit=10&offset=10>; rel=\"prev\", ",
+                "</api/v1/users?limit=10&offset=0>; rel=\"first\", ",
+                "</api/v1/users?limit=10&offset=20>; rel=\"last\""
+            )
+        );
+    }
+
+    #[test]
+    fn test_link_header_unaligned_and_empty() {
+        // prev never goes below zero
+        let value = links("/users", Page { limit: 10, offset: 5 }, 25);
+        assert!(value.contains("</users?limit=10&offset=0>; rel=\"prev\""));
+        assert!(value.contains("</users?limit=10&offset=15>; rel=\"next\""));
+
+        // Empty collection: only first and last, both at offset 0
+        assert_eq!(
+            links("/users", Page { limit: 10, offset: 0 }, 0),
+            "</users?limit=10&offset=0>; rel=\"first\", </users?limit=10&offset=0>; rel=\"last\""
+        );
+    }
+
+    #[test]
+    fn test_created_range_validation() {
+        let params = CreatedRangeParams {
+            created_from: Some("2024-03-01T00:00:00Z".to_string()),
+            created_to: Some("2024-03-01T00:00:00+00:00".to_string()),
+        };
+        let range = params.range().unwrap();
+        assert_eq!(range.from, range.to);
+        assert_eq!(CreatedRangeParams::default().range().unwrap(), CreatedRange::default());
+
+        let reversed = CreatedRangeParams {
+            created_from: Some("2024-03-02T00:00:00Z".to_string()),
+            created_to: Some("2024-03-01T00:00:00Z".to_string()),
+        };
+        assert!(reversed.range().unwrap_err().validation_errors.contains_key("created_to"));
+        let malformed = CreatedRangeParams { created_from: Some("last week".to_string()), created_to: None };
+        assert!(malformed.range().unwrap_err().validation_errors.contains_key("created_from"));
+    }
+}
@@ -0,0 +1,421 @@
+//! Session listings.
+//!
+//! A session is a stored refresh token. Listings never expose token values:
+//! only the last four characters are returned, enough to match a session
+//! against a client's own copy.
+
+use axum::{Extension, Json, extract::{OriginalUri, Path, Query, State}, response::IntoResponse};
+use axum::http::{header::LINK, HeaderMap, StatusCode};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use crate::api::auth::{database_error_response, ErrorResponse};
+use crate::api::links::base_url;
+use crate::config::settings::AppSettings;
+use crate::api::pagination::{link_header, Envelope, ListMeta, ListParams, Page, SortOrder};
+use crate::core::refresh_token::IDLE_TIMEOUT_DAYS;
+use crate::middleware::auth::{AuthenticatedToken, AuthenticatedUser, require_admin};
+
+/// An active session with its client metadata
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct SessionSummary {
+    pub id: Uuid,
+    /// Masked refresh token, e.g. `****3f9a`
+    #[schema(example = "****3f9a")]
+    pub token: String,
+    /// Client address the session was issued to, when known
+    pub ip_address: Option<String>,
+    /// `User-Agent` the session was issued to, when sent
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// When the token was last issued or rotated
+    pub last_used_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Selects `$1`'s sessions that are neither expired nor idle for `$2` days
+const ACTIVE_SESSIONS_FILTER: &str =
+    "WHERE user_id = $1 AND expires_at > NOW() AND last_used_at > NOW() - make_interval(days => $2)";
+
+const SESSION_COLUMNS: &str = "id, '****' || right(token, 4) AS token, ip_address, user_agent, created_at, last_used_at, expires_at";
+
+/// Sessions of `user_id` that are neither expired nor idle, most recently used first
+pub async fn active_sessions(pool: &PgPool, user_id: Uuid) -> Result<Vec<SessionSummary>, sqlx::Error> {
+    let query = format!("SELECT {} FROM refresh_tokens {} ORDER BY last_used_at DESC, id", SESSION_COLUMNS, ACTIVE_SESSIONS_FILTER);
+    sqlx::query_as::<_, SessionSummary>(&query)
+        .bind(user_id)
+        .bind(IDLE_TIMEOUT_DAYS as i32)
+        .fetch_all(pool)
+        .await
+}
+
+/// The active session `session_id` of `user_id`, if it hasn't expired or idled out
+pub async fn active_session(pool: &PgPool, user_id: Uuid, session_id: Uuid) -> Result<Option<SessionSummary>, sqlx::Error> {
+    let query = format!("SELECT {} FROM refresh_tokens {} AND id = $3", SESSION_COLUMNS, ACTIVE_SESSIONS_FILTER);
+    sqlx::query_as::<_, SessionSummary>(&query)
+        .bind(user_id)
+        .bind(IDLE_TIMEOUT_DAYS as i32)
+        .bind(session_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// One page of [`active_sessions`] ordered by `last_used_at`, plus the total
+/// number of active sessions
+pub async fn active_sessions_page(pool: &PgPool, user_id: Uuid, page: Page, order: SortOrder) -> Result<(Vec<SessionSummary>, i64), sqlx::Error> {
+    let query = format!(
+        "SELECT {} FROM refresh_tokens {} ORDER BY last_used_at {}, id {} LIMIT $3 OFFSET $4",
+        SESSION_COLUMNS,
+        ACTIVE_SESSIONS_FILTER,
+        order.as_sql(),
+        order.as_sql()
+    );
+    let sessions = sqlx::query_as::<_, SessionSummary>(&query)
+        .bind(user_id)
+        .bind(IDLE_TIMEOUT_DAYS as i32)
+        .bind(page.limit)
+        .bind(page.offset)
+        .fetch_all(pool)
+        .await?;
+    let total = sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM refresh_tokens {}", ACTIVE_SESSIONS_FILTER))
+        .bind(user_id)
+        .bind(IDLE_TIMEOUT_DAYS as i32)
+        .fetch_one(pool)
+        .await?;
+    Ok((sessions, total))
+}
+
+/// The only sort key for session listings
+fn parse_session_sort(raw: &str) -> Option<()> {
+    (raw == "last_used_at").then_some(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/me/sessions",
+    params(ListParams),
+    responses(
+        (status = 200, description = "The caller's active sessions, most recently used first unless `order=asc`. With `envelope=true` the body is a `SessionListEnvelope` carrying the total instead of a bare array", body = [SessionSummary],
+            headers(("Link" = String, description = "RFC 5988 pagination links: next, prev, first and last"))),
+        (status = 422, description = "Invalid pagination or sort parameters", body = ValidationErrorResponse),
+        (status = 401, description = "Kitchen authentication required"),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    ),
+    tag = "Session & Token Management",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_my_sessions(
+    AuthenticatedUser(user_id): AuthenticatedUser,
+    State(pool): State<PgPool>,
+    Extension(settings): Extension<Arc<AppSettings>>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    Query(params): Query<ListParams>,
+) -> impl IntoResponse {
+    let page = match params.page(settings.page_limits) {
+        Ok(page) => page,
+        Err(e) => return e.into_response(),
+    };
+    let ((), order) = match params.sort_by(parse_session_sort, ((), SortOrder::Desc)) {
+        Ok(sort) => sort,
+        Err(e) => return e.into_response(),
+    };
+
+    match active_sessions_page(&pool, user_id, page, order).await {
+        Ok((sessions, total)) => {
+            info!(user_id = %user_id, count = sessions.len(), total, "Listed own sessions");
+            let mut response = if params.wants_envelope() {
+                (StatusCode::OK, Json(Envelope { data: sessions, meta: ListMeta::new(page, total) })).into_response()
+            } else {
+                (StatusCode::OK, Json(sessions)).into_response()
+            };
+            if let Some(links) = link_header(&base_url(&settings, &headers).unwrap_or_default(), &uri, page, total) {
+                response.headers_mut().insert(LINK, links);
+            }
+            response
+        },
+        Err(e) => {
+            error!(user_id = %user_id, error = %e, "Failed to list sessions");
+            database_error_response(&e)
+        },
+    }
+}
+
+/// The session the caller's access token belongs to.
+///
+/// Tokens issued with a refresh token carry its id in the `sid` claim; a
+/// token from a plain login has no session and gets a 404, as does one whose
+/// session has since been rotated away, revoked or expired.
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/sessions/current",
+    responses(
+        (status = 200, description = "The session the access token was issued with - Rate limit: 10 req/min with 2 burst allowance", body = SessionSummary),
+        (status = 401, description = "Kitchen authentication required"),
+        (status = 404, description = "The token has no session, or it is no longer active", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    ),
+    tag = "Session & Token Management",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn current_session(AuthenticatedToken(token): AuthenticatedToken, State(pool): State<PgPool>) -> impl IntoResponse {
+    let Some(session_id) = token.session_id else {
+        warn!(user_id = %token.user_id, "Current session requested with a token that has no session");
+        return (StatusCode::NOT_FOUND, ErrorResponse::not_found("session")).into_response();
+    };
+
+    match active_session(&pool, token.user_id, session_id).await {
+        Ok(Some(session)) => {
+            info!(user_id = %token.user_id, session_id = %session_id, "Fetched current session");
+            (StatusCode::OK, Json(session)).into_response()
+        },
+        Ok(None) => {
+            warn!(user_id = %token.user_id, session_id = %session_id, "Current session is no longer active");
+            (StatusCode::NOT_FOUND, ErrorResponse::not_found("session")).into_response()
+        },
+        Err(e) => {
+            error!(user_id = %token.user_id, error = %e, "Failed to fetch current session");
+            database_error_response(&e)
+        },
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/{id}/sessions",
+    params(
+        ("id" = Uuid, Path, description = "Kitchen staff member whose sessions to list")
+    ),
+    responses(
+        (status = 200, description = "Active sessions, most recently used first - Rate limit: 100 req/min with 10 burst allowance", body = [SessionSummary]),
+        (status = 401, description = "Kitchen authentication required"),
+        (status = 403, description = "Forbidden - admin role required", body = ErrorResponse),
+        (status = 404, description = "Kitchen staff member not found", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse)
+    ),
+    tag = "Session & Token Management",
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_user_sessions(AuthenticatedUser(user_id): AuthenticatedUser, State(pool): State<PgPool>, Path(id): Path<Uuid>) -> impl IntoResponse {
+    if let Err(response) = require_admin(&pool, user_id).await {
+        return response;
+    }
+
+    match sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM users WHERE id = $1)").bind(id).fetch_one(&pool).await {
+        Ok(true) => {},
+        Ok(false) => return (StatusCode::NOT_FOUND, ErrorResponse::not_found("user")).into_response(),
+        Err(e) => {
+            error!(target_user_id = %id, error = %e, "Failed to look up user");
+            return database_error_response(&e);
+        },
+    }
+
+    match active_sessions(&pool, id).await {
+        Ok(sessions) => {
+            info!(authenticated_user_id = %user_id, target_user_id = %id, count = sessions.len(), "Admin listed user sessions");
+            (StatusCode::OK, Json(sessions)).into_response()
+        },
+        Err(e) => {
+            error!(target_user_id = %id, error = %e, "Failed to list sessions");
+            database_error_response(&e)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::oauth::issue_refresh_token;
+    use crate::middleware::client_context::SessionClient;
+    use axum::{body::Body, http::Request, routing::get, Router};
+    use crate::config::settings::AppSettings;
+    use crate::test_support::{access_token, insert_user_with_role, session_token, test_pool};
+    use tower::ServiceExt;
+
+    async fn list_as(pool: PgPool, actor: Uuid, target: Uuid) -> (StatusCode, serde_json::Value) {
+        let token = access_token(actor);
+        let app = Router::new()
+            .route("/api/v1/users/:id/sessions", get(list_user_sessions))
+            .with_state(pool)
+            .layer(AppSettings::default().layer());
+        let req = Request::builder()
+            .uri(format!("/api/v1/users/{}/sessions", target))
+            .header("authorization", format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        let status = res.status();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null))
+    }
+
+    #[tokio::test]
+    async fn test_admin_lists_masked_sessions_with_client_metadata() {
+        let pool = test_pool().await;
+        let admin = insert_user_with_role(&pool, "admin").await;
+        let target = insert_user_with_role(&pool, "user").await;
+        let client = SessionClient { ip: Some("203.0.113.7".parse().unwrap()), user_agent: Some("KitchenTablet/2.1".to_string()) };
+        let token = issue_refresh_token(&pool, target, &client).await.unwrap().token;
+        sqlx::query("INSERT INTO refresh_tokens (user_id, token, expires_at) VALUES ($1, $2, NOW() - INTERVAL '1 day')")
+            .bind(target)
+            .bind(format!("expired-{}", Uuid::new_v4()))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let (status, body) = list_as(pool, admin, target).await;
+        assert_eq!(status, StatusCode::OK);
+        let sessions = body.as_array().unwrap();
+        assert_eq!(sessions.len(), 1, "expired sessions are not listed");
+        assert_eq!(sessions[0]["token"], format!("****{}", &token[token.len() - 4..]));
+        assert_eq!(sessions[0]["ip_address"], "203.0.113.7");
+        assert_eq!(sessions[0]["user_agent"], "KitchenTablet/2.1");
+        assert!(sessions[0]["last_used_at"].is_string());
+    }
+
+    async fn list_own(pool: PgPool, user: Uuid, query: &str) -> (StatusCode, Option<String>, serde_json::Value) {
+        let token = access_token(user);
+        let app = Router::new()
+            .route("/api/v1/users/me/sessions", get(list_my_sessions))
+            .with_state(pool)
+            .layer(AppSettings::default().layer());
+        let req = Request::builder()
+            .uri(format!("/api/v1/users/me/sessions?{}", query))
+            .header("authorization", format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        let status = res.status();
+        let link = res.headers().get(LINK).map(|v| v.to_str().unwrap().to_string());
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        (status, link, serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null))
+    }
+
+    #[tokio::test]
+    async fn test_own_sessions_are_paginated_with_total() {
+        let pool = test_pool().await;
+        let user = insert_user_with_role(&pool, "user").await;
+        let other = insert_user_with_role(&pool, "user").await;
+        for minutes in 0..5 {
+            sqlx::query("INSERT INTO refresh_tokens (user_id, token, expires_at, last_used_at) VALUES ($1, $2, NOW() + INTERVAL '1 day', NOW() - make_interval(mins => $3))")
+                .bind(user)
+                .bind(format!("page-{}-{}", minutes, Uuid::new_v4()))
+                .bind(minutes)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        issue_refresh_token(&pool, other, &SessionClient::default()).await.unwrap();
+
+        let (status, link, body) = list_own(pool.clone(), user, "limit=2&offset=2&envelope=true").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["meta"]["total"], 5, "only the caller's sessions count");
+        assert_eq!(body["meta"]["next_cursor"], "4");
+        let page = body["data"].as_array().unwrap();
+        assert_eq!(page.len(), 2);
+        assert!(page[0]["last_used_at"].as_str() > page[1]["last_used_at"].as_str(), "most recently used first");
+        let link = link.unwrap();
+        assert!(link.contains("limit=2&offset=4>; rel=\"next\"") && link.contains("limit=2&offset=0>; rel=\"prev\""), "{}", link);
+
+        let (_, _, last) = list_own(pool.clone(), user, "limit=2&offset=4").await;
+        assert_eq!(last.as_array().unwrap().len(), 1);
+        let (_, _, oldest) = list_own(pool, user, "limit=1&order=asc&envelope=true").await;
+        assert!(oldest["data"][0]["last_used_at"].as_str() < page[1]["last_used_at"].as_str());
+    }
+
+    #[tokio::test]
+    async fn test_own_sessions_reject_invalid_pagination() {
+        let pool = test_pool().await;
+        let user = insert_user_with_role(&pool, "user").await;
+
+        let (status, _, body) = list_own(pool.clone(), user, "limit=0&offset=-1").await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body["validation_errors"]["limit"].is_array());
+        assert!(body["validation_errors"]["offset"].is_array());
+        let (status, _, _) = list_own(pool, user, "sort=token").await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    async fn get_current(pool: PgPool, token: &str) -> (StatusCode, serde_json::Value) {
+        let app = Router::new()
+            .route("/api/v1/auth/sessions/current", get(current_session))
+            .with_state(pool)
+            .layer(AppSettings::default().layer());
+        let req = Request::builder()
+            .uri("/api/v1/auth/sessions/current")
+            .header("authorization", format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        let status = res.status();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null))
+    }
+
+    #[tokio::test]
+    async fn test_current_session_follows_sid_claim() {
+        let pool = test_pool().await;
+        let user = insert_user_with_role(&pool, "user").await;
+        let client = SessionClient { ip: Some("198.51.100.4".parse().unwrap()), user_agent: Some("PassTerminal/1.0".to_string()) };
+        issue_refresh_token(&pool, user, &SessionClient::default()).await.unwrap();
+        let session = issue_refresh_token(&pool, user, &client).await.unwrap();
+        let token = session_token(user, Some(session.id));
+
+        let (status, body) = get_current(pool.clone(), &token).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["id"], session.id.to_string());
+        assert_eq!(body["token"], format!("****{}", &session.token[session.token.len() - 4..]));
+        assert_eq!(body["ip_address"], "198.51.100.4");
+        assert_eq!(body["user_agent"], "PassTerminal/1.0");
+
+        sqlx::query("DELETE FROM refresh_tokens WHERE id = $1").bind(session.id).execute(&pool).await.unwrap();
+        let (status, body) = get_current(pool, &token).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "a revoked session is no longer current");
+        assert_eq!(body["resource"], "session");
+    }
+
+    #[tokio::test]
+    async fn test_current_session_not_found_without_sid() {
+        let pool = test_pool().await;
+        let user = insert_user_with_role(&pool, "user").await;
+        issue_refresh_token(&pool, user, &SessionClient::default()).await.unwrap();
+        let token = access_token(user);
+
+        let (status, _) = get_current(pool.clone(), &token).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        // A sid naming someone else's session doesn't reveal it
+        let other = insert_user_with_role(&pool, "user").await;
+        let theirs = issue_refresh_token(&pool, other, &SessionClient::default()).await.unwrap();
+        let forged = session_token(user, Some(theirs.id));
+        assert_eq!(get_current(pool, &forged).await.0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_non_admin_cannot_list_sessions() {
+        let pool = test_pool().await;
+        let user = insert_user_with_role(&pool, "user").await;
+
+        let (status, _) = list_as(pool, user, user).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_sessions_of_unknown_user_not_found() {
+        let pool = test_pool().await;
+        let admin = insert_user_with_role(&pool, "admin").await;
+
+        let (status, _) = list_as(pool, admin, Uuid::new_v4()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -1,13 +1,179 @@
-use tracing::{info, debug};
+use tracing::{info, debug, warn};
+use crate::api::pagination::{DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use crate::api::avatar::DEFAULT_MAX_AVATAR_BYTES;
+use crate::middleware::header_limit::DEFAULT_MAX_HEADER_BYTES;
+use crate::core::auth::{JwtKeyRing, DEFAULT_MAX_PASSWORD_BYTES};
+use crate::core::cookie::{CookieAttributes, SameSite};
+use crate::middleware::validation::{DEFAULT_JSON_MAX_ARRAY_LEN, DEFAULT_JSON_MAX_DEPTH};
+use crate::middleware::auth::{is_valid_realm, DEFAULT_AUTH_REALM};
+use crate::api::user::DEFAULT_MAX_BATCH_SIZE;
+use crate::middleware::rate_limit::{IpRange, RateLimitConfig};
+use crate::middleware::rate_limit_configs::{parse_rate_limit_allowlist, parse_route_rate_limits};
+use std::collections::HashMap;
+
+pub mod settings;
 
 const MIN_GRPC_HEALTH_CHECK_INTERVAL_SECS: u64 = 1;
 
+/// Data requests allowed in flight at once before new ones are shed
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 512;
+
+/// Data requests that may wait for a slot once MAX_CONCURRENT_REQUESTS are in flight
+pub const DEFAULT_REQUEST_BUFFER_CAPACITY: usize = 128;
+
+/// How long servers drain in-flight requests after a shutdown signal
+pub const DEFAULT_SHUTDOWN_GRACE: std::time::Duration = std::time::Duration::from_secs(20);
+
+/// Idle time before keep-alive probes start on REST connections
+pub const DEFAULT_TCP_KEEPALIVE: std::time::Duration = std::time::Duration::from_secs(60);
+
 pub struct Config {
     pub server_port: u16,
     pub grpc_upstream_endpoint: String,
     pub grpc_connection_pool_size: usize,
     pub grpc_connection_timeout_secs: u64,
     pub grpc_health_check_interval_secs: u64,
+    /// Origins allowed by CORS; empty means any origin
+    pub cors_allowed_origins: Vec<String>,
+    /// Whether CORS responses set `Access-Control-Allow-Credentials: true`
+    pub cors_allow_credentials: bool,
+    /// Seconds browsers may cache a preflight response; 0 omits the header
+    pub cors_max_age_secs: u64,
+    /// Reverse proxies in front of the service whose `X-Forwarded-For`
+    /// entries are trusted; 0 ignores the header
+    pub trusted_proxy_hops: usize,
+    /// External scheme and host used for absolute links; derived from each request when unset
+    pub public_base_url: Option<String>,
+    /// Requests slower than this many milliseconds are logged; 0 disables
+    pub slow_request_ms: u64,
+    /// Accept cleartext HTTP/2 alongside HTTP/1.1 on the REST listener
+    pub http2_enabled: bool,
+    /// Disable Nagle's algorithm on accepted REST connections
+    pub tcp_nodelay: bool,
+    /// Idle seconds before TCP keep-alive probes start on REST connections; 0 disables
+    pub tcp_keepalive_secs: u64,
+    /// Fail `/health/ready` while the applied migrations are older than the binary expects
+    pub readiness_require_current_schema: bool,
+    /// Sentry DSN; panics and 5xx responses are reported only when set
+    pub sentry_dsn: Option<String>,
+    /// Log redacted request/response bodies at debug level; never on by default
+    pub debug_log_bodies: bool,
+    /// Rows list endpoints return when `limit` is omitted
+    pub default_page_size: i64,
+    /// Largest `limit` list endpoints accept
+    pub max_page_size: i64,
+    /// Clamp an oversized `limit` to `max_page_size` instead of returning 422
+    pub clamp_page_size: bool,
+    /// Most users one batch creation request may contain
+    pub max_batch_size: usize,
+    /// Largest avatar image accepted, in bytes
+    pub max_avatar_bytes: usize,
+    /// Largest combined request header size before a 431; 0 disables
+    pub max_header_bytes: usize,
+    /// Longest password, in bytes, accepted for hashing or verification
+    pub max_password_bytes: usize,
+    /// Deepest nesting of objects and arrays accepted in JSON bodies; 0 disables
+    pub json_max_depth: usize,
+    /// Longest array accepted in JSON bodies; 0 disables
+    pub json_max_array_len: usize,
+    /// Register the gRPC reflection service, which exposes the service schema
+    pub grpc_reflection_enabled: bool,
+    /// Accept gRPC-Web (HTTP/1.1, browser) calls on the gRPC port
+    pub grpc_web_enabled: bool,
+    /// Have registration also issue and return an initial refresh token
+    pub register_issues_refresh_token: bool,
+    /// Have login and registration set the access token in an `HttpOnly` cookie unless `cookie=false` is passed
+    pub auth_cookie_default: bool,
+    /// `SameSite`, `Secure`, `Domain` and `Path` of the access token cookie
+    pub auth_cookie: CookieAttributes,
+    /// Realm named in `WWW-Authenticate` challenges on 401 responses
+    pub auth_realm: String,
+    /// Email domains allowed to register (`*.example.com` for subdomains); empty allows all
+    pub allowed_email_domains: Vec<String>,
+    /// Email domains never allowed to register, checked before the allowlist
+    pub blocked_email_domains: Vec<String>,
+    /// Reject registrations from disposable email domains
+    pub reject_disposable_emails: bool,
+    /// File listing disposable domains, one per line, replacing the bundled list
+    pub disposable_email_domains_file: Option<String>,
+    /// `iss` written to and required in access tokens; unset skips the check
+    pub jwt_issuer: Option<String>,
+    /// `aud` written to and required in access tokens; unset skips the check
+    pub jwt_audience: Option<String>,
+    /// Keys access tokens are signed and verified with, from `APP_AUTH__JWT_*`
+    pub jwt_keys: JwtKeyRing,
+    /// Route the `GET /api/v1/auth/jwt-info` token debugging endpoint; keep off in production
+    pub jwt_info_enabled: bool,
+    /// Seconds to let in-flight requests finish after a shutdown signal before exiting anyway
+    pub shutdown_grace_secs: u64,
+    /// Rate limits for individual route templates, overriding their group's limit
+    pub route_rate_limits: HashMap<String, RateLimitConfig>,
+    /// Client addresses or CIDR blocks exempt from rate limiting
+    pub rate_limit_allowlist: Vec<IpRange>,
+    /// Data requests served at once before new ones get 503; health probes are never counted. 0 disables
+    pub max_concurrent_requests: usize,
+    /// Requests that may wait for a slot once the in-flight limit is reached; 0 sheds immediately
+    pub request_buffer_capacity: usize,
+    /// Add a `Repr-Digest` SHA-256 header over each response body
+    pub repr_digest_enabled: bool,
+    /// Reject cross-site writes authenticated by the access token cookie
+    pub csrf_protection: bool,
+}
+
+impl Default for Config {
+    /// Same values `load` falls back to when no environment is set
+    fn default() -> Self {
+        let server_port = 8080;
+        Self {
+            server_port,
+            grpc_upstream_endpoint: format!("http://127.0.0.1:{}", server_port + 1),
+            grpc_connection_pool_size: 10,
+            grpc_connection_timeout_secs: 30,
+            grpc_health_check_interval_secs: 60,
+            cors_allowed_origins: Vec::new(),
+            cors_allow_credentials: false,
+            cors_max_age_secs: 600,
+            trusted_proxy_hops: 0,
+            public_base_url: None,
+            slow_request_ms: 500,
+            http2_enabled: true,
+            tcp_nodelay: true,
+            tcp_keepalive_secs: DEFAULT_TCP_KEEPALIVE.as_secs(),
+            readiness_require_current_schema: true,
+            sentry_dsn: None,
+            debug_log_bodies: false,
+            default_page_size: DEFAULT_PAGE_SIZE,
+            max_page_size: MAX_PAGE_SIZE,
+            clamp_page_size: false,
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            max_avatar_bytes: DEFAULT_MAX_AVATAR_BYTES,
+            max_header_bytes: DEFAULT_MAX_HEADER_BYTES,
+            max_password_bytes: DEFAULT_MAX_PASSWORD_BYTES,
+            json_max_depth: DEFAULT_JSON_MAX_DEPTH,
+            json_max_array_len: DEFAULT_JSON_MAX_ARRAY_LEN,
+            grpc_reflection_enabled: true,
+            grpc_web_enabled: false,
+            register_issues_refresh_token: false,
+            auth_cookie_default: false,
+            auth_cookie: CookieAttributes::default(),
+            auth_realm: DEFAULT_AUTH_REALM.to_string(),
+            allowed_email_domains: Vec::new(),
+            blocked_email_domains: Vec::new(),
+            reject_disposable_emails: false,
+            disposable_email_domains_file: None,
+            jwt_issuer: None,
+            jwt_audience: None,
+            jwt_keys: JwtKeyRing::default(),
+            jwt_info_enabled: false,
+            shutdown_grace_secs: DEFAULT_SHUTDOWN_GRACE.as_secs(),
+            route_rate_limits: HashMap::new(),
+            rate_limit_allowlist: Vec::new(),
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            request_buffer_capacity: DEFAULT_REQUEST_BUFFER_CAPACITY,
+            repr_digest_enabled: false,
+            csrf_protection: false,
+        }
+    }
 }
 
 pub fn load() -> Config {
@@ -40,12 +206,273 @@ pub fn load() -> Config {
     let grpc_upstream_endpoint = std::env::var("GRPC_UPSTREAM_ENDPOINT")
         .unwrap_or_else(|_| format!("http://127.0.0.1:{}", server_port.saturating_add(1)));
     
+    // Load CORS configuration: comma-separated origin allowlist and credentials flag
+    let cors_allowed_origins = std::env::var("CORS_ALLOWED_ORIGINS")
+        .map(|origins| {
+            origins
+                .split(',')
+                .map(|o| o.trim().to_string())
+                .filter(|o| !o.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    
+    let cors_allow_credentials = std::env::var("CORS_ALLOW_CREDENTIALS")
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+    
+    let cors_max_age_secs = std::env::var("CORS_MAX_AGE_SECS")
+        .ok()
+        .and_then(|p| p.parse::<u64>().ok())
+        .unwrap_or(600); // Default to 10 minutes
+    
+    // Only trust forwarded client addresses when we know how many proxies add them
+    let trusted_proxy_hops = std::env::var("TRUSTED_PROXY_HOPS")
+        .ok()
+        .and_then(|p| p.parse::<usize>().ok())
+        .unwrap_or(0);
+    
+    let public_base_url = std::env::var("PUBLIC_BASE_URL")
+        .ok()
+        .filter(|url| !url.is_empty())
+        .and_then(|url| {
+            if url.starts_with("http://") || url.starts_with("https://") {
+                Some(url.trim_end_matches('/').to_string())
+            } else {
+                warn!(url = %url, "Ignoring PUBLIC_BASE_URL without an http:// or https:// scheme");
+                None
+            }
+        });
+    
+    let slow_request_ms = std::env::var("SLOW_REQUEST_MS")
+        .ok()
+        .and_then(|p| p.parse::<u64>().ok())
+        .unwrap_or(500); // Default to 500 milliseconds
+    
+    let http2_enabled = std::env::var("HTTP2_ENABLED")
+        .map(|v| !matches!(v.to_lowercase().as_str(), "0" | "false" | "no" | "off"))
+        .unwrap_or(true);
+    
+    let tcp_nodelay = std::env::var("TCP_NODELAY")
+        .map(|v| !matches!(v.to_lowercase().as_str(), "0" | "false" | "no" | "off"))
+        .unwrap_or(true);
+    
+    let tcp_keepalive_secs = std::env::var("TCP_KEEPALIVE_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_TCP_KEEPALIVE.as_secs());
+    
+    let readiness_require_current_schema = std::env::var("READINESS_REQUIRE_CURRENT_SCHEMA")
+        .map(|v| !matches!(v.to_lowercase().as_str(), "0" | "false" | "no" | "off"))
+        .unwrap_or(true);
+    
+    let sentry_dsn = std::env::var("SENTRY_DSN").ok().filter(|dsn| !dsn.is_empty());
+    
+    let debug_log_bodies = std::env::var("DEBUG_LOG_BODIES")
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+    
+    let max_page_size = std::env::var("MAX_PAGE_SIZE")
+        .ok()
+        .and_then(|p| p.parse::<i64>().ok().filter(|&n| n >= 1))
+        .unwrap_or(MAX_PAGE_SIZE);
+    
+    let mut default_page_size = std::env::var("DEFAULT_PAGE_SIZE")
+        .ok()
+        .and_then(|p| p.parse::<i64>().ok().filter(|&n| n >= 1))
+        .unwrap_or(DEFAULT_PAGE_SIZE);
+    if default_page_size > max_page_size {
+        warn!(default_page_size, max_page_size, "DEFAULT_PAGE_SIZE exceeds MAX_PAGE_SIZE; using the maximum");
+        default_page_size = max_page_size;
+    }
+    
+    let clamp_page_size = std::env::var("CLAMP_PAGE_SIZE")
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+    
+    let max_batch_size = std::env::var("MAX_BATCH_SIZE")
+        .ok()
+        .and_then(|p| p.parse::<usize>().ok().filter(|&n| n >= 1))
+        .unwrap_or(DEFAULT_MAX_BATCH_SIZE);
+    
+    let max_avatar_bytes = std::env::var("MAX_AVATAR_BYTES")
+        .ok()
+        .and_then(|p| p.parse::<usize>().ok().filter(|&n| n >= 1))
+        .unwrap_or(DEFAULT_MAX_AVATAR_BYTES);
+    
+    let max_password_bytes = std::env::var("MAX_PASSWORD_BYTES")
+        .ok()
+        .and_then(|p| p.parse::<usize>().ok().filter(|&n| n >= 1))
+        .unwrap_or(DEFAULT_MAX_PASSWORD_BYTES);
+    
+    let max_header_bytes = std::env::var("MAX_HEADER_BYTES")
+        .ok()
+        .and_then(|p| p.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MAX_HEADER_BYTES);
+    
+    let json_max_depth = std::env::var("JSON_MAX_DEPTH")
+        .ok()
+        .and_then(|p| p.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_JSON_MAX_DEPTH);
+    
+    let json_max_array_len = std::env::var("JSON_MAX_ARRAY_LEN")
+        .ok()
+        .and_then(|p| p.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_JSON_MAX_ARRAY_LEN);
+    if json_max_array_len != 0 && json_max_array_len < max_batch_size {
+        warn!(json_max_array_len, max_batch_size, "JSON_MAX_ARRAY_LEN is below MAX_BATCH_SIZE; full batches will be rejected");
+    }
+    
+    // Reflection is a development aid; keep it off on Render unless asked for
+    let grpc_reflection_enabled = std::env::var("GRPC_REFLECTION_ENABLED")
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or_else(|_| std::env::var("RENDER").is_err());
+    
+    let grpc_web_enabled = std::env::var("GRPC_WEB_ENABLED")
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+    
+    let register_issues_refresh_token = std::env::var("REGISTER_ISSUES_REFRESH_TOKEN")
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+    
+    let auth_cookie_default = std::env::var("AUTH_COOKIE_DEFAULT")
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+    
+    let auth_cookie = load_auth_cookie();
+    
+    // Goes inside a quoted header parameter, so quotes and control characters are refused
+    let auth_realm = std::env::var("AUTH_REALM")
+        .ok()
+        .filter(|realm| !realm.is_empty())
+        .and_then(|realm| {
+            if is_valid_realm(&realm) {
+                Some(realm)
+            } else {
+                warn!(realm = %realm, "Ignoring AUTH_REALM containing quotes, backslashes or non-printable characters");
+                None
+            }
+        })
+        .unwrap_or_else(|| DEFAULT_AUTH_REALM.to_string());
+    
+    let domain_list = |name: &str| {
+        std::env::var(name)
+            .map(|domains| {
+                domains
+                    .split(',')
+                    .map(|d| d.trim().to_lowercase())
+                    .filter(|d| !d.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let allowed_email_domains: Vec<String> = domain_list("ALLOWED_EMAIL_DOMAINS");
+    let blocked_email_domains: Vec<String> = domain_list("BLOCKED_EMAIL_DOMAINS");
+    
+    let reject_disposable_emails = std::env::var("REJECT_DISPOSABLE_EMAILS")
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+    
+    let disposable_email_domains_file = std::env::var("DISPOSABLE_EMAIL_DOMAINS_FILE")
+        .ok()
+        .filter(|path| !path.trim().is_empty());
+    
+    let jwt_issuer = std::env::var("JWT_ISSUER")
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    
+    let jwt_audience = std::env::var("JWT_AUDIENCE")
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    
+    let jwt_keys = JwtKeyRing::from_env();
+    
+    let jwt_info_enabled = std::env::var("JWT_INFO_ENABLED")
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+    
+    let shutdown_grace_secs = std::env::var("SHUTDOWN_GRACE_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_SHUTDOWN_GRACE.as_secs());
+    
+    let route_rate_limits = std::env::var("ROUTE_RATE_LIMITS")
+        .map(|v| parse_route_rate_limits(&v))
+        .unwrap_or_default();
+    
+    let rate_limit_allowlist = std::env::var("RATE_LIMIT_ALLOWLIST")
+        .map(|v| parse_rate_limit_allowlist(&v))
+        .unwrap_or_default();
+    
+    let max_concurrent_requests = std::env::var("MAX_CONCURRENT_REQUESTS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MAX_CONCURRENT_REQUESTS);
+    
+    let request_buffer_capacity = std::env::var("REQUEST_BUFFER_CAPACITY")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_REQUEST_BUFFER_CAPACITY);
+    
+    let repr_digest_enabled = std::env::var("REPR_DIGEST_ENABLED")
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+    
+    let csrf_protection = std::env::var("CSRF_PROTECTION")
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+    
     let config = Config { 
         server_port,
         grpc_upstream_endpoint,
         grpc_connection_pool_size,
         grpc_connection_timeout_secs,
         grpc_health_check_interval_secs,
+        cors_allowed_origins,
+        cors_allow_credentials,
+        cors_max_age_secs,
+        trusted_proxy_hops,
+        public_base_url,
+        slow_request_ms,
+        http2_enabled,
+        tcp_nodelay,
+        tcp_keepalive_secs,
+        readiness_require_current_schema,
+        sentry_dsn,
+        debug_log_bodies,
+        default_page_size,
+        max_page_size,
+        clamp_page_size,
+        max_batch_size,
+        max_avatar_bytes,
+        max_header_bytes,
+        max_password_bytes,
+        json_max_depth,
+        json_max_array_len,
+        grpc_reflection_enabled,
+        grpc_web_enabled,
+        register_issues_refresh_token,
+        auth_cookie_default,
+        auth_cookie,
+        auth_realm,
+        allowed_email_domains,
+        blocked_email_domains,
+        reject_disposable_emails,
+        disposable_email_domains_file,
+        jwt_issuer,
+        jwt_audience,
+        jwt_keys,
+        jwt_info_enabled,
+        shutdown_grace_secs,
+        route_rate_limits,
+        rate_limit_allowlist,
+        max_concurrent_requests,
+        request_buffer_capacity,
+        repr_digest_enabled,
+        csrf_protection,
     };
     
     info!(
@@ -54,9 +481,79 @@ pub fn load() -> Config {
         grpc_connection_pool_size = config.grpc_connection_pool_size,
         grpc_connection_timeout_secs = config.grpc_connection_timeout_secs,
         grpc_health_check_interval_secs = config.grpc_health_check_interval_secs,
+        cors_allowed_origins = ?config.cors_allowed_origins,
+        cors_allow_credentials = config.cors_allow_credentials,
+        cors_max_age_secs = config.cors_max_age_secs,
+        trusted_proxy_hops = config.trusted_proxy_hops,
+        public_base_url = ?config.public_base_url,
+        slow_request_ms = config.slow_request_ms,
+        http2_enabled = config.http2_enabled,
+        tcp_nodelay = config.tcp_nodelay,
+        tcp_keepalive_secs = config.tcp_keepalive_secs,
+        readiness_require_current_schema = config.readiness_require_current_schema,
+        sentry_enabled = config.sentry_dsn.is_some(),
+        debug_log_bodies = config.debug_log_bodies,
+        default_page_size = config.default_page_size,
+        max_page_size = config.max_page_size,
+        clamp_page_size = config.clamp_page_size,
+        max_batch_size = config.max_batch_size,
+        max_avatar_bytes = config.max_avatar_bytes,
+        max_header_bytes = config.max_header_bytes,
+        max_password_bytes = config.max_password_bytes,
+        json_max_depth = config.json_max_depth,
+        json_max_array_len = config.json_max_array_len,
+        grpc_reflection_enabled = config.grpc_reflection_enabled,
+        grpc_web_enabled = config.grpc_web_enabled,
+        register_issues_refresh_token = config.register_issues_refresh_token,
+        auth_cookie_default = config.auth_cookie_default,
+        auth_cookie = ?config.auth_cookie,
+        auth_realm = config.auth_realm.as_str(),
+        allowed_email_domains = ?config.allowed_email_domains,
+        blocked_email_domains = ?config.blocked_email_domains,
+        reject_disposable_emails = config.reject_disposable_emails,
+        disposable_email_domains_file = ?config.disposable_email_domains_file,
+        jwt_issuer = ?config.jwt_issuer,
+        jwt_audience = ?config.jwt_audience,
+        jwt_kid = config.jwt_keys.current().kid(),
+        jwt_info_enabled = config.jwt_info_enabled,
+        shutdown_grace_secs = config.shutdown_grace_secs,
+        route_rate_limits = ?config.route_rate_limits.keys().collect::<Vec<_>>(),
+        rate_limit_allowlist = ?config.rate_limit_allowlist,
+        max_concurrent_requests = config.max_concurrent_requests,
+        request_buffer_capacity = config.request_buffer_capacity,
+        repr_digest_enabled = config.repr_digest_enabled,
+        csrf_protection = config.csrf_protection,
         "Configuration loaded successfully"
     );
     debug!("Using port and gRPC settings from environment or default values");
     
     config
-}
\ No newline at end of file
+}
+
+/// Access token cookie attributes from `AUTH_COOKIE_SAME_SITE`,
+/// `AUTH_COOKIE_SECURE`, `AUTH_COOKIE_DOMAIN` and `AUTH_COOKIE_PATH`.
+///
+/// A combination browsers would refuse, such as `SameSite=None` without
+/// `Secure`, is ignored as a whole in favour of the defaults.
+fn load_auth_cookie() -> CookieAttributes {
+    let same_site = match std::env::var("AUTH_COOKIE_SAME_SITE") {
+        Ok(raw) => match raw.parse::<SameSite>() {
+            Ok(same_site) => same_site,
+            Err(e) => {
+                warn!(error = %e, "Ignoring invalid AUTH_COOKIE_SAME_SITE");
+                SameSite::default()
+            },
+        },
+        Err(_) => SameSite::default(),
+    };
+    let secure = std::env::var("AUTH_COOKIE_SECURE")
+        .map(|v| !matches!(v.to_lowercase().as_str(), "0" | "false" | "no" | "off"))
+        .unwrap_or(true);
+    let domain = std::env::var("AUTH_COOKIE_DOMAIN").ok().filter(|domain| !domain.is_empty());
+    let path = std::env::var("AUTH_COOKIE_PATH").ok().filter(|path| !path.is_empty()).unwrap_or_else(|| "/".to_string());
+
+    CookieAttributes::new(same_site, secure, domain, path).unwrap_or_else(|e| {
+        warn!(error = %e, "Ignoring AUTH_COOKIE_* settings; using Path=/; Secure; SameSite=Strict");
+        CookieAttributes::default()
+    })
+}
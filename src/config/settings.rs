@@ -0,0 +1,151 @@
+//! Settings read while serving requests.
+//!
+//! Each router built by `app_with_config` carries its own [`AppSettings`] as
+//! an `Arc<AppSettings>` request extension, so two apps built from different
+//! configs in one process don't see each other's values. Handlers take it
+//! with `Extension<Arc<AppSettings>>`; middleware and extractors that only
+//! hold the request read it with [`from_extensions`].
+
+use axum::{
+    http::{Extensions, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use std::sync::Arc;
+use tracing::{error, warn};
+
+use crate::api::health::ReadinessTargets;
+use crate::api::pagination::PageLimits;
+use crate::config::Config;
+use crate::core::auth::{JwtClaimsConfig, JwtKeyRing};
+use crate::core::cookie::CookieAttributes;
+use crate::core::email::{self, EmailDomainPolicy};
+use crate::middleware::rate_limit::IpRange;
+
+/// Per-app values consulted by middleware and handlers
+#[derive(Debug, Clone)]
+pub struct AppSettings {
+    /// Reverse proxies whose `X-Forwarded-For` entries are trusted
+    pub trusted_proxy_hops: usize,
+    /// External scheme and host for absolute links; derived from each
+    /// request when unset
+    pub public_base_url: Option<String>,
+    /// Page size bounds for list endpoints
+    pub page_limits: PageLimits,
+    /// Most users one batch request may create
+    pub max_batch_size: usize,
+    /// Largest avatar image accepted, in bytes
+    pub max_avatar_bytes: usize,
+    /// Combined request header size answered with a 431; 0 disables the check
+    pub max_header_bytes: usize,
+    /// Largest password, in bytes, accepted for hashing or verification
+    pub max_password_bytes: usize,
+    /// Deepest nesting of objects and arrays in a request body; 0 disables the check
+    pub json_max_depth: usize,
+    /// Longest array in a request body; 0 disables the check
+    pub json_max_array_len: usize,
+    /// Whether registration also issues a refresh token
+    pub register_issues_refresh_token: bool,
+    /// Whether `login` and `register` set the access token as a cookie when
+    /// the request doesn't say
+    pub auth_cookie_default: bool,
+    /// `SameSite`, `Secure`, `Domain` and `Path` of the access token cookie
+    pub auth_cookie: CookieAttributes,
+    /// Realm named in `WWW-Authenticate` challenges on 401 responses
+    pub auth_realm: String,
+    /// Email domains `register` accepts
+    pub email_domain_policy: EmailDomainPolicy,
+    /// Issuer and audience written to and required in access tokens
+    pub jwt_claims: JwtClaimsConfig,
+    /// Keys access tokens are signed and verified with
+    pub jwt_keys: JwtKeyRing,
+    /// Client addresses or CIDR blocks exempt from rate limiting
+    pub rate_limit_allowlist: Vec<IpRange>,
+    /// Optional dependencies probed by `/health/ready`
+    pub readiness_targets: ReadinessTargets,
+}
+
+impl AppSettings {
+    /// Settings for an app built with `config`.
+    ///
+    /// Readiness covers only the database and its schema; callers serving gRPC or using
+    /// Redis add those targets themselves.
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            trusted_proxy_hops: config.trusted_proxy_hops,
+            public_base_url: config.public_base_url.clone(),
+            page_limits: PageLimits::new(config.default_page_size, config.max_page_size, config.clamp_page_size),
+            max_batch_size: config.max_batch_size,
+            max_avatar_bytes: config.max_avatar_bytes,
+            max_header_bytes: config.max_header_bytes,
+            max_password_bytes: config.max_password_bytes,
+            json_max_depth: config.json_max_depth,
+            json_max_array_len: config.json_max_array_len,
+            register_issues_refresh_token: config.register_issues_refresh_token,
+            auth_cookie_default: config.auth_cookie_default,
+            auth_cookie: config.auth_cookie.clone(),
+            auth_realm: config.auth_realm.clone(),
+            email_domain_policy: email_domain_policy(config),
+            jwt_claims: JwtClaimsConfig { issuer: config.jwt_issuer.clone(), audience: config.jwt_audience.clone() },
+            jwt_keys: config.jwt_keys.clone(),
+            rate_limit_allowlist: config.rate_limit_allowlist.clone(),
+            readiness_targets: ReadinessTargets {
+                require_current_schema: config.readiness_require_current_schema,
+                ..ReadinessTargets::default()
+            },
+        }
+    }
+
+    /// Layer making these settings a request extension of every route below it
+    pub fn layer(self) -> Extension<Arc<Self>> {
+        Extension(Arc::new(self))
+    }
+}
+
+impl Default for AppSettings {
+    /// Settings of an app built with `Config::default()`
+    fn default() -> Self {
+        Self::from_config(&Config::default())
+    }
+}
+
+/// Email domains allowed to register, optionally excluding disposable ones
+fn email_domain_policy(config: &Config) -> EmailDomainPolicy {
+    let policy = EmailDomainPolicy::new(config.allowed_email_domains.clone(), config.blocked_email_domains.clone());
+    if !config.reject_disposable_emails {
+        return policy;
+    }
+    let path = config.disposable_email_domains_file.as_deref();
+    let domains = email::load_disposable_domains(path).unwrap_or_else(|e| {
+        warn!(path = ?path, error = %e, "Could not read DISPOSABLE_EMAIL_DOMAINS_FILE; using the bundled list");
+        email::parse_domain_list(email::BUNDLED_DISPOSABLE_DOMAINS)
+    });
+    policy.with_disposable_domains(domains)
+}
+
+/// Settings of the app serving a request.
+///
+/// Every router from `app_with_config` installs them, so a request without
+/// them hit a router that was wired up wrong. That is answered with a 500
+/// rather than by quietly serving with defaults.
+#[allow(clippy::result_large_err)]
+pub fn from_extensions(extensions: &Extensions) -> Result<Arc<AppSettings>, Response> {
+    extensions.get::<Arc<AppSettings>>().cloned().ok_or_else(|| {
+        error!("AppSettings extension missing; the router was built without AppSettings::layer");
+        (StatusCode::INTERNAL_SERVER_ERROR, "Missing app settings").into_response()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_extensions_reads_the_installed_settings() {
+        let mut extensions = Extensions::new();
+        assert_eq!(from_extensions(&extensions).unwrap_err().status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        extensions.insert(Arc::new(AppSettings { trusted_proxy_hops: 2, ..AppSettings::default() }));
+        assert_eq!(from_extensions(&extensions).unwrap().trusted_proxy_hops, 2);
+    }
+}
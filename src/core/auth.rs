@@ -50,12 +50,15 @@
 use serde::{Deserialize, Serialize};
 use argon2::Argon2;
 use argon2::password_hash::{SaltString, PasswordHasher, PasswordHash, PasswordVerifier};
-use jsonwebtoken::{encode, decode, Header, Validation, EncodingKey, DecodingKey, Algorithm, TokenData};
+use jsonwebtoken::{encode, decode, decode_header, Header, Validation, EncodingKey, DecodingKey, Algorithm, TokenData};
 use std::env;
+use std::fmt;
+use std::sync::LazyLock;
 use rand_core::OsRng;
 use utoipa::ToSchema;
 use tracing::{info, warn, error, debug};
 use validator::{Validate, ValidationError};
+use crate::core::email::Email;
 use crate::middleware::validation::{ValidatedRequest, InputSanitizer};
 
 /// User registration request structure with comprehensive validation.
@@ -65,9 +68,10 @@ use crate::middleware::validation::{ValidatedRequest, InputSanitizer};
 ///
 /// # Validation Rules
 ///
-/// - **Email**: Must be valid email format (RFC 5322 compliant)
+/// - **Email**: Parsed into an [`Email`] during deserialization
 /// - **Password**: 8-128 characters with strength requirements (mixed case, numbers, symbols)
 /// - **Full Name**: 1-100 characters, required field
+/// - **Display Name**: Optional, 1-100 characters; `full_name` is shown when omitted
 ///
 /// # Security Features
 ///
@@ -79,12 +83,14 @@ use crate::middleware::validation::{ValidatedRequest, InputSanitizer};
 ///
 /// ```rust
 /// use kitchen_api::core::auth::RegisterRequest;
+/// use kitchen_api::core::email::Email;
 /// use validator::Validate;
 ///
 /// let request = RegisterRequest {
-///     email: "chef@restaurant.com".to_string(),
+///     email: Email::parse("chef@restaurant.com").unwrap(),
 ///     password: "SecurePass123!".to_string(),
 ///     full_name: "Head Chef".to_string(),
+///     display_name: None,
 /// };
 ///
 /// // Validate the request
@@ -100,23 +106,31 @@ use crate::middleware::validation::{ValidatedRequest, InputSanitizer};
 /// with appropriate security validation for restaurant environments.
 #[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
 pub struct RegisterRequest {
-    #[validate(email(message = "Invalid email format"))]
-    pub email: String,
+    #[schema(value_type = String, format = "email", example = "chef@restaurant.com")]
+    pub email: Email,
     #[validate(length(min = 8, max = 128, message = "Password must be between 8 and 128 characters"))]
     #[validate(custom(function = "validate_password_strength", message = "Password does not meet security requirements"))]
     pub password: String,
     #[validate(length(min = 1, max = 100, message = "Full name must be between 1 and 100 characters"))]
     pub full_name: String,
+    #[validate(length(min = 1, max = 100, message = "Display name must be between 1 and 100 characters"))]
+    #[serde(default)]
+    #[schema(example = "Chef Marco")]
+    pub display_name: Option<String>,
 }
 
-impl ValidatedRequest for RegisterRequest {}
+impl ValidatedRequest for RegisterRequest {
+    fn password_fields(&self) -> Vec<(&'static str, &str)> {
+        vec![("password", &self.password)]
+    }
+}
 
 impl RegisterRequest {
     /// Sanitizes the request data to prevent XSS and normalize input.
     ///
     /// This method applies appropriate sanitization to each field:
-    /// - Email: Trimmed and converted to lowercase
-    /// - Full name: HTML entities escaped, trimmed
+    /// - Email: Already normalized by [`Email::parse`]
+    /// - Full and display name: HTML entities escaped, trimmed
     /// - Password: Left unchanged to preserve security
     ///
     /// # Security Note
@@ -129,11 +143,13 @@ impl RegisterRequest {
     ///
     /// ```rust
     /// use kitchen_api::core::auth::RegisterRequest;
+    /// use kitchen_api::core::email::Email;
     ///
     /// let mut request = RegisterRequest {
-    ///     email: "  Chef@Restaurant.COM  ".to_string(),
+    ///     email: Email::parse("  Chef@Restaurant.COM  ").unwrap(),
     ///     password: "SecurePass123!".to_string(),
     ///     full_name: "<script>alert('xss')</script>Chef Name".to_string(),
+    ///     display_name: None,
     /// };
     ///
     /// request.sanitize();
@@ -143,8 +159,10 @@ impl RegisterRequest {
     /// assert!(request.full_name.contains("&lt;script&gt;")); // HTML escaped
     /// ```
     pub fn sanitize(&mut self) {
-        self.email = InputSanitizer::sanitize_email(&self.email);
         self.full_name = InputSanitizer::sanitize_text(&self.full_name);
+        if let Some(name) = self.display_name.as_mut() {
+            *name = InputSanitizer::sanitize_text(name);
+        }
         // Note: We don't sanitize password as it should remain as-is for security
     }
 }
@@ -156,7 +174,7 @@ impl RegisterRequest {
 ///
 /// # Validation Rules
 ///
-/// - **Email**: Must be valid email format
+/// - **Email**: Parsed into an [`Email`] during deserialization
 /// - **Password**: Required field (minimum 1 character)
 ///
 /// # Security Features
@@ -169,10 +187,11 @@ impl RegisterRequest {
 ///
 /// ```rust
 /// use kitchen_api::core::auth::LoginRequest;
+/// use kitchen_api::core::email::Email;
 /// use validator::Validate;
 ///
 /// let request = LoginRequest {
-///     email: "chef@restaurant.com".to_string(),
+///     email: Email::parse("chef@restaurant.com").unwrap(),
 ///     password: "SecurePass123!".to_string(),
 /// };
 ///
@@ -189,24 +208,69 @@ impl RegisterRequest {
 /// including order management, inventory tracking, and shift coordination.
 #[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
 pub struct LoginRequest {
-    #[validate(email(message = "Invalid email format"))]
-    pub email: String,
+    #[schema(value_type = String, format = "email", example = "chef@restaurant.com")]
+    pub email: Email,
     #[validate(length(min = 1, message = "Password is required"))]
     pub password: String,
 }
 
-impl ValidatedRequest for LoginRequest {}
+impl ValidatedRequest for LoginRequest {
+    fn password_fields(&self) -> Vec<(&'static str, &str)> {
+        vec![("password", &self.password)]
+    }
+}
 
-impl LoginRequest {
-    /// Sanitize the request data
-    pub fn sanitize(&mut self) {
-        self.email = InputSanitizer::sanitize_email(&self.email);
-        // Note: We don't sanitize password as it should remain as-is for security
+
+/// Password change request for an already authenticated user.
+///
+/// The current password is re-verified before the change is applied, and the
+/// new password must satisfy the same strength policy as registration.
+///
+/// # Examples
+///
+/// ```rust
+/// use kitchen_api::core::auth::ChangePasswordRequest;
+/// use validator::Validate;
+///
+/// let request = ChangePasswordRequest {
+///     current_password: "SecurePass123!".to_string(),
+///     new_password: "EvenSaferPass456!".to_string(),
+/// };
+///
+/// assert!(request.validate().is_ok());
+/// ```
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct ChangePasswordRequest {
+    #[validate(length(min = 1, message = "Current password is required"))]
+    pub current_password: String,
+    #[validate(length(min = 8, max = 128, message = "Password must be between 8 and 128 characters"))]
+    #[validate(custom(function = "validate_password_strength", message = "Password does not meet security requirements"))]
+    pub new_password: String,
+}
+
+impl ValidatedRequest for ChangePasswordRequest {
+    fn password_fields(&self) -> Vec<(&'static str, &str)> {
+        vec![("current_password", &self.current_password), ("new_password", &self.new_password)]
+    }
+}
+
+/// Default cap on the size of a password accepted for hashing or verification
+pub const DEFAULT_MAX_PASSWORD_BYTES: usize = 256;
+
+/// Rejects passwords over `max` bytes, so oversized input is answered with
+/// 422 before Argon2 sees it; hashing huge inputs is cheap to request and
+/// costly to serve
+pub(crate) fn validate_password_size(password: &str, max: usize) -> Result<(), ValidationError> {
+    if password.len() <= max {
+        return Ok(());
     }
+    let mut error = ValidationError::new("password_too_long");
+    error.message = Some(format!("Password must be at most {} bytes", max).into());
+    Err(error)
 }
 
 /// Custom validator for password strength
-fn validate_password_strength(password: &str) -> Result<(), ValidationError> {
+pub(crate) fn validate_password_strength(password: &str) -> Result<(), ValidationError> {
     match InputSanitizer::validate_password_strength(password) {
         Ok(()) => Ok(()),
         Err(errors) => {
@@ -225,16 +289,116 @@ pub struct UserProfile {
     pub preferences: Option<UserPreferences>,
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+/// User preferences with typed known fields.
+///
+/// Keys this version doesn't know about are kept in `extra` and written back
+/// unchanged, so newer clients can store settings without a server release.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
 pub struct UserPreferences {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub theme: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub notifications: Option<bool>,
+    /// Unrecognised preference keys, preserved as-is
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl UserPreferences {
+    /// Theme reported when the user hasn't picked one
+    pub const DEFAULT_THEME: &'static str = "light";
+    /// Whether notifications are on when the user hasn't said
+    pub const DEFAULT_NOTIFICATIONS: bool = true;
+
+    /// Fill known keys the user hasn't set with their defaults.
+    ///
+    /// A stored value of the wrong type for a known key is replaced by the
+    /// default, so the key is never emitted twice.
+    pub fn with_defaults(mut self) -> Self {
+        if self.theme.is_none() {
+            self.extra.remove("theme");
+            self.theme = Some(Self::DEFAULT_THEME.to_string());
+        }
+        if self.notifications.is_none() {
+            self.extra.remove("notifications");
+            self.notifications = Some(Self::DEFAULT_NOTIFICATIONS);
+        }
+        self
+    }
+
+    /// Build from the stored JSON document.
+    ///
+    /// Known keys holding an unexpected type are kept in `extra` rather than
+    /// failing the whole document. Returns `None` for non-object values.
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        let mut extra = value.as_object()?.clone();
+        let theme = match extra.get("theme") {
+            Some(serde_json::Value::String(theme)) => Some(theme.clone()),
+            _ => None,
+        };
+        if theme.is_some() {
+            extra.remove("theme");
+        }
+        let notifications = extra.get("notifications").and_then(serde_json::Value::as_bool);
+        if notifications.is_some() {
+            extra.remove("notifications");
+        }
+        Some(UserPreferences { theme, notifications, extra })
+    }
 }
 
+/// Lifetime of an access token issued by [`create_jwt`], in seconds
+pub const JWT_TTL_SECS: i64 = 24 * 60 * 60;
+
+/// Cookie carrying the access token for clients that opt into cookie delivery
+pub const ACCESS_TOKEN_COOKIE: &str = "access_token";
+
 #[derive(Debug, Serialize, Deserialize)]
 struct Claims {
     sub: String,
     exp: usize,
+    /// Fractional seconds, so a logout cutoff in the same second as a later
+    /// login still tells the two apart
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    iat: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    iss: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    aud: Option<String>,
+    /// Session (stored refresh token) the access token was issued with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sid: Option<String>,
+}
+
+/// Issuer and audience of access tokens.
+///
+/// A configured value is written to new tokens and required when verifying,
+/// so tokens minted for another service (or before the value was set) are
+/// rejected. Unset values are neither written nor checked.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JwtClaimsConfig {
+    pub issuer: Option<String>,
+    pub audience: Option<String>,
+}
+
+impl JwtClaimsConfig {
+    fn validation(&self, algorithm: Algorithm) -> Validation {
+        let mut validation = Validation::new(algorithm);
+        // Only the key's algorithm; whatever else the token header names fails
+        validation.algorithms = vec![algorithm];
+        let mut required = vec!["exp"];
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+            required.push("iss");
+        }
+        if let Some(audience) = &self.audience {
+            validation.set_audience(&[audience]);
+            required.push("aud");
+        }
+        validation.set_required_spec_claims(&required);
+        validation
+    }
 }
 
 /// Hashes a password using Argon2 with a random salt.
@@ -304,6 +468,12 @@ pub fn hash_password(password: &str) -> anyhow::Result<String> {
     Ok(hash)
 }
 
+/// Runs [`hash_password`] on the blocking thread pool, so hashing many
+/// passwords doesn't stall the async workers serving other requests
+pub async fn hash_password_blocking(password: String) -> anyhow::Result<String> {
+    tokio::task::spawn_blocking(move || hash_password(&password)).await?
+}
+
 /// Verifies a password against its Argon2 hash.
 ///
 /// This function takes a plaintext password and compares it against
@@ -366,6 +536,8 @@ pub fn verify_password(password: &str, hash: &str) -> bool {
     debug!("Starting password verification");
     let parsed_hash = PasswordHash::new(hash);
     if let Ok(parsed_hash) = parsed_hash {
+        #[cfg(test)]
+        ARGON2_VERIFICATIONS.with(|count| count.set(count.get() + 1));
         let verification_result = Argon2::default().verify_password(password.as_bytes(), &parsed_hash).is_ok();
         if verification_result {
             debug!("Password verification successful");
@@ -379,6 +551,324 @@ pub fn verify_password(password: &str, hash: &str) -> bool {
     }
 }
 
+/// Hash of a random password, checked against when no account matches so
+/// unknown and known emails cost the same Argon2 work
+static DUMMY_PASSWORD_HASH: LazyLock<String> = LazyLock::new(|| {
+    let password = SaltString::generate(&mut OsRng);
+    hash_password(password.as_str()).expect("hashing a random password cannot fail")
+});
+
+/// Compute the dummy hash now, so the first login for an unknown email
+/// doesn't also pay for hashing and stand out by its timing
+pub fn init_dummy_password_hash() {
+    LazyLock::force(&DUMMY_PASSWORD_HASH);
+}
+
+/// Run a verification that always fails, taking as long as a real one.
+///
+/// Login calls this when the email is unknown; returning early instead would
+/// let response times reveal which emails have accounts.
+pub fn verify_dummy_password(password: &str) {
+    let _ = verify_password(password, &DUMMY_PASSWORD_HASH);
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Argon2 verifications run on this thread, so tests can tell a login
+    /// path did the work without timing it
+    static ARGON2_VERIFICATIONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Argon2 verifications run so far on the current thread
+#[cfg(test)]
+pub(crate) fn argon2_verifications() -> usize {
+    ARGON2_VERIFICATIONS.with(|count| count.get())
+}
+
+/// Key id used when `APP_AUTH__JWT_KID` is not set
+const DEFAULT_JWT_KID: &str = "default";
+
+/// Secret signing tokens when `APP_AUTH__JWT_SECRET` is not set; only fit for development
+pub(crate) const DEFAULT_JWT_SECRET: &str = "mysecretkeymysecretkeymysecretkey12";
+
+/// Signing material for a key; the algorithm follows from the variant
+#[derive(Clone)]
+enum JwtKeyMaterial {
+    /// Shared HMAC secret (HS256)
+    Hmac(String),
+    /// RSA key in PEM form (RS256); verification only needs the public half
+    Rsa { private_pem: Option<String>, public_pem: String },
+    /// The configured algorithm can't be set up, for the given reason. Signing
+    /// and verifying both fail rather than falling back to another key.
+    Unusable(String),
+}
+
+impl fmt::Debug for JwtKeyMaterial {
+    /// Names the algorithm only, so settings can be logged without leaking secrets
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JwtKeyMaterial::Hmac(_) => f.write_str("Hmac(..)"),
+            JwtKeyMaterial::Rsa { private_pem, .. } => f.debug_struct("Rsa").field("can_sign", &private_pem.is_some()).finish(),
+            JwtKeyMaterial::Unusable(reason) => f.debug_tuple("Unusable").field(reason).finish(),
+        }
+    }
+}
+
+/// A named signing key
+#[derive(Debug, Clone)]
+pub struct JwtKey {
+    kid: String,
+    material: JwtKeyMaterial,
+}
+
+impl JwtKey {
+    /// HS256 key from a shared secret
+    pub fn hmac(kid: impl Into<String>, secret: impl Into<String>) -> Self {
+        Self { kid: kid.into(), material: JwtKeyMaterial::Hmac(secret.into()) }
+    }
+
+    /// RS256 key from PEMs; without the private half it can only verify
+    pub fn rsa(kid: impl Into<String>, private_pem: Option<String>, public_pem: impl Into<String>) -> Self {
+        Self { kid: kid.into(), material: JwtKeyMaterial::Rsa { private_pem, public_pem: public_pem.into() } }
+    }
+
+    /// Key id written to the `kid` header of tokens this key signs
+    pub fn kid(&self) -> &str {
+        &self.kid
+    }
+
+    fn algorithm(&self) -> Algorithm {
+        match self.material {
+            JwtKeyMaterial::Hmac(_) | JwtKeyMaterial::Unusable(_) => Algorithm::HS256,
+            JwtKeyMaterial::Rsa { .. } => Algorithm::RS256,
+        }
+    }
+
+    fn encoding_key(&self) -> anyhow::Result<EncodingKey> {
+        match &self.material {
+            JwtKeyMaterial::Hmac(secret) => Ok(EncodingKey::from_secret(secret.as_bytes())),
+            JwtKeyMaterial::Rsa { private_pem: Some(pem), .. } => Ok(EncodingKey::from_rsa_pem(pem.as_bytes())?),
+            JwtKeyMaterial::Rsa { private_pem: None, .. } => Err(anyhow::anyhow!("no private key for JWT key id {}", self.kid)),
+            JwtKeyMaterial::Unusable(reason) => Err(anyhow::anyhow!("JWT signing is misconfigured: {}", reason)),
+        }
+    }
+
+    fn decoding_key(&self) -> anyhow::Result<DecodingKey> {
+        match &self.material {
+            JwtKeyMaterial::Hmac(secret) => Ok(DecodingKey::from_secret(secret.as_bytes())),
+            JwtKeyMaterial::Rsa { public_pem, .. } => Ok(DecodingKey::from_rsa_pem(public_pem.as_bytes())?),
+            JwtKeyMaterial::Unusable(reason) => Err(anyhow::anyhow!("JWT signing is misconfigured: {}", reason)),
+        }
+    }
+
+    /// Public JWK for RSA keys; HMAC secrets are never published
+    fn to_jwk(&self) -> anyhow::Result<Option<JsonWebKey>> {
+        use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+        use rsa::pkcs1::DecodeRsaPublicKey;
+        use rsa::pkcs8::DecodePublicKey;
+        use rsa::traits::PublicKeyParts;
+
+        let public_pem = match &self.material {
+            JwtKeyMaterial::Rsa { public_pem, .. } => public_pem,
+            JwtKeyMaterial::Hmac(_) => return Ok(None),
+            JwtKeyMaterial::Unusable(reason) => return Err(anyhow::anyhow!("JWT signing is misconfigured: {}", reason)),
+        };
+        let public_key = rsa::RsaPublicKey::from_public_key_pem(public_pem)
+            .or_else(|_| rsa::RsaPublicKey::from_pkcs1_pem(public_pem))
+            .map_err(|e| anyhow::anyhow!("invalid RSA public key for JWT key id {}: {}", self.kid, e))?;
+        Ok(Some(JsonWebKey {
+            kty: "RSA".to_string(),
+            key_use: "sig".to_string(),
+            alg: "RS256".to_string(),
+            kid: self.kid.clone(),
+            n: URL_SAFE_NO_PAD.encode(public_key.n().to_bytes_be()),
+            e: URL_SAFE_NO_PAD.encode(public_key.e().to_bytes_be()),
+        }))
+    }
+}
+
+/// Signing keys currently accepted for JWT verification.
+///
+/// New tokens are always signed with `current`. `APP_AUTH__JWT_ALGORITHM`
+/// selects `HS256` (default, `APP_AUTH__JWT_SECRET`) or `RS256`
+/// (`APP_AUTH__JWT_PRIVATE_KEY` and `APP_AUTH__JWT_PUBLIC_KEY`, PEM).
+///
+/// During a rotation the previous key is configured via
+/// `APP_AUTH__JWT_PREVIOUS_KID` plus either `APP_AUTH__JWT_PREVIOUS_PUBLIC_KEY`
+/// or `APP_AUTH__JWT_PREVIOUS_SECRET`, and stays valid for verification until
+/// `APP_AUTH__JWT_PREVIOUS_VALID_UNTIL` (RFC 3339). When that is unset it
+/// stays valid for [`JWT_TTL_SECS`] after the ring is loaded, long enough for
+/// every token it signed to expire.
+///
+/// The ring is read once, when the configuration is loaded, and reaches
+/// handlers through [`AppSettings`](crate::config::settings::AppSettings);
+/// rotating keys takes a restart. Tests and callers that manage keys
+/// themselves build one with [`JwtKeyRing::new`].
+#[derive(Debug, Clone)]
+pub struct JwtKeyRing {
+    current: JwtKey,
+    previous: Option<JwtKey>,
+    /// When `previous` stops being accepted; `None` keeps it for as long as
+    /// it's configured, which only rings built with [`JwtKeyRing::new`] do
+    previous_valid_until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<JwtKey> for JwtKeyRing {
+    fn from(current: JwtKey) -> Self {
+        Self::new(current, None)
+    }
+}
+
+impl Default for JwtKeyRing {
+    /// HS256 with the built-in development secret, as when nothing is configured
+    fn default() -> Self {
+        Self::from(JwtKey::hmac(DEFAULT_JWT_KID, DEFAULT_JWT_SECRET))
+    }
+}
+
+impl JwtKeyRing {
+    /// Ring signing with `current` and also accepting `previous`, if given.
+    /// A previous key sharing the current kid is ignored.
+    pub fn new(current: JwtKey, previous: Option<JwtKey>) -> Self {
+        let previous = previous.filter(|key| key.kid != current.kid);
+        Self { current, previous, previous_valid_until: None }
+    }
+
+    /// Key new tokens are signed with
+    pub fn current(&self) -> &JwtKey {
+        &self.current
+    }
+
+    /// Whether tokens are signed with the built-in development secret
+    pub fn uses_default_secret(&self) -> bool {
+        matches!(&self.current.material, JwtKeyMaterial::Hmac(secret) if secret == DEFAULT_JWT_SECRET)
+    }
+
+    /// Load keys from `APP_AUTH__JWT_*` environment variables.
+    ///
+    /// An algorithm that can't be set up (`RS256` without both PEMs, or an
+    /// unknown name) yields a key that refuses to sign or verify, never a
+    /// fallback to HS256 and its default secret.
+    pub fn from_env() -> Self {
+        Self::from_vars(|name| env::var(name).ok())
+    }
+
+    /// Load keys like [`from_env`](Self::from_env), looking variables up with `var`
+    pub(crate) fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        // PEMs may use `\n` escapes so keys fit on one line
+        let pem = |name: &str| var(name).map(|pem| pem.replace("\\n", "\n"));
+        let kid = var("APP_AUTH__JWT_KID").unwrap_or_else(|| DEFAULT_JWT_KID.to_string());
+        let material = match var("APP_AUTH__JWT_ALGORITHM").as_deref() {
+            Some("RS256") => match (pem("APP_AUTH__JWT_PRIVATE_KEY"), pem("APP_AUTH__JWT_PUBLIC_KEY")) {
+                (private_pem @ Some(_), Some(public_pem)) => JwtKeyMaterial::Rsa { private_pem, public_pem },
+                _ => {
+                    error!("RS256 requires APP_AUTH__JWT_PRIVATE_KEY and APP_AUTH__JWT_PUBLIC_KEY; refusing to sign tokens");
+                    JwtKeyMaterial::Unusable("RS256 requires APP_AUTH__JWT_PRIVATE_KEY and APP_AUTH__JWT_PUBLIC_KEY".to_string())
+                }
+            },
+            None | Some("HS256") => JwtKeyMaterial::Hmac(var("APP_AUTH__JWT_SECRET").unwrap_or_else(|| {
+                warn!("JWT secret not found in environment, using default");
+                DEFAULT_JWT_SECRET.to_string()
+            })),
+            Some(other) => {
+                error!(algorithm = %other, "Unsupported JWT algorithm; refusing to sign tokens");
+                JwtKeyMaterial::Unusable(format!("unsupported APP_AUTH__JWT_ALGORITHM {}", other))
+            }
+        };
+        let current = JwtKey { kid, material };
+
+        let previous_material = pem("APP_AUTH__JWT_PREVIOUS_PUBLIC_KEY")
+            .map(|public_pem| JwtKeyMaterial::Rsa { private_pem: None, public_pem })
+            .or_else(|| var("APP_AUTH__JWT_PREVIOUS_SECRET").map(JwtKeyMaterial::Hmac));
+        let previous = match (previous_material, var("APP_AUTH__JWT_PREVIOUS_KID")) {
+            (Some(material), Some(kid)) => Some(JwtKey { kid, material }),
+            _ => None,
+        };
+        let configured_until = var("APP_AUTH__JWT_PREVIOUS_VALID_UNTIL").and_then(|raw| match chrono::DateTime::parse_from_rfc3339(&raw) {
+            Ok(until) => Some(until.with_timezone(&chrono::Utc)),
+            Err(e) => {
+                warn!(error = %e, "Ignoring invalid APP_AUTH__JWT_PREVIOUS_VALID_UNTIL");
+                None
+            }
+        });
+        // Tokens the previous key signed expire within one TTL of the rotation,
+        // so without an explicit end the window closes once they all have
+        let previous_valid_until = previous.as_ref().map(|_| {
+            configured_until.unwrap_or_else(|| chrono::Utc::now() + chrono::Duration::seconds(JWT_TTL_SECS))
+        });
+
+        Self { previous_valid_until, ..Self::new(current, previous) }
+    }
+
+    /// Previous key, while its rotation window is open
+    fn previous(&self) -> Option<&JwtKey> {
+        let open = self.previous_valid_until.is_none_or(|until| chrono::Utc::now() <= until);
+        if !open {
+            debug!(kid = ?self.previous.as_ref().map(|key| &key.kid), "Previous JWT key rotation window has closed");
+        }
+        self.previous.as_ref().filter(|_| open)
+    }
+
+    /// Key for a token's `kid`; tokens without one predate rotation and use the current key
+    fn key_for(&self, kid: Option<&str>) -> Option<&JwtKey> {
+        match kid {
+            None => Some(&self.current),
+            Some(kid) if kid == self.current.kid => Some(&self.current),
+            Some(kid) => self.previous().filter(|key| key.kid == kid),
+        }
+    }
+}
+
+/// A public signing key in JWK format (RFC 7517)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct JsonWebKey {
+    /// Key type, always `RSA`
+    #[schema(example = "RSA")]
+    pub kty: String,
+    /// Intended use, always `sig`
+    #[serde(rename = "use")]
+    #[schema(example = "sig")]
+    pub key_use: String,
+    #[schema(example = "RS256")]
+    pub alg: String,
+    /// Matches the `kid` header of tokens signed with this key
+    pub kid: String,
+    /// Base64url-encoded modulus
+    pub n: String,
+    /// Base64url-encoded public exponent
+    #[schema(example = "AQAB")]
+    pub e: String,
+}
+
+/// JWK Set served at `/.well-known/jwks.json`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct JsonWebKeySet {
+    pub keys: Vec<JsonWebKey>,
+}
+
+/// Public keys external verifiers can use to check our tokens.
+///
+/// Contains the current RS256 key and, during a rotation, the previous one.
+/// Empty when tokens are signed with HS256, since a shared secret cannot be
+/// published. Keys that fail to parse are logged and left out; an unusable
+/// signing configuration is an error, since no published set would be right.
+pub fn jwks(keys: &JwtKeyRing) -> anyhow::Result<JsonWebKeySet> {
+    if let JwtKeyMaterial::Unusable(reason) = &keys.current.material {
+        return Err(anyhow::anyhow!("JWT signing is misconfigured: {}", reason));
+    }
+    let keys = std::iter::once(&keys.current)
+        .chain(keys.previous())
+        .filter_map(|key| match key.to_jwk() {
+            Ok(jwk) => jwk,
+            Err(e) => {
+                error!(kid = %key.kid, error = %e, "Skipping unusable key in JWKS");
+                None
+            }
+        })
+        .collect();
+    Ok(JsonWebKeySet { keys })
+}
+
 /// Creates a JWT token for the specified user with 24-hour expiration.
 ///
 /// This function generates a signed JWT token containing the user ID
@@ -399,12 +889,17 @@ pub fn verify_password(password: &str, hash: &str) -> bool {
 /// The JWT contains:
 /// - **sub** (subject): User UUID as string
 /// - **exp** (expiration): Unix timestamp (24 hours from creation)
-/// - **alg** (algorithm): HS256 (HMAC-SHA256)
+/// - **alg** (algorithm): HS256 (HMAC-SHA256), or RS256 when configured
+/// - **kid** (key id): Identifies the signing key so it can be rotated
+/// - **iss**/**aud** (issuer/audience): Only with [`create_session_jwt`]
 ///
 /// # Environment Configuration
 ///
-/// Requires `APP_AUTH__JWT_SECRET` environment variable for signing.
+/// Requires `APP_AUTH__JWT_SECRET` environment variable for signing, with
+/// its key id in `APP_AUTH__JWT_KID` (defaults to `"default"`).
 /// Falls back to a default secret with warning if not configured.
+/// Set `APP_AUTH__JWT_ALGORITHM=RS256` with `APP_AUTH__JWT_PRIVATE_KEY` and
+/// `APP_AUTH__JWT_PUBLIC_KEY` to sign with RSA instead.
 ///
 /// # Security Features
 ///
@@ -454,16 +949,33 @@ pub fn verify_password(password: &str, hash: &str) -> bool {
 /// - Consider shorter expiration for high-security environments
 /// - Implement token refresh for long-running sessions
 pub fn create_jwt(user_id: uuid::Uuid) -> anyhow::Result<String> {
-    info!(user_id = %user_id, "Creating JWT token");
     debug!("Loading JWT secret from environment");
+    encode_jwt(user_id, JwtKeyRing::from_env().current(), &JwtClaimsConfig::default())
+}
+
+/// Creates a JWT signed with the current key of `keys` and carrying the
+/// issuer and audience in `claims`. Its `sid` claim names the session
+/// (refresh token row) it was issued with, when there is one.
+pub fn create_session_jwt(user_id: uuid::Uuid, session_id: Option<uuid::Uuid>, keys: &JwtKeyRing, claims: &JwtClaimsConfig) -> anyhow::Result<String> {
+    encode_session_jwt(user_id, session_id, keys.current(), claims)
+}
+
+/// Creates a JWT like [`create_jwt`], signed with an explicit key instead of
+/// the one configured in the environment
+pub fn create_jwt_with_key(user_id: uuid::Uuid, key: &JwtKey) -> anyhow::Result<String> {
+    encode_jwt(user_id, key, &JwtClaimsConfig::default())
+}
+
+fn encode_jwt(user_id: uuid::Uuid, key: &JwtKey, claims_config: &JwtClaimsConfig) -> anyhow::Result<String> {
+    encode_session_jwt(user_id, None, key, claims_config)
+}
+
+fn encode_session_jwt(user_id: uuid::Uuid, session_id: Option<uuid::Uuid>, key: &JwtKey, claims_config: &JwtClaimsConfig) -> anyhow::Result<String> {
+    info!(user_id = %user_id, session_id = ?session_id, "Creating JWT token");
     
-    let secret = env::var("APP_AUTH__JWT_SECRET").unwrap_or_else(|_| {
-        warn!("JWT secret not found in environment, using default");
-        "mysecretkeymysecretkeymysecretkey12".to_string()
-    });
-    
-    let expiration = chrono::Utc::now()
-        .checked_add_signed(chrono::Duration::hours(24))
+    let now = chrono::Utc::now();
+    let expiration = now
+        .checked_add_signed(chrono::Duration::seconds(JWT_TTL_SECS))
         .expect("valid timestamp")
         .timestamp() as usize;
     
@@ -471,10 +983,20 @@ pub fn create_jwt(user_id: uuid::Uuid) -> anyhow::Result<String> {
     let claims = Claims {
         sub: user_id.to_string(),
         exp: expiration,
+        iat: Some(now.timestamp_micros() as f64 / 1_000_000.0),
+        iss: claims_config.issuer.clone(),
+        aud: claims_config.audience.clone(),
+        sid: session_id.map(|id| id.to_string()),
     };
     
     debug!("Encoding JWT token");
-    let token = encode(&Header::new(Algorithm::HS256), &claims, &EncodingKey::from_secret(secret.as_bytes()))
+    let mut header = Header::new(key.algorithm());
+    header.kid = Some(key.kid.clone());
+    let encoding_key = key.encoding_key().map_err(|e| {
+        error!(user_id = %user_id, error = %e, "Failed to load JWT signing key");
+        e
+    })?;
+    let token = encode(&header, &claims, &encoding_key)
         .map_err(|e| {
             error!(user_id = %user_id, error = %e, "Failed to encode JWT token");
             anyhow::anyhow!(e)
@@ -485,19 +1007,78 @@ pub fn create_jwt(user_id: uuid::Uuid) -> anyhow::Result<String> {
 }
 
 pub fn verify_jwt(token: &str) -> anyhow::Result<uuid::Uuid> {
-    debug!("Starting JWT token verification");
+    verify_jwt_claims(token).map(|verified| verified.user_id)
+}
+
+/// Subject, expiry and issue time of a token that passed verification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifiedToken {
+    pub user_id: uuid::Uuid,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    /// `None` for tokens minted before `iat` was added to the claims
+    pub issued_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Session the token was issued with; `None` when it came without a
+    /// refresh token, e.g. from a plain login
+    pub session_id: Option<uuid::Uuid>,
+}
+
+/// Verifies `token` like [`verify_jwt`], also returning its expiry
+pub fn verify_jwt_claims(token: &str) -> anyhow::Result<VerifiedToken> {
     debug!("Loading JWT secret from environment");
+    decode_jwt(token, &JwtKeyRing::from_env(), &JwtClaimsConfig::default())
+}
+
+/// Verifies `token` against `keys`, also requiring the issuer and audience
+/// configured in `claims`
+pub fn verify_jwt_with_claims(token: &str, keys: &JwtKeyRing, claims: &JwtClaimsConfig) -> anyhow::Result<VerifiedToken> {
+    decode_jwt(token, keys, claims)
+}
+
+/// Whether a [`verify_jwt`] failure was only the token having expired
+pub fn is_expired_jwt_error(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<jsonwebtoken::errors::Error>()
+        .is_some_and(|e| matches!(e.kind(), jsonwebtoken::errors::ErrorKind::ExpiredSignature))
+}
+
+/// Verifies `token` against an explicit key ring instead of the environment
+pub fn verify_jwt_with_key(token: &str, keys: &JwtKeyRing) -> anyhow::Result<VerifiedToken> {
+    decode_jwt(token, keys, &JwtClaimsConfig::default())
+}
+
+/// Whether the token header declares `alg: none`, i.e. claims to need no signature
+fn is_unsigned(token: &str) -> bool {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    let header = token.split('.').next().and_then(|header| URL_SAFE_NO_PAD.decode(header).ok());
+    let alg = header.and_then(|header| serde_json::from_slice::<serde_json::Value>(&header).ok());
+    matches!(alg.as_ref().and_then(|header| header.get("alg")).and_then(|alg| alg.as_str()), Some(alg) if alg.eq_ignore_ascii_case("none"))
+}
+
+fn decode_jwt(token: &str, keys: &JwtKeyRing, claims_config: &JwtClaimsConfig) -> anyhow::Result<VerifiedToken> {
+    debug!("Starting JWT token verification");
+    if is_unsigned(token) {
+        // jsonwebtoken can't parse such a header anyway; name it so it shows up in logs
+        warn!("Rejected unsigned JWT (alg: none)");
+        return Err(anyhow::anyhow!("unsigned JWT rejected"));
+    }
+    let header = decode_header(token).map_err(|e| {
+        warn!(error = %e, "JWT header could not be decoded");
+        anyhow::anyhow!(e)
+    })?;
+    let key = keys.key_for(header.kid.as_deref()).ok_or_else(|| {
+        warn!(kid = ?header.kid, "JWT signed with unknown or retired key id");
+        anyhow::anyhow!("unknown JWT key id")
+    })?;
+    if header.alg != key.algorithm() {
+        warn!(kid = ?header.kid, alg = ?header.alg, expected = ?key.algorithm(), "JWT algorithm does not match its signing key");
+        return Err(anyhow::anyhow!("unexpected JWT algorithm"));
+    }
     
-    let secret = env::var("APP_AUTH__JWT_SECRET").unwrap_or_else(|_| {
-        warn!("JWT secret not found in environment, using default");
-        "mysecretkeymysecretkeymysecretkey12".to_string()
-    });
-    
-    debug!("Decoding JWT token");
+    debug!(kid = ?header.kid, "Decoding JWT token");
+    // The algorithm comes from the selected key, never from the token header
     let token_data: TokenData<Claims> = decode::<Claims>(
         token,
-        &DecodingKey::from_secret(secret.as_bytes()),
-        &Validation::new(Algorithm::HS256),
+        &key.decoding_key()?,
+        &claims_config.validation(key.algorithm()),
     ).map_err(|e| {
         warn!(error = %e, "JWT token verification failed");
         anyhow::anyhow!(e)
@@ -510,8 +1091,37 @@ pub fn verify_jwt(token: &str) -> anyhow::Result<uuid::Uuid> {
             anyhow::anyhow!(e)
         })?;
     
+    let expires_at = chrono::DateTime::from_timestamp(token_data.claims.exp as i64, 0)
+        .ok_or_else(|| anyhow::anyhow!("JWT expiry out of range"))?;
+    let issued_at = token_data.claims.iat.and_then(|iat| chrono::DateTime::from_timestamp_micros((iat * 1_000_000.0).round() as i64));
+    let session_id = token_data.claims.sid.as_deref().and_then(|sid| uuid::Uuid::parse_str(sid).ok());
+    
     info!(user_id = %user_id, "JWT token verified successfully");
-    Ok(user_id)
+    Ok(VerifiedToken { user_id, expires_at, issued_at, session_id })
+}
+
+/// Header fields and claims of a token, read without checking it
+#[derive(Debug, Clone)]
+pub struct InspectedToken {
+    pub algorithm: Algorithm,
+    pub kid: Option<String>,
+    /// The payload as sent, including claims this service does not use
+    pub claims: serde_json::Value,
+}
+
+/// Decodes `token` without verifying its signature, expiry, issuer or audience.
+///
+/// Only for showing a token to its holder; never trust the result. Use
+/// [`verify_jwt_claims`] to find out whether the token is accepted.
+pub fn inspect_jwt(token: &str) -> anyhow::Result<InspectedToken> {
+    let header = decode_header(token)?;
+    let mut validation = Validation::new(header.alg);
+    validation.insecure_disable_signature_validation();
+    validation.validate_exp = false;
+    validation.validate_aud = false;
+    validation.set_required_spec_claims::<&str>(&[]);
+    let data = decode::<serde_json::Value>(token, &DecodingKey::from_secret(&[]), &validation)?;
+    Ok(InspectedToken { algorithm: header.alg, kid: header.kid, claims: data.claims })
 }
 
 pub fn use_verify_jwt_for_warning(token: &str) -> bool {
@@ -521,12 +1131,18 @@ pub fn use_verify_jwt_for_warning(token: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::env;
+    use std::collections::HashMap;
     use uuid::Uuid;
 
-    /// Setup test environment with proper JWT secret
-    fn setup_test_env() {
-        env::set_var("APP_AUTH__JWT_SECRET", "testsecretkeytestsecretkeytestsecr");
+    /// Explicit HS256 key, so tests don't depend on the environment
+    fn test_key() -> JwtKey {
+        JwtKey::hmac("default", "testsecretkeytestsecretkeytestsecr")
+    }
+
+    /// Ring loaded as if `vars` were the only `APP_AUTH__JWT_*` variables set
+    fn ring_from(vars: &[(&str, &str)]) -> JwtKeyRing {
+        let vars: HashMap<String, String> = vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect();
+        JwtKeyRing::from_vars(|name| vars.get(name).cloned())
     }
 
     #[test]
@@ -581,12 +1197,10 @@ mod tests {
     }
 
     #[test]
-    #[serial_test::serial]
     fn test_create_jwt_success() {
-        setup_test_env();
         let user_id = Uuid::new_v4();
         
-        let token = create_jwt(user_id).expect("JWT creation should succeed");
+        let token = create_jwt_with_key(user_id, &test_key()).expect("JWT creation should succeed");
         
         // Token should not be empty and should have JWT structure (header.payload.signature)
         assert!(!token.is_empty());
@@ -594,34 +1208,45 @@ mod tests {
     }
 
     #[test]
-    #[serial_test::serial]
     fn test_create_jwt_different_users() {
-        setup_test_env();
         let user_id1 = Uuid::new_v4();
         let user_id2 = Uuid::new_v4();
         
-        let token1 = create_jwt(user_id1).expect("First JWT creation should succeed");
-        let token2 = create_jwt(user_id2).expect("Second JWT creation should succeed");
+        let token1 = create_jwt_with_key(user_id1, &test_key()).expect("First JWT creation should succeed");
+        let token2 = create_jwt_with_key(user_id2, &test_key()).expect("Second JWT creation should succeed");
         
         // Different users should produce different tokens
         assert_ne!(token1, token2);
     }
 
     #[test]
-    #[serial_test::serial]
     fn test_verify_jwt_success() {
-        setup_test_env();
         let user_id = Uuid::new_v4();
         
-        let token = create_jwt(user_id).expect("JWT creation should succeed");
-        let parsed_id = verify_jwt(&token).expect("JWT verification should succeed");
+        let token = create_jwt_with_key(user_id, &test_key()).expect("JWT creation should succeed");
+        let verified = verify_jwt_with_key(&token, &test_key().into()).expect("JWT verification should succeed");
         
-        assert_eq!(user_id, parsed_id);
+        assert_eq!(user_id, verified.user_id);
+        assert!(verified.expires_at > chrono::Utc::now());
+    }
+
+    #[test]
+    fn test_key_ring_from_vars() {
+        let keys = ring_from(&[("APP_AUTH__JWT_SECRET", "testsecretkeytestsecretkeytestsecr")]);
+        let user_id = Uuid::new_v4();
+        
+        // Tokens from the explicit key match what the variables configure
+        let token = create_jwt_with_key(user_id, &test_key()).expect("JWT creation should succeed");
+        assert_eq!(verify_jwt_with_key(&token, &keys).unwrap().user_id, user_id);
+        let token = create_jwt_with_key(user_id, keys.current()).expect("JWT creation should succeed");
+        assert_eq!(verify_jwt_with_key(&token, &test_key().into()).unwrap().user_id, user_id);
+        assert!(!keys.uses_default_secret());
+        assert!(ring_from(&[]).uses_default_secret());
     }
 
     #[test]
     fn test_verify_jwt_invalid_token() {
-        setup_test_env();
+        let keys = JwtKeyRing::from(test_key());
         
         let invalid_tokens = vec![
             "invalid.token.value",
@@ -633,32 +1258,49 @@ mod tests {
         ];
         
         for invalid_token in invalid_tokens {
-            let result = verify_jwt(invalid_token);
+            let result = verify_jwt_with_key(invalid_token, &keys);
             assert!(result.is_err(), "Token '{}' should be invalid", invalid_token);
         }
     }
 
+    /// `header.claims.signature` with the header declaring `alg`
+    fn token_with_alg(alg: &str, user_id: Uuid, signature: &str) -> String {
+        use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+        let header = serde_json::json!({ "alg": alg, "typ": "JWT" });
+        let claims = serde_json::json!({ "sub": user_id.to_string(), "exp": chrono::Utc::now().timestamp() + 60 });
+        format!("{}.{}.{}", URL_SAFE_NO_PAD.encode(header.to_string()), URL_SAFE_NO_PAD.encode(claims.to_string()), signature)
+    }
+
+    #[test]
+    fn test_verify_jwt_rejects_alg_none() {
+        let keys = JwtKeyRing::from(test_key());
+        let user_id = Uuid::new_v4();
+        let signed = create_jwt_with_key(user_id, &test_key()).unwrap();
+        let signature = signed.rsplit('.').next().unwrap();
+
+        for alg in ["none", "None", "NONE"] {
+            for signature in ["", signature] {
+                let token = token_with_alg(alg, user_id, signature);
+                assert!(is_unsigned(&token));
+                let err = verify_jwt_with_key(&token, &keys).unwrap_err();
+                assert_eq!(err.to_string(), "unsigned JWT rejected");
+            }
+        }
+        assert!(!is_unsigned(&signed));
+    }
+
     #[test]
-    #[serial_test::serial]
     fn test_verify_jwt_wrong_secret() {
-        setup_test_env();
         let user_id = Uuid::new_v4();
-        let token = create_jwt(user_id).expect("JWT creation should succeed");
+        let token = create_jwt_with_key(user_id, &test_key()).expect("JWT creation should succeed");
         
-        // Change the secret
-        env::set_var("APP_AUTH__JWT_SECRET", "different_secret_key_for_testing");
-        
-        let result = verify_jwt(&token);
+        let other = JwtKeyRing::from(JwtKey::hmac("default", "different_secret_key_for_testing"));
+        let result = verify_jwt_with_key(&token, &other);
         assert!(result.is_err(), "JWT verification should fail with wrong secret");
-        
-        // Restore original secret for other tests
-        setup_test_env();
     }
 
     #[test]
-    #[serial_test::serial]
     fn test_use_verify_jwt_for_warning() {
-        setup_test_env();
         let user_id = Uuid::new_v4();
         let valid_token = create_jwt(user_id).expect("JWT creation should succeed");
         
@@ -666,13 +1308,269 @@ mod tests {
         assert!(!use_verify_jwt_for_warning("invalid_token"));
     }
 
-    // Test struct validation
+    /// Token issued under kid `2024-01`, before the rotation to `2024-02`
+    fn token_from_previous_key(user_id: Uuid) -> String {
+        let old_keys = ring_from(&[("APP_AUTH__JWT_SECRET", "old_secret_key_old_secret_key_old1"), ("APP_AUTH__JWT_KID", "2024-01")]);
+        create_jwt_with_key(user_id, old_keys.current()).expect("JWT creation should succeed")
+    }
+
+    /// Variables configuring the rotation to `2024-02`, still accepting `2024-01`
+    const ROTATED_VARS: [(&str, &str); 4] = [
+        ("APP_AUTH__JWT_SECRET", "new_secret_key_new_secret_key_new1"),
+        ("APP_AUTH__JWT_KID", "2024-02"),
+        ("APP_AUTH__JWT_PREVIOUS_SECRET", "old_secret_key_old_secret_key_old1"),
+        ("APP_AUTH__JWT_PREVIOUS_KID", "2024-01"),
+    ];
+
+    #[test]
+    fn test_create_jwt_sets_kid_header() {
+        let key = JwtKey::hmac("2024-02", "testsecretkeytestsecretkeytestsecr");
+        let token = create_jwt_with_key(Uuid::new_v4(), &key).expect("JWT creation should succeed");
+        assert_eq!(decode_header(&token).unwrap().kid.as_deref(), Some("2024-02"));
+    }
+
+    #[test]
+    fn test_verify_jwt_accepts_previous_key_during_rotation() {
+        let user_id = Uuid::new_v4();
+        let old_key = JwtKey::hmac("2024-01", "old_secret_key_old_secret_key_old1");
+        let new_key = JwtKey::hmac("2024-02", "new_secret_key_new_secret_key_new1");
+        let old_token = create_jwt_with_key(user_id, &old_key).expect("JWT creation should succeed");
+
+        let rotating = JwtKeyRing::new(new_key.clone(), Some(old_key));
+        assert_eq!(verify_jwt_with_key(&old_token, &rotating).unwrap().user_id, user_id);
+
+        // New tokens use the current key
+        let new_token = create_jwt_with_key(user_id, rotating.current()).expect("JWT creation should succeed");
+        assert_eq!(decode_header(&new_token).unwrap().kid.as_deref(), Some("2024-02"));
+        assert_eq!(verify_jwt_with_key(&new_token, &rotating).unwrap().user_id, user_id);
+
+        // Once the previous key is dropped its tokens are rejected
+        assert!(verify_jwt_with_key(&old_token, &new_key.into()).is_err());
+    }
+
+    #[test]
+    fn test_verify_jwt_accepts_previous_key_from_vars() {
+        let user_id = Uuid::new_v4();
+        let old_token = token_from_previous_key(user_id);
+
+        assert_eq!(verify_jwt_with_key(&old_token, &ring_from(&ROTATED_VARS)).unwrap().user_id, user_id);
+    }
+
+    #[test]
+    fn test_verify_jwt_rejects_previous_key_after_window() {
+        let user_id = Uuid::new_v4();
+        let old_token = token_from_previous_key(user_id);
+
+        let mut vars = ROTATED_VARS.to_vec();
+        vars.push(("APP_AUTH__JWT_PREVIOUS_VALID_UNTIL", "2000-01-01T00:00:00Z"));
+        assert!(verify_jwt_with_key(&old_token, &ring_from(&vars)).is_err());
+
+        // Without the previous key configured its tokens are rejected too
+        assert!(verify_jwt_with_key(&old_token, &ring_from(&ROTATED_VARS[..2])).is_err());
+    }
+
+    #[test]
+    fn test_previous_key_window_defaults_to_one_token_lifetime() {
+        let before = chrono::Utc::now();
+        let keys = ring_from(&ROTATED_VARS);
+        let until = keys.previous_valid_until.expect("a configured previous key gets a window");
+        let ttl = chrono::Duration::seconds(JWT_TTL_SECS);
+        assert!(until >= before + ttl && until <= chrono::Utc::now() + ttl, "{}", until);
+        assert!(keys.previous().is_some());
+
+        let mut vars = ROTATED_VARS.to_vec();
+        vars.push(("APP_AUTH__JWT_PREVIOUS_VALID_UNTIL", "2030-01-01T00:00:00Z"));
+        assert_eq!(ring_from(&vars).previous_valid_until.unwrap().to_rfc3339(), "2030-01-01T00:00:00+00:00");
+        assert!(ring_from(&ROTATED_VARS[..2]).previous_valid_until.is_none(), "no window without a previous key");
+    }
+
+    #[test]
+    fn test_verify_jwt_without_kid_uses_current_key() {
+        let claims = Claims {
+            sub: Uuid::new_v4().to_string(),
+            exp: (chrono::Utc::now().timestamp() + 60) as usize,
+            iat: None,
+            iss: None,
+            aud: None,
+            sid: None,
+        };
+        let token = encode(&Header::new(Algorithm::HS256), &claims, &EncodingKey::from_secret(b"testsecretkeytestsecretkeytestsecr")).unwrap();
+        assert!(verify_jwt_with_key(&token, &test_key().into()).is_ok());
+    }
+
+    fn claims_config(issuer: Option<&str>, audience: Option<&str>) -> JwtClaimsConfig {
+        JwtClaimsConfig { issuer: issuer.map(str::to_string), audience: audience.map(str::to_string) }
+    }
+
+    #[test]
+    fn test_verify_jwt_accepts_matching_issuer_and_audience() {
+        let user_id = Uuid::new_v4();
+        let expected = claims_config(Some("https://auth.kitchen.test"), Some("kitchen-api"));
+        let token = encode_jwt(user_id, &test_key(), &expected).expect("JWT creation should succeed");
+        assert_eq!(decode_jwt(&token, &test_key().into(), &expected).unwrap().user_id, user_id);
+    }
+
+    #[test]
+    fn test_verify_jwt_rejects_wrong_audience() {
+        let expected = claims_config(Some("https://auth.kitchen.test"), Some("kitchen-api"));
+        let other = claims_config(Some("https://auth.kitchen.test"), Some("billing-api"));
+        let token = encode_jwt(Uuid::new_v4(), &test_key(), &other).expect("JWT creation should succeed");
+        assert!(decode_jwt(&token, &test_key().into(), &expected).is_err());
+
+        // A token without any audience is rejected once one is required
+        let token = encode_jwt(Uuid::new_v4(), &test_key(), &claims_config(Some("https://auth.kitchen.test"), None)).unwrap();
+        assert!(decode_jwt(&token, &test_key().into(), &expected).is_err());
+    }
+
+    #[test]
+    fn test_verify_jwt_rejects_wrong_issuer() {
+        let expected = claims_config(Some("https://auth.kitchen.test"), Some("kitchen-api"));
+        let other = claims_config(Some("https://evil.example"), Some("kitchen-api"));
+        let token = encode_jwt(Uuid::new_v4(), &test_key(), &other).expect("JWT creation should succeed");
+        assert!(decode_jwt(&token, &test_key().into(), &expected).is_err());
+
+        let token = encode_jwt(Uuid::new_v4(), &test_key(), &claims_config(None, Some("kitchen-api"))).unwrap();
+        assert!(decode_jwt(&token, &test_key().into(), &expected).is_err());
+    }
+
+    #[test]
+    fn test_unset_issuer_and_audience_are_not_written() {
+        let token = encode_jwt(Uuid::new_v4(), &test_key(), &JwtClaimsConfig::default()).unwrap();
+        let payload = token.split('.').nth(1).unwrap();
+        use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+        let claims: serde_json::Value = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).unwrap()).unwrap();
+        assert!(claims.get("iss").is_none());
+        assert!(claims.get("aud").is_none());
+        assert!(decode_jwt(&token, &test_key().into(), &JwtClaimsConfig::default()).is_ok());
+    }
+
+    const RS256_PRIVATE: &str = include_str!("../../tests/fixtures/jwt/rs256_current_private.pem");
+    const RS256_PUBLIC: &str = include_str!("../../tests/fixtures/jwt/rs256_current_public.pem");
+    const RS256_PREVIOUS_PRIVATE: &str = include_str!("../../tests/fixtures/jwt/rs256_previous_private.pem");
+    const RS256_PREVIOUS_PUBLIC: &str = include_str!("../../tests/fixtures/jwt/rs256_previous_public.pem");
+
+    /// Variables configuring RS256 with the current key pair under `kid`
+    fn rs256_vars(kid: &str) -> Vec<(&str, &str)> {
+        vec![
+            ("APP_AUTH__JWT_ALGORITHM", "RS256"),
+            ("APP_AUTH__JWT_KID", kid),
+            ("APP_AUTH__JWT_PRIVATE_KEY", RS256_PRIVATE),
+            ("APP_AUTH__JWT_PUBLIC_KEY", RS256_PUBLIC),
+        ]
+    }
+
+    #[test]
+    fn test_rs256_round_trip_and_rejects_hs256_tokens() {
+        let user_id = Uuid::new_v4();
+        let hs256_token = create_jwt_with_key(user_id, &JwtKey::hmac("rsa-2024", RS256_PUBLIC)).expect("JWT creation should succeed");
+
+        let key = JwtKey::rsa("rsa-2024", Some(RS256_PRIVATE.to_string()), RS256_PUBLIC);
+        let token = create_jwt_with_key(user_id, &key).expect("JWT creation should succeed");
+        let header = decode_header(&token).unwrap();
+        assert_eq!(header.alg, Algorithm::RS256);
+        assert_eq!(header.kid.as_deref(), Some("rsa-2024"));
+        let keys = JwtKeyRing::from(key);
+        assert_eq!(verify_jwt_with_key(&token, &keys).unwrap().user_id, user_id);
+
+        // The key decides the algorithm, so an HS256 token - even one using
+        // the public key as its secret - can't slip through
+        assert!(verify_jwt_with_key(&hs256_token, &keys).is_err());
+    }
+
+    #[test]
+    fn test_rs256_public_only_key_cannot_sign() {
+        let key = JwtKey::rsa("rsa-verify-only", None, RS256_PUBLIC);
+        assert!(create_jwt_with_key(Uuid::new_v4(), &key).is_err());
+    }
+
+    #[test]
+    fn test_misconfigured_algorithm_refuses_to_sign_instead_of_falling_back() {
+        let mut vars = rs256_vars("rsa-missing");
+        vars.retain(|(name, _)| *name != "APP_AUTH__JWT_PRIVATE_KEY");
+        vars.push(("APP_AUTH__JWT_SECRET", "test_secret_key_that_must_not_be_used"));
+        let keys = ring_from(&vars);
+        let hs256_token = create_jwt_with_key(Uuid::new_v4(), &JwtKey::hmac("rsa-missing", "test_secret_key_that_must_not_be_used")).unwrap();
+
+        let err = create_jwt_with_key(Uuid::new_v4(), keys.current()).unwrap_err();
+        assert!(err.to_string().contains("misconfigured"), "{}", err);
+        assert!(verify_jwt_with_key(&hs256_token, &keys).is_err(), "nothing verifies against an unusable key");
+
+        let keys = ring_from(&[("APP_AUTH__JWT_ALGORITHM", "ES256")]);
+        assert!(create_jwt_with_key(Uuid::new_v4(), keys.current()).is_err());
+    }
+
+    #[test]
+    fn test_rs256_pem_accepts_escaped_newlines() {
+        let private_pem = RS256_PRIVATE.replace('\n', "\\n");
+        let public_pem = RS256_PUBLIC.replace('\n', "\\n");
+        let mut vars = rs256_vars("rsa-escaped");
+        vars.retain(|(name, _)| !name.ends_with("_KEY"));
+        vars.extend([("APP_AUTH__JWT_PRIVATE_KEY", private_pem.as_str()), ("APP_AUTH__JWT_PUBLIC_KEY", public_pem.as_str())]);
+        let keys = ring_from(&vars);
+        let user_id = Uuid::new_v4();
+        let token = create_jwt_with_key(user_id, keys.current()).expect("JWT creation should succeed");
+        assert_eq!(verify_jwt_with_key(&token, &keys).unwrap().user_id, user_id);
+    }
+
+    #[test]
+    fn test_jwks_lists_current_and_previous_public_keys() {
+        // A token from the previous key pair stays verifiable during rotation
+        let previous = ring_from(&[
+            ("APP_AUTH__JWT_ALGORITHM", "RS256"),
+            ("APP_AUTH__JWT_KID", "rsa-2023"),
+            ("APP_AUTH__JWT_PRIVATE_KEY", RS256_PREVIOUS_PRIVATE),
+            ("APP_AUTH__JWT_PUBLIC_KEY", RS256_PREVIOUS_PUBLIC),
+        ]);
+        let user_id = Uuid::new_v4();
+        let old_token = create_jwt_with_key(user_id, previous.current()).expect("JWT creation should succeed");
+
+        let mut vars = rs256_vars("rsa-2024");
+        vars.extend([("APP_AUTH__JWT_PREVIOUS_KID", "rsa-2023"), ("APP_AUTH__JWT_PREVIOUS_PUBLIC_KEY", RS256_PREVIOUS_PUBLIC)]);
+        let keys = ring_from(&vars);
+        assert_eq!(verify_jwt_with_key(&old_token, &keys).unwrap().user_id, user_id);
+
+        let set = jwks(&keys).unwrap();
+        let kids: Vec<&str> = set.keys.iter().map(|key| key.kid.as_str()).collect();
+        assert_eq!(kids, ["rsa-2024", "rsa-2023"]);
+        for key in &set.keys {
+            assert_eq!(key.kty, "RSA");
+            assert_eq!(key.alg, "RS256");
+            assert_eq!(key.e, "AQAB");
+            // 2048-bit modulus, base64url without padding
+            assert_eq!(key.n.len(), 342);
+        }
+        let json = serde_json::to_value(&set).unwrap();
+        assert_eq!(json["keys"][0]["use"], "sig");
+        assert!(json["keys"][0].get("d").is_none(), "private exponent must never be published");
+    }
+
+    #[test]
+    fn test_jwks_omits_hmac_secrets() {
+        assert!(jwks(&ring_from(&ROTATED_VARS)).unwrap().keys.is_empty());
+    }
+
+    #[test]
+    fn test_oversized_passwords_fail_validation() {
+        use crate::config::settings::AppSettings;
+        let long = "Aa1!".repeat(DEFAULT_MAX_PASSWORD_BYTES / 4 + 1);
+        let request = LoginRequest { email: Email::parse("chef@restaurant.com").unwrap(), password: long.clone() };
+        let errors = request.validate_with(&AppSettings::default()).unwrap_err();
+        assert_eq!(errors.field_errors()["password"][0].code, "password_too_long");
+
+        let change = ChangePasswordRequest { current_password: long.clone(), new_password: "SecurePass123!".to_string() };
+        assert!(change.validate_with(&AppSettings::default()).unwrap_err().field_errors().contains_key("current_password"));
+
+        let settings = AppSettings { max_password_bytes: long.len(), ..AppSettings::default() };
+        assert!(request.validate_with(&settings).is_ok());
+    }
+
     #[test]
     fn test_register_request_validation() {
         let request = RegisterRequest {
-            email: "test@example.com".to_string(),
+            email: Email::parse("test@example.com").unwrap(),
             password: "password123".to_string(),
             full_name: "Test User".to_string(),
+            display_name: None,
         };
         
         assert_eq!(request.email, "test@example.com");
@@ -683,7 +1581,7 @@ mod tests {
     #[test]
     fn test_login_request_validation() {
         let request = LoginRequest {
-            email: "user@example.com".to_string(),
+            email: Email::parse("user@example.com").unwrap(),
             password: "userpass".to_string(),
         };
         
@@ -697,6 +1595,7 @@ mod tests {
         let preferences = UserPreferences {
             theme: Some("dark".to_string()),
             notifications: Some(true),
+            ..Default::default()
         };
         
         let profile = UserProfile {
@@ -715,4 +1614,46 @@ mod tests {
         assert_eq!(prefs.theme, Some("dark".to_string()));
         assert_eq!(prefs.notifications, Some(true));
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_user_preferences_round_trip_preserves_unknown_keys() {
+        let raw = serde_json::json!({
+            "theme": "dark",
+            "notifications": false,
+            "language": "pt-BR",
+            "dashboard": {"widgets": ["orders", "stock"]}
+        });
+
+        let prefs: UserPreferences = serde_json::from_value(raw.clone()).unwrap();
+        assert_eq!(prefs.theme.as_deref(), Some("dark"));
+        assert_eq!(prefs.notifications, Some(false));
+        assert_eq!(prefs.extra.len(), 2);
+        assert_eq!(prefs.extra["language"], "pt-BR");
+
+        assert_eq!(serde_json::to_value(&prefs).unwrap(), raw);
+        assert_eq!(UserPreferences::from_json(&raw), Some(prefs));
+    }
+
+    #[test]
+    fn test_user_preferences_from_json_is_lenient() {
+        let raw = serde_json::json!({"theme": 3, "beta": true});
+        let prefs = UserPreferences::from_json(&raw).unwrap();
+        assert!(prefs.theme.is_none());
+        assert!(prefs.notifications.is_none());
+        assert_eq!(prefs.extra["theme"], 3);
+        // Absent known fields are omitted rather than serialized as null
+        assert_eq!(serde_json::to_value(&prefs).unwrap(), raw);
+
+        assert!(UserPreferences::from_json(&serde_json::json!("dark")).is_none());
+    }
+
+    #[test]
+    fn test_user_preferences_with_defaults() {
+        let raw = serde_json::json!({"theme": 3, "notifications": false, "beta": true});
+        let prefs = UserPreferences::from_json(&raw).unwrap().with_defaults();
+        assert_eq!(
+            serde_json::to_value(&prefs).unwrap(),
+            serde_json::json!({"theme": "light", "notifications": false, "beta": true})
+        );
+    }
+}
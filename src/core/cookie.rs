@@ -0,0 +1,179 @@
+//! Attributes of the access token cookie.
+//!
+//! Deployments differ in how the cookie must travel: a same-origin app wants
+//! `SameSite=Strict`, while a frontend on another site needs `SameSite=None`.
+//! [`CookieAttributes`] holds the configurable parts and refuses combinations
+//! browsers would drop, so a bad setting can't silently break cookie logins.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Error returned for a cookie attribute combination that can't be used
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidCookieAttributes {
+    #[error("Unknown SameSite value '{0}'; expected Strict, Lax or None")]
+    SameSite(String),
+    #[error("SameSite=None requires the Secure attribute")]
+    NoneWithoutSecure,
+    #[error("Cookie {0} must not contain whitespace, ';', ',' or control characters")]
+    Value(&'static str),
+    #[error("Cookie Path must start with '/'")]
+    Path,
+}
+
+/// When browsers send the cookie on cross-site requests
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SameSite {
+    /// Only on requests from this site
+    #[default]
+    Strict,
+    /// Also on top-level cross-site navigations
+    Lax,
+    /// On every request; requires `Secure`
+    None,
+}
+
+impl SameSite {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SameSite::Strict => "Strict",
+            SameSite::Lax => "Lax",
+            SameSite::None => "None",
+        }
+    }
+}
+
+impl fmt::Display for SameSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SameSite {
+    type Err = InvalidCookieAttributes;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "strict" => Ok(SameSite::Strict),
+            "lax" => Ok(SameSite::Lax),
+            "none" => Ok(SameSite::None),
+            _ => Err(InvalidCookieAttributes::SameSite(s.to_string())),
+        }
+    }
+}
+
+/// `SameSite`, `Secure`, `Domain` and `Path` of the access token cookie.
+///
+/// The cookie is always `HttpOnly`. The only way to build one is
+/// [`CookieAttributes::new`], which rejects `SameSite=None` without `Secure`
+/// and values that would break out of the `Set-Cookie` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CookieAttributes {
+    same_site: SameSite,
+    secure: bool,
+    domain: Option<String>,
+    path: String,
+}
+
+impl Default for CookieAttributes {
+    /// `Path=/; Secure; SameSite=Strict` with no `Domain`
+    fn default() -> Self {
+        Self { same_site: SameSite::Strict, secure: true, domain: None, path: "/".to_string() }
+    }
+}
+
+impl CookieAttributes {
+    pub fn new(same_site: SameSite, secure: bool, domain: Option<String>, path: String) -> Result<Self, InvalidCookieAttributes> {
+        if same_site == SameSite::None && !secure {
+            return Err(InvalidCookieAttributes::NoneWithoutSecure);
+        }
+        if domain.as_deref().is_some_and(|domain| domain.is_empty() || !is_attribute_value(domain)) {
+            return Err(InvalidCookieAttributes::Value("Domain"));
+        }
+        if !is_attribute_value(&path) {
+            return Err(InvalidCookieAttributes::Value("Path"));
+        }
+        if !path.starts_with('/') {
+            return Err(InvalidCookieAttributes::Path);
+        }
+        Ok(Self { same_site, secure, domain, path })
+    }
+
+    pub fn same_site(&self) -> SameSite {
+        self.same_site
+    }
+
+    pub fn secure(&self) -> bool {
+        self.secure
+    }
+
+    pub fn domain(&self) -> Option<&str> {
+        self.domain.as_deref()
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// `Set-Cookie` value setting `name` to `value` for `max_age_secs`
+    pub fn set_cookie(&self, name: &str, value: &str, max_age_secs: i64) -> String {
+        let mut cookie = format!("{}={}; Path={}", name, value, self.path);
+        if let Some(domain) = &self.domain {
+            cookie.push_str("; Domain=");
+            cookie.push_str(domain);
+        }
+        cookie.push_str(&format!("; Max-Age={}", max_age_secs));
+        if self.secure {
+            cookie.push_str("; Secure");
+        }
+        cookie.push_str("; HttpOnly; SameSite=");
+        cookie.push_str(self.same_site.as_str());
+        cookie
+    }
+}
+
+/// Whether `value` can sit in a `Set-Cookie` attribute without ending it early
+fn is_attribute_value(value: &str) -> bool {
+    !value.chars().any(|c| c.is_whitespace() || c.is_control() || c == ';' || c == ',')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_attributes_match_strict_secure_cookie() {
+        let cookie = CookieAttributes::default().set_cookie("access_token", "abc", 60);
+        assert_eq!(cookie, "access_token=abc; Path=/; Max-Age=60; Secure; HttpOnly; SameSite=Strict");
+    }
+
+    #[test]
+    fn test_attribute_combinations() {
+        let lax = CookieAttributes::new(SameSite::Lax, false, None, "/api".to_string()).unwrap();
+        assert_eq!(lax.set_cookie("t", "v", 5), "t=v; Path=/api; Max-Age=5; HttpOnly; SameSite=Lax");
+
+        let cross_site = CookieAttributes::new(SameSite::None, true, Some(".restaurant.com".to_string()), "/".to_string()).unwrap();
+        assert_eq!(cross_site.set_cookie("t", "v", 5), "t=v; Path=/; Domain=.restaurant.com; Max-Age=5; Secure; HttpOnly; SameSite=None");
+
+        assert_eq!("LAX".parse::<SameSite>(), Ok(SameSite::Lax));
+        assert_eq!(" none ".parse::<SameSite>(), Ok(SameSite::None));
+        assert!(matches!("relaxed".parse::<SameSite>(), Err(InvalidCookieAttributes::SameSite(_))));
+    }
+
+    #[test]
+    fn test_same_site_none_requires_secure() {
+        assert_eq!(
+            CookieAttributes::new(SameSite::None, false, None, "/".to_string()),
+            Err(InvalidCookieAttributes::NoneWithoutSecure)
+        );
+        assert!(CookieAttributes::new(SameSite::Strict, false, None, "/".to_string()).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_values_that_break_the_header() {
+        let injected = CookieAttributes::new(SameSite::Strict, true, Some("a.com; SameSite=None".to_string()), "/".to_string());
+        assert_eq!(injected, Err(InvalidCookieAttributes::Value("Domain")));
+        assert_eq!(CookieAttributes::new(SameSite::Strict, true, None, "api".to_string()), Err(InvalidCookieAttributes::Path));
+        assert_eq!(CookieAttributes::new(SameSite::Strict, true, Some(String::new()), "/".to_string()), Err(InvalidCookieAttributes::Value("Domain")));
+    }
+}
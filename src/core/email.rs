@@ -0,0 +1,273 @@
+//! Validated, normalized email addresses.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+use validator::ValidateEmail;
+
+/// Longest address accepted, per the RFC 5321 path limit
+const MAX_EMAIL_LEN: usize = 254;
+
+/// Disposable email domains shipped with the service
+pub const BUNDLED_DISPOSABLE_DOMAINS: &str = include_str!("disposable_email_domains.txt");
+
+/// Error returned when a string is not a usable email address
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid email format")]
+pub struct InvalidEmail;
+
+/// An email address that has been trimmed, lowercased and validated.
+///
+/// The only way to build one from user input is [`Email::parse`], which also
+/// runs on deserialization, so request types holding an `Email` can't carry
+/// an unvalidated address. Values read back from the database are trusted
+/// as-is since they were parsed on the way in.
+///
+/// ```rust
+/// use server::core::email::Email;
+///
+/// let email = Email::parse("  Chef@Restaurant.COM ").unwrap();
+/// assert_eq!(email.to_string(), "chef@restaurant.com");
+/// assert!(Email::parse("not-an-email").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type)]
+#[serde(try_from = "String", into = "String")]
+#[sqlx(transparent)]
+pub struct Email(String);
+
+impl Email {
+    /// Normalize (trim, lowercase) and validate an address
+    pub fn parse(raw: &str) -> Result<Self, InvalidEmail> {
+        let normalized = raw.trim().to_lowercase();
+        if normalized.len() > MAX_EMAIL_LEN || !normalized.validate_email() {
+            return Err(InvalidEmail);
+        }
+        Ok(Self(normalized))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+
+    /// The part after the `@`, e.g. `restaurant.com`
+    pub fn domain(&self) -> &str {
+        self.0.rsplit_once('@').map_or("", |(_, domain)| domain)
+    }
+}
+
+/// Whether `domain` matches `pattern`: either an exact domain or
+/// `*.example.com`, which matches any subdomain but not `example.com` itself
+fn domain_matches(pattern: &str, domain: &str) -> bool {
+    match pattern.strip_prefix("*.") {
+        Some(parent) => domain.strip_suffix(parent).is_some_and(|sub| sub.len() > 1 && sub.ends_with('.')),
+        None => domain == pattern,
+    }
+}
+
+/// Parse a domain list: one domain per line, blank lines and `#` comments ignored
+pub fn parse_domain_list(text: &str) -> BTreeSet<String> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim().to_lowercase())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// Disposable domains from `path`, or the bundled list when no path is given
+pub fn load_disposable_domains(path: Option<&str>) -> std::io::Result<BTreeSet<String>> {
+    match path {
+        Some(path) => Ok(parse_domain_list(&std::fs::read_to_string(path)?)),
+        None => Ok(parse_domain_list(BUNDLED_DISPOSABLE_DOMAINS)),
+    }
+}
+
+/// Email domains allowed to register.
+///
+/// A blocked match always wins. With an empty allowlist every domain that
+/// isn't blocked is allowed; otherwise the domain must match the allowlist.
+/// Disposable domains are tracked separately so they can be reported with
+/// their own error.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmailDomainPolicy {
+    allowed: Vec<String>,
+    blocked: Vec<String>,
+    disposable: BTreeSet<String>,
+}
+
+impl EmailDomainPolicy {
+    /// Allows every domain
+    pub const fn unrestricted() -> Self {
+        Self { allowed: Vec::new(), blocked: Vec::new(), disposable: BTreeSet::new() }
+    }
+
+    pub fn new(allowed: Vec<String>, blocked: Vec<String>) -> Self {
+        let normalize = |patterns: Vec<String>| patterns.into_iter().map(|p| p.trim().to_lowercase()).filter(|p| !p.is_empty()).collect();
+        Self { allowed: normalize(allowed), blocked: normalize(blocked), disposable: BTreeSet::new() }
+    }
+
+    /// Also reject addresses at these disposable domains or their subdomains
+    pub fn with_disposable_domains(mut self, domains: BTreeSet<String>) -> Self {
+        self.disposable = domains;
+        self
+    }
+
+    pub fn is_unrestricted(&self) -> bool {
+        self.allowed.is_empty() && self.blocked.is_empty() && self.disposable.is_empty()
+    }
+
+    pub fn permits(&self, email: &Email) -> bool {
+        let domain = email.domain();
+        if self.blocked.iter().any(|pattern| domain_matches(pattern, domain)) {
+            return false;
+        }
+        self.allowed.is_empty() || self.allowed.iter().any(|pattern| domain_matches(pattern, domain))
+    }
+
+    /// Whether the address is at a disposable domain or one of its subdomains
+    pub fn is_disposable(&self, email: &Email) -> bool {
+        let mut domain = email.domain();
+        loop {
+            if self.disposable.contains(domain) {
+                return true;
+            }
+            match domain.split_once('.') {
+                Some((_, parent)) => domain = parent,
+                None => return false,
+            }
+        }
+    }
+}
+
+impl fmt::Display for Email {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for Email {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Email {
+    type Err = InvalidEmail;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<String> for Email {
+    type Error = InvalidEmail;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+impl From<Email> for String {
+    fn from(email: Email) -> Self {
+        email.0
+    }
+}
+
+impl PartialEq<str> for Email {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Email {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_normalizes() {
+        let email = Email::parse("  Head.Chef+Shift@Restaurant.COM\n").unwrap();
+        assert_eq!(email.as_str(), "head.chef+shift@restaurant.com");
+        assert_eq!(email.to_string(), "head.chef+shift@restaurant.com");
+        assert_eq!(email, Email::parse("head.chef+shift@restaurant.com").unwrap());
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_addresses() {
+        for raw in ["", "   ", "invalid-email", "@restaurant.com", "chef@", "chef@@restaurant.com", "chef restaurant@x.com"] {
+            assert_eq!(Email::parse(raw), Err(InvalidEmail), "{:?} should be rejected", raw);
+        }
+        let too_long = format!("{}@restaurant.com", "a".repeat(MAX_EMAIL_LEN));
+        assert!(Email::parse(&too_long).is_err());
+    }
+
+    fn email(raw: &str) -> Email {
+        Email::parse(raw).unwrap()
+    }
+
+    #[test]
+    fn test_domain_policy_allowlist() {
+        let policy = EmailDomainPolicy::new(vec!["Restaurant.com".to_string()], Vec::new());
+        assert!(policy.permits(&email("chef@restaurant.com")));
+        assert!(!policy.permits(&email("chef@gmail.com")));
+        assert!(!policy.permits(&email("chef@kitchen.restaurant.com")));
+        assert!(!policy.permits(&email("chef@notrestaurant.com")));
+    }
+
+    #[test]
+    fn test_domain_policy_blocklist_wins() {
+        let policy = EmailDomainPolicy::new(vec!["*.restaurant.com".to_string()], vec!["temp.restaurant.com".to_string(), "mailinator.com".to_string()]);
+        assert!(policy.permits(&email("chef@paris.restaurant.com")));
+        assert!(!policy.permits(&email("chef@temp.restaurant.com")));
+
+        let open = EmailDomainPolicy::new(Vec::new(), vec!["mailinator.com".to_string()]);
+        assert!(open.permits(&email("chef@gmail.com")));
+        assert!(!open.permits(&email("chef@mailinator.com")));
+    }
+
+    #[test]
+    fn test_domain_policy_wildcard_matches_subdomains_only() {
+        let policy = EmailDomainPolicy::new(vec!["*.restaurant.com".to_string()], Vec::new());
+        assert!(policy.permits(&email("chef@paris.restaurant.com")));
+        assert!(policy.permits(&email("chef@a.b.restaurant.com")));
+        assert!(!policy.permits(&email("chef@restaurant.com")));
+        assert!(!policy.permits(&email("chef@badrestaurant.com")));
+        assert!(EmailDomainPolicy::unrestricted().permits(&email("chef@anything.io")));
+    }
+
+    #[test]
+    fn test_disposable_domains_rejected() {
+        let policy = EmailDomainPolicy::unrestricted().with_disposable_domains(load_disposable_domains(None).unwrap());
+        assert!(policy.is_disposable(&email("someone@mailinator.com")));
+        assert!(policy.is_disposable(&email("someone@inbox.yopmail.com")));
+        assert!(!policy.is_disposable(&email("chef@restaurant.com")));
+        assert!(!policy.is_disposable(&email("chef@notmailinator.com")));
+        assert!(!EmailDomainPolicy::unrestricted().is_disposable(&email("someone@mailinator.com")));
+    }
+
+    #[test]
+    fn test_disposable_domains_file_replaces_bundled_list() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), "# ours\nThrowaway.example\n\n").unwrap();
+        let domains = load_disposable_domains(file.path().to_str()).unwrap();
+        assert_eq!(domains, BTreeSet::from(["throwaway.example".to_string()]));
+        assert!(load_disposable_domains(Some("/nonexistent/disposable.txt")).is_err());
+    }
+
+    #[test]
+    fn test_serde_validates_and_round_trips() {
+        let email: Email = serde_json::from_str("\" Chef@Restaurant.com \"").unwrap();
+        assert_eq!(serde_json::to_string(&email).unwrap(), "\"chef@restaurant.com\"");
+
+        let err = serde_json::from_str::<Email>("\"not-an-email\"").unwrap_err();
+        assert!(err.to_string().contains("Invalid email format"));
+    }
+}
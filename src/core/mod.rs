@@ -1,3 +1,7 @@
 pub mod auth;
+pub mod cookie;
+pub mod email;
 pub mod refresh_token;
+pub mod role;
+pub mod timestamped;
 pub mod user; 
\ No newline at end of file
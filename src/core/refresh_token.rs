@@ -11,8 +11,14 @@ pub struct RefreshToken {
     pub token: String,
     pub expires_at: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
+    /// When the token was last issued or rotated; drives idle expiry
+    #[serde(default = "Utc::now")]
+    pub last_used_at: DateTime<Utc>,
 }
 
+/// Sessions unused for this long expire regardless of `expires_at`
+pub const IDLE_TIMEOUT_DAYS: i64 = 7;
+
 impl RefreshToken {
     /// Create a new refresh token with default expiration (30 days)
     pub fn new(user_id: Uuid, token: String) -> Self {
@@ -23,6 +29,7 @@ impl RefreshToken {
             token,
             expires_at: now + Duration::days(30),
             created_at: now,
+            last_used_at: now,
         }
     }
 
@@ -31,9 +38,14 @@ impl RefreshToken {
         Utc::now() > self.expires_at
     }
 
-    /// Check if the refresh token is valid (not expired)
+    /// Check if the token has gone unused for longer than the idle timeout
+    pub fn is_idle_expired(&self) -> bool {
+        is_idle_expired(self.last_used_at, Utc::now())
+    }
+
+    /// Check if the refresh token is valid (neither expired nor idle)
     pub fn is_valid(&self) -> bool {
-        !self.is_expired()
+        !self.is_expired() && !self.is_idle_expired()
     }
 
     /// Get remaining validity duration
@@ -47,6 +59,11 @@ impl RefreshToken {
     }
 }
 
+/// Whether a token last used at `last_used_at` is idle-expired as of `now`
+pub fn is_idle_expired(last_used_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    now - last_used_at > Duration::days(IDLE_TIMEOUT_DAYS)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -62,6 +79,7 @@ mod tests {
             token: "sometokenstring".to_string(),
             expires_at: Utc::now(),
             created_at: Utc::now(),
+            last_used_at: Utc::now(),
         };
         
         let json = serde_json::to_string(&token).expect("Should serialize");
@@ -110,6 +128,7 @@ mod tests {
             token: "expired_token".to_string(),
             expires_at: old_expiry,
             created_at: old_expiry - Duration::days(30),
+            last_used_at: old_expiry,
         };
         
         assert!(token.is_expired());
@@ -140,6 +159,7 @@ mod tests {
             token: "expired_token".to_string(),
             expires_at: old_expiry,
             created_at: old_expiry - Duration::days(30),
+            last_used_at: old_expiry,
         };
         
         let remaining = token.remaining_validity();
@@ -157,6 +177,7 @@ mod tests {
             token: "edge_case_token".to_string(),
             expires_at: now,
             created_at: now - Duration::days(30),
+            last_used_at: now,
         };
         
         // Token exactly at expiry should be considered expired
@@ -187,4 +208,22 @@ mod tests {
         assert_eq!(token.expires_at, cloned.expires_at);
         assert_eq!(token.created_at, cloned.created_at);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_refresh_token_idle_expiry() {
+        let mut token = RefreshToken::new(Uuid::new_v4(), "idle_token".to_string());
+        assert!(!token.is_idle_expired());
+
+        token.last_used_at = Utc::now() - Duration::days(IDLE_TIMEOUT_DAYS + 1);
+        assert!(token.is_idle_expired());
+        assert!(!token.is_expired(), "absolute expiry is still in the future");
+        assert!(!token.is_valid());
+    }
+
+    #[test]
+    fn test_is_idle_expired_boundary() {
+        let now = Utc::now();
+        assert!(!is_idle_expired(now - Duration::days(IDLE_TIMEOUT_DAYS), now));
+        assert!(is_idle_expired(now - Duration::days(IDLE_TIMEOUT_DAYS) - Duration::seconds(1), now));
+    }
+}
@@ -0,0 +1,142 @@
+//! Account roles.
+
+use serde::{Deserialize, Serialize};
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef};
+use sqlx::{Decode, Encode, Postgres};
+use std::fmt;
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+/// Error returned for a role name this version doesn't know
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Unknown role '{0}'")]
+pub struct InvalidRole(pub String);
+
+/// What an account is allowed to do.
+///
+/// Stored in `users.role` as checked text (see the `users_role_check`
+/// constraint) and serialized in `snake_case`, so `"line_cook"` is accepted
+/// and a typo like `"admln"` is rejected both by the API and by the database.
+///
+/// ```rust
+/// use server::core::role::Role;
+///
+/// assert_eq!("line_cook".parse::<Role>().unwrap(), Role::LineCook);
+/// assert!("admln".parse::<Role>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Regular account; the default for new users
+    #[default]
+    User,
+    /// Full access, including managing other accounts
+    Admin,
+    Manager,
+    LineCook,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [Role::User, Role::Admin, Role::Manager, Role::LineCook];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Admin => "admin",
+            Role::Manager => "manager",
+            Role::LineCook => "line_cook",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Role {
+    type Err = InvalidRole;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Role::ALL
+            .into_iter()
+            .find(|role| role.as_str() == s)
+            .ok_or_else(|| InvalidRole(s.to_string()))
+    }
+}
+
+impl sqlx::Type<Postgres> for Role {
+    fn type_info() -> PgTypeInfo {
+        <str as sqlx::Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <str as sqlx::Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl Encode<'_, Postgres> for Role {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        <&str as Encode<Postgres>>::encode(self.as_str(), buf)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for Role {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(<&str as Decode<Postgres>>::decode(value)?.parse()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{test_pool, unique_email};
+
+    #[test]
+    fn test_serde_round_trip() {
+        for role in Role::ALL {
+            let json = serde_json::to_string(&role).unwrap();
+            assert_eq!(json, format!("\"{}\"", role.as_str()));
+            assert_eq!(serde_json::from_str::<Role>(&json).unwrap(), role);
+        }
+        assert_eq!(serde_json::to_string(&Role::LineCook).unwrap(), "\"line_cook\"");
+    }
+
+    #[test]
+    fn test_unknown_roles_rejected() {
+        for raw in ["admln", "Admin", "", "line-cook"] {
+            assert_eq!(raw.parse::<Role>(), Err(InvalidRole(raw.to_string())));
+            assert!(serde_json::from_value::<Role>(serde_json::json!(raw)).is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_database_round_trip() {
+        let pool = test_pool().await;
+
+        for role in Role::ALL {
+            let id: uuid::Uuid = sqlx::query_scalar("INSERT INTO users (email, password_hash, full_name, role) VALUES ($1, 'hash', 'Role Tester', $2) RETURNING id")
+                .bind(unique_email("role"))
+                .bind(role)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            let stored: Role = sqlx::query_scalar("SELECT role FROM users WHERE id = $1")
+                .bind(id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(stored, role);
+        }
+
+        // The check constraint keeps typos out even when bypassing the enum
+        let typo = sqlx::query("INSERT INTO users (email, password_hash, full_name, role) VALUES ($1, 'hash', 'Role Tester', 'admln')")
+            .bind(unique_email("role"))
+            .execute(&pool)
+            .await;
+        assert!(typo.is_err());
+    }
+}
@@ -0,0 +1,108 @@
+use chrono::{DateTime, Utc};
+use crate::core::refresh_token::RefreshToken;
+use crate::core::user::User;
+
+/// Entities that record when they were created and last modified.
+///
+/// Implementors only provide field accessors; `created()` and `touch()` keep
+/// the stamping rules in one place. Entities without an `updated_at` column
+/// (e.g. `RefreshToken`) use the defaults, which treat creation time as the
+/// last modification and ignore `touch()`.
+pub trait Timestamped {
+    fn created_at(&self) -> DateTime<Utc>;
+    fn set_created_at(&mut self, at: DateTime<Utc>);
+
+    fn updated_at(&self) -> DateTime<Utc> {
+        self.created_at()
+    }
+
+    fn set_updated_at(&mut self, _at: DateTime<Utc>) {}
+
+    /// Stamp a new entity: both timestamps set to the same instant
+    fn created(&mut self) {
+        let now = Utc::now();
+        self.set_created_at(now);
+        self.set_updated_at(now);
+    }
+
+    /// Record a modification
+    fn touch(&mut self) {
+        self.set_updated_at(Utc::now());
+    }
+}
+
+impl Timestamped for User {
+    fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    fn set_created_at(&mut self, at: DateTime<Utc>) {
+        self.created_at = at;
+    }
+
+    fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+
+    fn set_updated_at(&mut self, at: DateTime<Utc>) {
+        self.updated_at = at;
+    }
+}
+
+impl Timestamped for RefreshToken {
+    fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    fn set_created_at(&mut self, at: DateTime<Utc>) {
+        self.created_at = at;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::email::Email;
+    use chrono::Duration;
+    use uuid::Uuid;
+
+    fn old_user() -> User {
+        let mut user = User::new(Email::parse("ts@example.com").unwrap(), "hash".to_string(), "Stamp".to_string());
+        let past = Utc::now() - Duration::days(1);
+        user.created_at = past;
+        user.updated_at = past;
+        user
+    }
+
+    #[test]
+    fn test_user_created_sets_both_timestamps() {
+        let mut user = old_user();
+        user.created();
+        assert_eq!(user.created_at, user.updated_at);
+        assert!(Utc::now() - user.created_at < Duration::seconds(5));
+    }
+
+    #[test]
+    fn test_user_touch_only_moves_updated_at() {
+        let mut user = old_user();
+        let created = user.created_at;
+        user.touch();
+        assert_eq!(Timestamped::created_at(&user), created);
+        assert!(Timestamped::updated_at(&user) > created);
+    }
+
+    #[test]
+    fn test_refresh_token_defaults() {
+        let mut token = RefreshToken::new(Uuid::new_v4(), "ts-token".to_string());
+        let created = token.created_at;
+
+        // No updated_at column: touch is a no-op and updated_at mirrors created_at
+        token.touch();
+        assert_eq!(Timestamped::created_at(&token), created);
+        assert_eq!(Timestamped::updated_at(&token), created);
+
+        token.created_at = created - Duration::days(1);
+        token.created();
+        assert!(token.created_at > created - Duration::days(1));
+    }
+}
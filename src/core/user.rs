@@ -3,28 +3,60 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use utoipa::ToSchema;
+use crate::core::email::Email;
+use crate::core::role::Role;
+use crate::core::timestamped::Timestamped;
+use crate::infrastructure::database::Upsert;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct User {
     pub id: Uuid,
-    pub email: String,
+    #[schema(value_type = String, format = "email")]
+    pub email: Email,
     pub password_hash: String,
     pub full_name: String,
+    /// Name shown in the UI when it differs from the legal `full_name`
+    #[serde(default)]
+    #[sqlx(default)]
+    pub display_name: Option<String>,
     pub preferences: Option<serde_json::Value>,
+    #[serde(default)]
+    pub role: Role,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl Upsert for User {
+    fn columns() -> &'static [&'static str] {
+        &["id", "email", "password_hash", "full_name", "display_name", "preferences", "role", "created_at", "updated_at"]
+    }
+
+    fn bind_columns<'q>(&'q self, query: sqlx::query::QueryAs<'q, sqlx::Postgres, Self, sqlx::postgres::PgArguments>) -> sqlx::query::QueryAs<'q, sqlx::Postgres, Self, sqlx::postgres::PgArguments> {
+        query
+            .bind(self.id)
+            .bind(&self.email)
+            .bind(&self.password_hash)
+            .bind(&self.full_name)
+            .bind(&self.display_name)
+            .bind(&self.preferences)
+            .bind(self.role)
+            .bind(self.created_at)
+            .bind(self.updated_at)
+    }
+}
+
 impl User {
     /// Create a new user with default timestamps
-    pub fn new(email: String, password_hash: String, full_name: String) -> Self {
+    pub fn new(email: Email, password_hash: String, full_name: String) -> Self {
         let now = Utc::now();
         Self {
             id: Uuid::new_v4(),
             email,
             password_hash,
             full_name,
+            display_name: None,
             preferences: None,
+            role: Role::default(),
             created_at: now,
             updated_at: now,
         }
@@ -33,7 +65,7 @@ impl User {
     /// Update user preferences
     pub fn update_preferences(&mut self, preferences: serde_json::Value) {
         self.preferences = Some(preferences);
-        self.updated_at = Utc::now();
+        self.touch();
     }
 
     /// Check if email is valid format (basic validation)
@@ -65,12 +97,12 @@ impl User {
         domain_parts.iter().all(|part| !part.is_empty())
     }
 
-    /// Get user's display name (full_name or email if no full_name)
+    /// Get user's display name (display_name, else full_name, else email)
     pub fn display_name(&self) -> &str {
-        if self.full_name.is_empty() {
-            &self.email
-        } else {
-            &self.full_name
+        match self.display_name.as_deref() {
+            Some(name) if !name.is_empty() => name,
+            _ if !self.full_name.is_empty() => &self.full_name,
+            _ => self.email.as_str(),
         }
     }
 }
@@ -86,10 +118,12 @@ mod tests {
     fn test_user_serialization() {
         let user = User {
             id: Uuid::new_v4(),
-            email: "test@example.com".to_string(),
+            email: Email::parse("test@example.com").unwrap(),
             password_hash: "hash".to_string(),
             full_name: "Test User".to_string(),
+            display_name: None,
             preferences: Some(serde_json::json!({"theme": "dark"})),
+            role: Role::Manager,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -100,11 +134,12 @@ mod tests {
         assert_eq!(user.email, deserialized.email);
         assert_eq!(user.full_name, deserialized.full_name);
         assert_eq!(user.preferences, deserialized.preferences);
+        assert_eq!(user.role, deserialized.role);
     }
 
     #[test]
     fn test_user_new() {
-        let email = "newuser@example.com".to_string();
+        let email = Email::parse("newuser@example.com").unwrap();
         let password_hash = "hashedpassword123".to_string();
         let full_name = "New User".to_string();
         
@@ -122,7 +157,7 @@ mod tests {
     #[test]
     fn test_user_update_preferences() {
         let mut user = User::new(
-            "pref@example.com".to_string(),
+            Email::parse("pref@example.com").unwrap(),
             "hash".to_string(),
             "Preference User".to_string(),
         );
@@ -165,7 +200,7 @@ mod tests {
     #[test]
     fn test_user_display_name_with_full_name() {
         let user = User::new(
-            "display@example.com".to_string(),
+            Email::parse("display@example.com").unwrap(),
             "hash".to_string(),
             "Display Name".to_string(),
         );
@@ -176,7 +211,7 @@ mod tests {
     #[test]
     fn test_user_display_name_without_full_name() {
         let user = User::new(
-            "fallback@example.com".to_string(),
+            Email::parse("fallback@example.com").unwrap(),
             "hash".to_string(),
             "".to_string(),
         );
@@ -187,7 +222,7 @@ mod tests {
     #[test]
     fn test_user_clone() {
         let user = User::new(
-            "clone@example.com".to_string(),
+            Email::parse("clone@example.com").unwrap(),
             "clonehash".to_string(),
             "Clone User".to_string(),
         );
@@ -206,7 +241,7 @@ mod tests {
     #[test]
     fn test_user_preferences_json_roundtrip() {
         let mut user = User::new(
-            "json@example.com".to_string(),
+            Email::parse("json@example.com").unwrap(),
             "hash".to_string(),
             "JSON User".to_string(),
         );
@@ -238,7 +273,7 @@ mod tests {
     #[test]
     fn test_user_multiple_preference_updates() {
         let mut user = User::new(
-            "multiple@example.com".to_string(),
+            Email::parse("multiple@example.com").unwrap(),
             "hash".to_string(),
             "Multiple Updates".to_string(),
         );
@@ -29,43 +29,105 @@ pub mod validation;
         crate::api::auth::register,
         crate::api::auth::login,
         crate::api::auth::refresh,
+        crate::api::auth::validate_token,
+        crate::api::auth::jwt_info,
+        crate::api::auth::change_password,
+        crate::api::auth::logout_all,
+        crate::api::email_change::request_email_change,
+        crate::api::email_change::verify_email_change,
+        crate::api::auth::jwks,
+        crate::api::oauth::token,
         
         // User management endpoints
         crate::api::user::create_user,
+        crate::api::user::list_users,
+        crate::api::user::count_users,
         crate::api::user::get_user,
+        crate::api::user::user_exists,
         crate::api::user::get_current_user,
+        crate::api::user::get_current_user_preferences,
         crate::api::user::get_current_user_stats,
+        crate::api::avatar::upload_avatar,
+        crate::api::avatar::get_avatar,
         crate::api::user::update_user,
+        crate::api::user::patch_user,
+        crate::api::user::batch_create_users,
+        crate::api::jobs::get_job,
         crate::api::user::delete_user,
         
         // Health check endpoints
         crate::api::health::live,
+        crate::api::health::startup,
         crate::api::health::ready,
+        crate::api::health::info,
+        crate::api::admin::get_maintenance_mode,
+        crate::api::admin::set_maintenance_mode,
+        crate::api::admin::list_audit_log,
+        crate::api::admin::hard_delete_user,
+        crate::api::admin::export_users,
         
         // Refresh token management endpoints
         crate::api::refresh_token::create_refresh_token,
         crate::api::refresh_token::get_refresh_token,
         crate::api::refresh_token::update_refresh_token,
         crate::api::refresh_token::delete_refresh_token,
+        crate::api::sessions::list_user_sessions,
+        crate::api::sessions::list_my_sessions,
+        crate::api::sessions::current_session,
     ),
     components(
         schemas(
             // Authentication schemas
             crate::core::auth::RegisterRequest,
             crate::core::auth::LoginRequest,
+            crate::core::auth::ChangePasswordRequest,
+            crate::api::email_change::ChangeEmailRequest,
+            crate::api::email_change::EmailChangePending,
+            crate::api::email_change::VerifyEmailChangeRequest,
             crate::api::auth::TokenResponse,
+            crate::api::auth::TokenValidation,
+            crate::api::auth::JwtInfo,
             crate::api::auth::ErrorResponse,
+            crate::api::auth::ErrorCode,
+            crate::core::auth::JsonWebKey,
+            crate::core::auth::JsonWebKeySet,
+            crate::api::oauth::TokenRequest,
+            crate::api::oauth::OAuthTokenResponse,
+            crate::api::oauth::OAuthError,
             
             // User schemas
             crate::core::user::User,
             crate::api::user::PublicUser,
+            crate::api::avatar::AvatarUploaded,
+            crate::api::pagination::ListMeta,
+            crate::api::pagination::UserListEnvelope,
+            crate::api::pagination::SessionListEnvelope,
+            crate::core::auth::UserPreferences,
+            crate::core::role::Role,
             crate::api::user::UserInfoWithStats,
+            crate::api::user::UpdateUserRequest,
+            crate::api::user::AdminPatchUserRequest,
+            crate::api::user::CreateUserPayload,
+            crate::api::user::BatchCreateUsersRequest,
+            crate::api::user::BatchItemResult,
+            crate::api::user::BatchCreateUsersResult,
+            crate::api::user::UserCount,
+            crate::infrastructure::jobs::Job,
             
             // Health schemas
             crate::api::health::HealthStatus,
+            crate::api::health::ProbeStatus,
+            crate::api::health::DependencyCheck,
+            crate::api::health::MigrationStatus,
+            crate::api::health::BuildInfo,
+            crate::api::admin::MaintenanceStatus,
+            crate::api::admin::AuditEntry,
+            crate::api::admin::AuditLogPage,
+            crate::api::admin::UserPurgeSummary,
             
             // Refresh token schemas
             crate::core::refresh_token::RefreshToken,
+            crate::api::sessions::SessionSummary,
             
             // Validation schemas
             crate::middleware::validation::ValidationErrorResponse,
@@ -444,7 +444,7 @@ impl ExampleValidator {
         match serde_json::from_value::<User>(user_example.clone()) {
             Ok(user) => {
                 // Validate email format
-                if !User::is_valid_email(&user.email) {
+                if !User::is_valid_email(user.email.as_str()) {
                     result.schema_errors.push("User example has invalid email format".to_string());
                     result.success = false;
                 }
@@ -0,0 +1,42 @@
+//! Append-only audit trail stored in the `audit_log` table.
+
+use serde_json::Value;
+use sqlx::PgExecutor;
+use tracing::warn;
+use uuid::Uuid;
+
+/// Action names written to `audit_log.action`
+pub mod actions {
+    pub const USER_REGISTERED: &str = "user_registered";
+    pub const LOGIN_SUCCEEDED: &str = "login_succeeded";
+    pub const LOGIN_FAILED: &str = "login_failed";
+    pub const PASSWORD_CHANGED: &str = "password_changed";
+    pub const LOGGED_OUT_EVERYWHERE: &str = "logged_out_everywhere";
+    pub const EMAIL_CHANGE_REQUESTED: &str = "email_change_requested";
+    pub const EMAIL_CHANGED: &str = "email_changed";
+    pub const USER_CREATED: &str = "user_created";
+    pub const USER_UPDATED: &str = "user_updated";
+    pub const USER_DELETED: &str = "user_deleted";
+    pub const USER_PURGED: &str = "user_purged";
+    pub const USERS_EXPORTED: &str = "users_exported";
+    pub const MAINTENANCE_TOGGLED: &str = "maintenance_toggled";
+}
+
+/// Insert an audit row. Use inside a transaction when the event must be atomic
+/// with the change it describes.
+pub async fn record<'e>(executor: impl PgExecutor<'e>, user_id: Option<Uuid>, action: &str, details: Option<Value>) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO audit_log (user_id, action, details) VALUES ($1, $2, $3)")
+        .bind(user_id)
+        .bind(action)
+        .bind(details)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Best-effort variant of [`record`] for events that must not fail the request
+pub async fn record_or_warn<'e>(executor: impl PgExecutor<'e>, user_id: Option<Uuid>, action: &str, details: Option<Value>) {
+    if let Err(e) = record(executor, user_id, action, details).await {
+        warn!(action = %action, user_id = ?user_id, error = %e, "Failed to write audit log entry");
+    }
+}
@@ -0,0 +1,79 @@
+use dashmap::DashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Small in-process cache with a fixed time-to-live per entry.
+///
+/// Entries are evicted lazily on read. Each replica keeps its own copy, so
+/// writers must also invalidate remote copies (see [`super::notify`]).
+pub struct TtlCache<K, V> {
+    entries: DashMap<K, (V, Instant)>,
+    ttl: Duration,
+}
+
+impl<K, V> TtlCache<K, V>
+where
+    K: Eq + Hash + std::fmt::Debug,
+    V: Clone,
+{
+    pub fn new(ttl: Duration) -> Self {
+        Self { entries: DashMap::new(), ttl }
+    }
+
+    /// Return a fresh entry, dropping it if it has expired
+    pub fn get(&self, key: &K) -> Option<V> {
+        let value = self.entries.get(key).and_then(|entry| {
+            let (value, inserted_at) = entry.value();
+            (inserted_at.elapsed() < self.ttl).then(|| value.clone())
+        });
+        if value.is_none() {
+            self.entries.remove_if(key, |_, (_, inserted_at)| inserted_at.elapsed() >= self.ttl);
+        }
+        value
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        self.entries.insert(key, (value, Instant::now()));
+    }
+
+    pub fn invalidate(&self, key: &K) {
+        if self.entries.remove(key).is_some() {
+            debug!(key = ?key, "Cache entry invalidated");
+        }
+    }
+
+    pub fn clear(&self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_get_invalidate() {
+        let cache = TtlCache::new(Duration::from_secs(60));
+        cache.insert(1, "one");
+        assert_eq!(cache.get(&1), Some("one"));
+        cache.invalidate(&1);
+        assert_eq!(cache.get(&1), None);
+    }
+
+    #[test]
+    fn test_expired_entries_are_dropped() {
+        let cache = TtlCache::new(Duration::ZERO);
+        cache.insert(1, "one");
+        assert_eq!(cache.get(&1), None);
+        assert!(cache.is_empty());
+    }
+}
@@ -1,6 +1,12 @@
 use async_trait::async_trait;
-use sqlx::{PgPool, FromRow, Error};
+use rand_core::{OsRng, RngCore};
+use sqlx::{PgPool, FromRow, Error, Postgres, Transaction};
+use sqlx::postgres::PgArguments;
+use sqlx::query::QueryAs;
+use std::future::Future;
 use std::marker::PhantomData;
+use std::pin::Pin;
+use std::time::Duration;
 use tracing::{info, warn, error, debug};
 
 #[async_trait]
@@ -23,6 +29,129 @@ where
     async fn update(&self, id: Id, update_fn: impl FnOnce(T) -> T + Send) -> Result<Option<T>, Error>;
 }
 
+/// Entities `PgCrud::upsert` can write.
+///
+/// Column names are static strings supplied by the implementation, never
+/// caller input, so they are safe to splice into the SQL text.
+pub trait Upsert: Sized {
+    /// Every column written, in the order `bind_columns` binds them
+    fn columns() -> &'static [&'static str];
+
+    /// Columns of the unique constraint that detects an existing row
+    fn conflict_target() -> &'static [&'static str] {
+        &["id"]
+    }
+
+    /// Columns overwritten when the row already exists. Defaults to every
+    /// column outside the conflict target except `created_at`, so the
+    /// original creation time survives.
+    fn update_columns() -> Vec<&'static str> {
+        Self::columns()
+            .iter()
+            .copied()
+            .filter(|column| !Self::conflict_target().contains(column) && *column != "created_at")
+            .collect()
+    }
+
+    /// Bind a value for each of `columns()`, in order
+    fn bind_columns<'q>(&'q self, query: QueryAs<'q, Postgres, Self, PgArguments>) -> QueryAs<'q, Postgres, Self, PgArguments>;
+}
+
+/// `INSERT ... ON CONFLICT (...) DO UPDATE` statement for `table`.
+///
+/// With no update columns the conflicting row is "touched" by rewriting its
+/// conflict columns, so `RETURNING *` still yields it.
+pub fn upsert_sql(table: &str, columns: &[&str], conflict_target: &[&str], update_columns: &[&str]) -> String {
+    let placeholders: Vec<String> = (1..=columns.len()).map(|n| format!("${}", n)).collect();
+    let updates = if update_columns.is_empty() { conflict_target } else { update_columns };
+    let assignments: Vec<String> = updates.iter().map(|column| format!("{0} = EXCLUDED.{0}", column)).collect();
+    format!(
+        "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT ({}) DO UPDATE SET {} RETURNING *",
+        table,
+        columns.join(", "),
+        placeholders.join(", "),
+        conflict_target.join(", "),
+        assignments.join(", "),
+    )
+}
+
+/// SQLSTATE Postgres reports for a `unique_violation`
+pub const UNIQUE_VIOLATION: &str = "23505";
+
+/// Whether `e` is a unique constraint violation.
+///
+/// Matches on the SQLSTATE rather than the message text, which varies with
+/// server locale and version.
+pub fn is_unique_violation(e: &Error) -> bool {
+    e.as_database_error()
+        .and_then(|db| db.code())
+        .is_some_and(|code| code == UNIQUE_VIOLATION)
+}
+
+/// SQLSTATE Postgres reports when a serializable transaction can't commit
+pub const SERIALIZATION_FAILURE: &str = "40001";
+
+/// SQLSTATE Postgres reports when it aborts one side of a deadlock
+pub const DEADLOCK_DETECTED: &str = "40P01";
+
+/// Attempts `with_retryable_tx` makes before handing back the last error
+pub const MAX_TX_ATTEMPTS: u32 = 3;
+
+/// Backoff before the first retry; doubled for each one after
+const TX_RETRY_BASE_DELAY: Duration = Duration::from_millis(10);
+
+/// Whether `e` aborted a transaction that may succeed if simply run again
+pub fn is_retryable(e: &Error) -> bool {
+    e.as_database_error()
+        .and_then(|db| db.code())
+        .is_some_and(|code| code == SERIALIZATION_FAILURE || code == DEADLOCK_DETECTED)
+}
+
+/// Future returned by a `with_retryable_tx` body, borrowing its transaction
+pub type TxFuture<'c, T> = Pin<Box<dyn Future<Output = Result<T, Error>> + Send + 'c>>;
+
+/// Backoff before retry number `attempt` (1-based): exponential, plus up to
+/// the same again in random jitter so colliding transactions drift apart
+fn tx_retry_delay(attempt: u32) -> Duration {
+    let base = TX_RETRY_BASE_DELAY * 2u32.pow(attempt.saturating_sub(1));
+    let jitter = OsRng.next_u64() % (base.as_micros() as u64 + 1);
+    base + Duration::from_micros(jitter)
+}
+
+/// Run `f` in a transaction, committing when it returns `Ok`.
+///
+/// An `Err` rolls the transaction back. Serialization failures and deadlocks
+/// (see `is_retryable`) start a fresh transaction and call `f` again, up to
+/// `MAX_TX_ATTEMPTS` times, so `f` must be safe to repeat. Any other error is
+/// returned straight away.
+pub async fn with_retryable_tx<T, F>(pool: &PgPool, mut f: F) -> Result<T, Error>
+where
+    F: for<'c> FnMut(&'c mut Transaction<'static, Postgres>) -> TxFuture<'c, T>,
+{
+    let mut attempt = 1;
+    loop {
+        let mut tx = pool.begin().await?;
+        let result = match f(&mut tx).await {
+            Ok(value) => tx.commit().await.map(|_| value),
+            Err(e) => {
+                if let Err(rollback_err) = tx.rollback().await {
+                    warn!(error = %rollback_err, "Failed to rollback transaction");
+                }
+                Err(e)
+            }
+        };
+        match result {
+            Err(e) if attempt < MAX_TX_ATTEMPTS && is_retryable(&e) => {
+                let delay = tx_retry_delay(attempt);
+                warn!(attempt, delay_ms = delay.as_millis() as u64, error = %e, "Retrying transaction");
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
 pub struct PgCrud<T> {
     pub pool: PgPool,
     pub table: String,
@@ -42,6 +171,31 @@ where
     // Remove the create_with helper. No generic insert helper is provided.
 }
 
+impl<T> PgCrud<T>
+where
+    T: Upsert + Send + Sync + Unpin + for<'r> FromRow<'r, sqlx::postgres::PgRow> + 'static,
+{
+    /// Insert `entity`, or update the existing row matching its conflict
+    /// target, returning the stored row either way
+    pub async fn upsert(&self, entity: &T) -> Result<T, Error> {
+        debug!(table = %self.table, "Starting database upsert operation");
+        let query = upsert_sql(&self.table, T::columns(), T::conflict_target(), &T::update_columns());
+        debug!(table = %self.table, query = %query, "Constructed upsert query");
+
+        let row = entity
+            .bind_columns(sqlx::query_as::<_, T>(&query))
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(table = %self.table, error = %e, "Database upsert operation failed");
+                e
+            })?;
+
+        info!(table = %self.table, "Database upsert operation successful");
+        Ok(row)
+    }
+}
+
 #[async_trait]
 impl<T, Id> Crud<T, Id> for PgCrud<T>
 where
@@ -114,4 +268,133 @@ where
         warn!(table = %self.table, "Update method called but not implemented");
         unimplemented!("Provide entity-specific update logic using closures or higher-order functions")
     }
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::email::Email;
+    use crate::core::user::User;
+    use crate::test_support::{test_pool, unique_email};
+
+    #[test]
+    fn test_upsert_sql_defaults() {
+        let updates = User::update_columns();
+        assert_eq!(updates, ["email", "password_hash", "full_name", "display_name", "preferences", "role", "updated_at"]);
+        assert_eq!(
+            upsert_sql("users", User::columns(), User::conflict_target(), &updates),
+            "INSERT INTO users (id, email, password_hash, full_name, display_name, preferences, role, created_at, updated_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) ON CONFLICT (id) DO UPDATE SET \
+             email = EXCLUDED.email, password_hash = EXCLUDED.password_hash, full_name = EXCLUDED.full_name, \
+             display_name = EXCLUDED.display_name, preferences = EXCLUDED.preferences, role = EXCLUDED.role, updated_at = EXCLUDED.updated_at RETURNING *"
+        );
+    }
+
+    #[test]
+    fn test_upsert_sql_without_update_columns() {
+        assert_eq!(
+            upsert_sql("idempotency_keys", &["key", "response"], &["key"], &[]),
+            "INSERT INTO idempotency_keys (key, response) VALUES ($1, $2) ON CONFLICT (key) DO UPDATE SET key = EXCLUDED.key RETURNING *"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upsert_inserts_then_updates() {
+        let pool = test_pool().await;
+        let crud: PgCrud<User> = PgCrud::new(pool.clone(), "users");
+
+        let email = Email::parse(&format!("upsert-{}@test.com", uuid::Uuid::new_v4())).unwrap();
+        let user = User::new(email, "hash".to_string(), "Before".to_string());
+        let inserted = crud.upsert(&user).await.expect("insert path");
+        assert_eq!(inserted.id, user.id);
+        assert_eq!(inserted.full_name, "Before");
+
+        let mut changed = user.clone();
+        changed.full_name = "After".to_string();
+        changed.preferences = Some(serde_json::json!({ "theme": "dark" }));
+        changed.created_at = chrono::Utc::now() + chrono::Duration::days(1);
+        let updated = crud.upsert(&changed).await.expect("update path");
+
+        assert_eq!(updated.id, user.id);
+        assert_eq!(updated.full_name, "After");
+        assert_eq!(updated.preferences, changed.preferences);
+        // created_at isn't part of the update set
+        assert_eq!(updated.created_at.timestamp_micros(), inserted.created_at.timestamp_micros());
+
+        let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE id = $1")
+            .bind(user.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rows, 1);
+
+        Crud::<User, uuid::Uuid>::delete(&crud, user.id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_retryable_tx_retries_serialization_failure() {
+        let pool = test_pool().await;
+        let email = unique_email("retry-tx");
+        let mut attempts = 0;
+
+        let id = with_retryable_tx(&pool, |tx| {
+            attempts += 1;
+            let first = attempts == 1;
+            let email = email.clone();
+            Box::pin(async move {
+                let id: uuid::Uuid = sqlx::query_scalar("INSERT INTO users (email, password_hash, full_name) VALUES ($1, 'hash', 'Retry') RETURNING id")
+                    .bind(&email)
+                    .fetch_one(&mut **tx)
+                    .await?;
+                if first {
+                    sqlx::query("DO $$ BEGIN RAISE EXCEPTION 'simulated' USING ERRCODE = 'serialization_failure'; END $$")
+                        .execute(&mut **tx)
+                        .await?;
+                }
+                Ok(id)
+            })
+        })
+        .await
+        .expect("second attempt succeeds");
+
+        assert_eq!(attempts, 2);
+        // Only the committed attempt's row survives
+        let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE email = $1")
+            .bind(&email)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rows, 1);
+        sqlx::query("DELETE FROM users WHERE id = $1").bind(id).execute(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_retryable_tx_returns_other_errors_immediately() {
+        let pool = test_pool().await;
+        let mut attempts = 0;
+
+        let err = with_retryable_tx(&pool, |tx| {
+            attempts += 1;
+            Box::pin(async move {
+                sqlx::query("DO $$ BEGIN RAISE EXCEPTION 'simulated' USING ERRCODE = 'unique_violation'; END $$")
+                    .execute(&mut **tx)
+                    .await?;
+                Ok(())
+            })
+        })
+        .await
+        .unwrap_err();
+
+        assert_eq!(attempts, 1);
+        assert!(is_unique_violation(&err));
+        assert!(!is_retryable(&err));
+    }
+
+    #[test]
+    fn test_tx_retry_delay_grows_with_jitter() {
+        for attempt in 1..MAX_TX_ATTEMPTS {
+            let base = TX_RETRY_BASE_DELAY * 2u32.pow(attempt - 1);
+            let delay = tx_retry_delay(attempt);
+            assert!(delay >= base && delay <= base * 2, "attempt {}: {:?}", attempt, delay);
+        }
+    }
+}
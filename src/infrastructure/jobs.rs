@@ -0,0 +1,181 @@
+//! Background jobs stored in the `jobs` table.
+//!
+//! A request that asks for `Prefer: respond-async` gets a job row back
+//! immediately; the work runs on a spawned task that records its outcome on
+//! the row, so any replica can answer status polls. Jobs this process hasn't
+//! finished when it shuts down are marked failed by [`fail_interrupted`]
+//! rather than left `running` forever.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, PgPool};
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::{LazyLock, Mutex};
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Values written to `jobs.status`
+pub mod states {
+    pub const PENDING: &str = "pending";
+    pub const RUNNING: &str = "running";
+    pub const COMPLETED: &str = "completed";
+    pub const FAILED: &str = "failed";
+}
+
+/// Job kinds written to `jobs.kind`
+pub mod kinds {
+    pub const USER_BATCH_CREATE: &str = "user_batch_create";
+}
+
+/// A job and, once finished, its outcome
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Job {
+    pub id: Uuid,
+    pub kind: String,
+    /// `pending`, `running`, `completed` or `failed`
+    pub status: String,
+    pub requested_by: Option<Uuid>,
+    /// Output of a completed job
+    pub result: Option<Value>,
+    /// Why a failed job failed
+    pub error: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Job {
+    /// Whether the job has stopped, successfully or not
+    pub fn is_finished(&self) -> bool {
+        self.status == states::COMPLETED || self.status == states::FAILED
+    }
+}
+
+/// Record a new pending job
+pub async fn create(pool: &PgPool, kind: &str, requested_by: Option<Uuid>) -> Result<Job, sqlx::Error> {
+    sqlx::query_as::<_, Job>("INSERT INTO jobs (kind, requested_by) VALUES ($1, $2) RETURNING *")
+        .bind(kind)
+        .bind(requested_by)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn find(pool: &PgPool, id: Uuid) -> Result<Option<Job>, sqlx::Error> {
+    sqlx::query_as::<_, Job>("SELECT * FROM jobs WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+async fn set_status(pool: &PgPool, id: Uuid, status: &str, result: Option<Value>, error: Option<String>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE jobs SET status = $2, result = $3, error = $4, updated_at = NOW(),
+         completed_at = CASE WHEN $2 IN ('completed', 'failed') THEN NOW() END
+         WHERE id = $1",
+    )
+    .bind(id)
+    .bind(status)
+    .bind(result)
+    .bind(error)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Jobs spawned by this process whose outcome hasn't been recorded yet
+static IN_FLIGHT: LazyLock<Mutex<HashSet<Uuid>>> = LazyLock::new(Default::default);
+
+/// Error recorded on jobs cut off by a shutdown
+pub const INTERRUPTED_ERROR: &str = "Interrupted by server shutdown; resubmit the request";
+
+/// Run `work` for job `id` on a background task, recording its result or error.
+///
+/// Failing to write a status change is logged rather than retried; the job
+/// then stays in its previous state.
+pub fn spawn<F>(pool: PgPool, id: Uuid, work: F)
+where
+    F: Future<Output = Result<Value, String>> + Send + 'static,
+{
+    IN_FLIGHT.lock().unwrap_or_else(|e| e.into_inner()).insert(id);
+    tokio::spawn(async move {
+        if let Err(e) = set_status(&pool, id, states::RUNNING, None, None).await {
+            warn!(job_id = %id, error = %e, "Failed to mark job running");
+        }
+        let (status, result, error) = match work.await {
+            Ok(result) => (states::COMPLETED, Some(result), None),
+            Err(message) => {
+                warn!(job_id = %id, error = %message, "Job failed");
+                (states::FAILED, None, Some(message))
+            },
+        };
+        match set_status(&pool, id, status, result, error).await {
+            Ok(()) => info!(job_id = %id, status, "Job finished"),
+            Err(e) => error!(job_id = %id, status, error = %e, "Failed to record job outcome"),
+        }
+        IN_FLIGHT.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+    });
+}
+
+/// Mark every job this process spawned but hasn't finished as failed.
+///
+/// Called once the servers have drained, since the tasks running them die
+/// with the process. Only this process's jobs are touched; other replicas
+/// keep theirs. Returns how many jobs were marked.
+pub async fn fail_interrupted(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let ids: Vec<Uuid> = IN_FLIGHT.lock().unwrap_or_else(|e| e.into_inner()).iter().copied().collect();
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    let failed = sqlx::query(
+        "UPDATE jobs SET status = $2, error = $3, updated_at = NOW(), completed_at = NOW()
+         WHERE id = ANY($1) AND status IN ('pending', 'running')",
+    )
+    .bind(&ids)
+    .bind(states::FAILED)
+    .bind(INTERRUPTED_ERROR)
+    .execute(pool)
+    .await?
+    .rows_affected();
+    warn!(failed, "Marked unfinished jobs failed at shutdown");
+    Ok(failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::lazy_pool;
+    use std::time::Duration;
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_unfinished_jobs_fail_at_shutdown() {
+        let pool = lazy_pool();
+        let stuck = create(&pool, "test", None).await.unwrap();
+        let done = create(&pool, "test", None).await.unwrap();
+        spawn(pool.clone(), stuck.id, std::future::pending());
+        spawn(pool.clone(), done.id, async { Ok(Value::Null) });
+
+        let mut job = stuck.clone();
+        for _ in 0..50 {
+            job = find(&pool, stuck.id).await.unwrap().unwrap();
+            if job.status == states::RUNNING && find(&pool, done.id).await.unwrap().unwrap().is_finished() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(job.status, states::RUNNING);
+
+        // Other tests may have jobs in flight too, so check these two by id
+        fail_interrupted(&pool).await.unwrap();
+        let job = find(&pool, stuck.id).await.unwrap().unwrap();
+        assert_eq!(job.status, states::FAILED);
+        assert_eq!(job.error.as_deref(), Some(INTERRUPTED_ERROR));
+        assert!(job.completed_at.is_some());
+        assert_eq!(find(&pool, done.id).await.unwrap().unwrap().status, states::COMPLETED);
+
+        IN_FLIGHT.lock().unwrap().remove(&stuck.id);
+        sqlx::query("DELETE FROM jobs WHERE id = ANY($1)").bind(vec![stuck.id, done.id]).execute(&pool).await.unwrap();
+    }
+}
@@ -0,0 +1,48 @@
+//! Outgoing email.
+//!
+//! No provider is bundled; deployments install one with [`set_mailer`]. Until
+//! then [`LogMailer`] writes each message to the debug log, which is enough
+//! for local development.
+
+use async_trait::async_trait;
+use std::sync::{Arc, LazyLock, RwLock};
+use tracing::debug;
+
+/// A plain-text message to a single recipient
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutgoingEmail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Delivers [`OutgoingEmail`]s
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: &OutgoingEmail) -> anyhow::Result<()>;
+}
+
+/// Logs messages instead of sending them
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, email: &OutgoingEmail) -> anyhow::Result<()> {
+        debug!(to = %email.to, subject = %email.subject, body = %email.body, "No mailer configured; email logged instead of sent");
+        Ok(())
+    }
+}
+
+static MAILER: LazyLock<RwLock<Arc<dyn Mailer>>> = LazyLock::new(|| RwLock::new(Arc::new(LogMailer)));
+
+/// Replace the process-wide mailer
+pub fn set_mailer(mailer: Arc<dyn Mailer>) {
+    *MAILER.write().unwrap_or_else(|e| e.into_inner()) = mailer;
+}
+
+/// Send `email` through the configured mailer
+pub async fn send(email: &OutgoingEmail) -> anyhow::Result<()> {
+    let mailer = MAILER.read().unwrap_or_else(|e| e.into_inner()).clone();
+    mailer.send(email).await
+}
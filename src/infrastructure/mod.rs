@@ -1 +1,8 @@
-pub mod database; 
\ No newline at end of file
+pub mod database;
+pub mod cache;
+pub mod notify;
+pub mod audit;
+pub mod mail;
+pub mod jobs;
+pub mod preflight;
+pub mod single_flight;
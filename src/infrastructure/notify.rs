@@ -0,0 +1,127 @@
+//! Cross-instance cache invalidation over PostgreSQL `LISTEN/NOTIFY`.
+//!
+//! Writers call [`notify_user_changed`] after modifying a user; every replica
+//! runs [`spawn_user_change_listener`], which drops the matching entry from its
+//! local cache when the notification arrives.
+
+use crate::infrastructure::cache::TtlCache;
+use sqlx::postgres::PgListener;
+use sqlx::{PgExecutor, PgPool};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+/// Channel carrying the id of a user whose cached data is stale
+pub const USER_CHANGED_CHANNEL: &str = "user_changed";
+
+/// Publish a `user_changed` notification for `user_id`.
+///
+/// When called inside a transaction the notification is only delivered on commit.
+pub async fn notify_user_changed<'e>(executor: impl PgExecutor<'e>, user_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT pg_notify($1, $2)")
+        .bind(USER_CHANGED_CHANNEL)
+        .bind(user_id.to_string())
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Apply a single `user_changed` payload to `cache`
+pub fn handle_user_changed<V: Clone>(cache: &TtlCache<Uuid, V>, payload: &str) {
+    match Uuid::parse_str(payload.trim()) {
+        Ok(user_id) => cache.invalidate(&user_id),
+        Err(_) => warn!(payload = %payload, "Ignoring malformed user_changed notification"),
+    }
+}
+
+/// Listen for `user_changed` notifications and invalidate `cache` entries.
+///
+/// If the listener connection drops, notifications sent in the meantime are
+/// lost, so the whole cache is cleared once the connection is re-established.
+pub fn spawn_user_change_listener<V>(pool: PgPool, cache: &'static TtlCache<Uuid, V>) -> JoinHandle<()>
+where
+    V: Clone + Send + Sync + 'static,
+{
+    tokio::spawn(async move {
+        loop {
+            let mut listener = match PgListener::connect_with(&pool).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    error!(error = %e, "Failed to connect user_changed listener, retrying");
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    continue;
+                }
+            };
+            if let Err(e) = listener.listen(USER_CHANGED_CHANNEL).await {
+                error!(error = %e, "Failed to LISTEN on user_changed, retrying");
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+            info!(channel = USER_CHANGED_CHANNEL, "Listening for cache invalidation notifications");
+
+            loop {
+                match listener.try_recv().await {
+                    Ok(Some(notification)) => {
+                        debug!(payload = %notification.payload(), "Received user_changed notification");
+                        handle_user_changed(cache, notification.payload());
+                    }
+                    Ok(None) => {
+                        warn!("user_changed listener connection lost, clearing cache");
+                        cache.clear();
+                    }
+                    Err(e) => {
+                        error!(error = %e, "user_changed listener failed, reconnecting");
+                        cache.clear();
+                        break;
+                    }
+                }
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_pool;
+    use std::sync::LazyLock;
+
+    static TEST_CACHE: LazyLock<TtlCache<Uuid, &'static str>> = LazyLock::new(|| TtlCache::new(Duration::from_secs(60)));
+
+    #[test]
+    fn test_handle_user_changed_invalidates_entry() {
+        let cache = TtlCache::new(Duration::from_secs(60));
+        let id = Uuid::new_v4();
+        cache.insert(id, 1);
+        handle_user_changed(&cache, "not-a-uuid");
+        assert_eq!(cache.get(&id), Some(1));
+        handle_user_changed(&cache, &id.to_string());
+        assert_eq!(cache.get(&id), None);
+    }
+
+    #[tokio::test]
+    async fn test_notification_invalidates_cache() {
+        let pool = test_pool().await;
+
+        let id = Uuid::new_v4();
+        let listener = spawn_user_change_listener(pool.clone(), &TEST_CACHE);
+
+        // Keep notifying until the listener is subscribed and has processed one
+        let invalidated = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                TEST_CACHE.insert(id, "stale");
+                notify_user_changed(&pool, id).await.unwrap();
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                if TEST_CACHE.get(&id).is_none() {
+                    break;
+                }
+            }
+        })
+        .await;
+
+        listener.abort();
+        assert!(invalidated.is_ok(), "notification did not invalidate the cache entry");
+    }
+}
@@ -0,0 +1,96 @@
+use dashmap::DashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+/// Coalesces concurrent calls for the same key into a single execution.
+///
+/// The first caller for a key runs the work; callers arriving while it is in
+/// flight wait for and share its result. Once it finishes the key is released,
+/// so later calls run fresh (pair with [`super::cache::TtlCache`] to reuse
+/// results beyond the in-flight window). If the leading caller is cancelled, a
+/// waiting caller takes over the work.
+pub struct SingleFlight<K, V> {
+    in_flight: DashMap<K, Arc<OnceCell<V>>>,
+}
+
+impl<K, V> SingleFlight<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    pub fn new() -> Self {
+        Self { in_flight: DashMap::new() }
+    }
+
+    /// Run `work` for `key`, or join an identical call already in progress
+    pub async fn run<F, Fut>(&self, key: K, work: F) -> V
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+    {
+        let cell = self.in_flight.entry(key.clone()).or_insert_with(|| Arc::new(OnceCell::new())).clone();
+        let value = cell.get_or_init(work).await.clone();
+        self.in_flight.remove_if(&key, |_, current| Arc::ptr_eq(current, &cell));
+        value
+    }
+
+    /// Number of keys with work currently in flight
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+}
+
+impl<K, V> Default for SingleFlight<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_concurrent_calls_share_one_execution() {
+        let flight: Arc<SingleFlight<u32, usize>> = Arc::new(SingleFlight::new());
+        let runs = Arc::new(AtomicUsize::new(0));
+
+        let callers: Vec<_> = (0..16)
+            .map(|_| {
+                let flight = flight.clone();
+                let runs = runs.clone();
+                tokio::spawn(async move {
+                    flight
+                        .run(7, || async move {
+                            tokio::time::sleep(Duration::from_millis(50)).await;
+                            runs.fetch_add(1, Ordering::SeqCst) + 1
+                        })
+                        .await
+                })
+            })
+            .collect();
+
+        for caller in callers {
+            assert_eq!(caller.await.unwrap(), 1);
+        }
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(flight.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_sequential_calls_and_distinct_keys_run_separately() {
+        let flight: SingleFlight<u32, u32> = SingleFlight::new();
+        assert_eq!(flight.run(1, || async { 10 }).await, 10);
+        assert_eq!(flight.run(1, || async { 11 }).await, 11);
+        assert_eq!(flight.run(2, || async { 20 }).await, 20);
+        assert_eq!(flight.in_flight(), 0);
+    }
+}
@@ -1,11 +1,15 @@
 // Main application entry point for Enterprise Rust JWT Backend
 use sqlx::PgPool;
 use std::net::SocketAddr;
-use tokio::net::TcpListener;
 use tokio::signal;
+use tokio::sync::watch;
+use std::time::Duration;
 use tracing_subscriber;
 
-use server::{app, grpc_server};
+use server::{app_with_settings, bind_rest_listener, serve_rest_with_shutdown, RestServerOptions, TcpTuning};
+use server::config::settings::AppSettings;
+#[cfg(feature = "grpc")]
+use server::grpc_server;
 
 #[tokio::main]
 async fn main() {
@@ -15,16 +19,32 @@ async fn main() {
 
     dotenvy::dotenv().ok();
     let config = server::config::load();
+    // Flushes queued error reports when dropped at exit
+    let _error_reporting = server::middleware::error_reporting::init(config.sentry_dsn.as_deref());
+    
+    // `--check` validates config, database and schema, then exits without serving
+    if std::env::args().skip(1).any(|arg| arg == "--check") {
+        let database_url = std::env::var("APP_DATABASE_URL").ok();
+        let report = server::infrastructure::preflight::run(database_url.as_deref(), &config.jwt_keys).await;
+        println!("{}", report);
+        std::process::exit(if report.passed() { 0 } else { 1 });
+    }
+    
     let db_url = std::env::var("APP_DATABASE_URL").expect("APP_DATABASE_URL must be set in .env or environment");
     let pool = PgPool::connect_lazy(&db_url).unwrap();
+
+    // Answer 503 on data routes until the database is reachable
+    server::middleware::startup::mark_starting();
+    tokio::spawn(server::middleware::startup::complete_when_ready(pool.clone()));
+    
+    // Keep the per-replica stats cache coherent with writes from other instances
+    server::infrastructure::notify::spawn_user_change_listener(pool.clone(), &server::api::user::USER_STATS_CACHE);
     
-    // REST API server
-    let rest_app = app(pool.clone());
     let rest_addr = SocketAddr::from(([0, 0, 0, 0], config.server_port));
     tracing::info!("Starting REST API server on {}", rest_addr);
     
-    // Check if gRPC server should be enabled
-    let enable_grpc = match std::env::var("ENABLE_GRPC") {
+    // Check if gRPC server should be enabled; never without the `grpc` feature
+    let enable_grpc = cfg!(feature = "grpc") && match std::env::var("ENABLE_GRPC") {
         Ok(val) => val.to_lowercase() == "true",
         Err(_) => {
             // Enable gRPC by default in local development, disable on Render only if ENABLE_GRPC is not explicitly set
@@ -32,69 +52,29 @@ async fn main() {
         }
     };
     
-    if enable_grpc {
-        tracing::info!("gRPC server enabled");
-        let grpc_pool = pool.clone();
-        let grpc_addr = if config.server_port == u16::MAX {
-            tracing::error!("Cannot start gRPC server: REST port {} is the maximum allowed (65535), cannot assign gRPC port.", config.server_port);
-            std::process::exit(1);
-        } else {
-            SocketAddr::from(([0, 0, 0, 0], config.server_port + 1))
-        };
-        
-        // Start both servers concurrently
-        let rest_server = async {
-            let listener = match TcpListener::bind(rest_addr).await {
-                Ok(listener) => listener,
-                Err(e) => {
-                    tracing::error!("Failed to bind REST server to {}: {}", rest_addr, e);
-                    if e.kind() == std::io::ErrorKind::AddrInUse {
-                        tracing::error!("Port {} is already in use. Please ensure no other instance is running or use a different port.", rest_addr.port());
-                    }
-                    std::process::exit(1);
-                }
-            };
-            
-            if let Err(e) = axum::serve(listener, rest_app.into_make_service()).await {
-                tracing::error!("REST server error: {}", e);
-            }
-        };
-        
-        let grpc_server_task = async {
-            if let Err(e) = grpc_server(grpc_pool, grpc_addr, &config).await {
-                tracing::error!("gRPC server error: {}", e);
-                // Check if it's a port binding issue
-                if e.to_string().contains("Address already in use") || e.to_string().contains("AddrInUse") {
-                    tracing::error!("Port {} is already in use for gRPC server. Please ensure no other instance is running or use a different port.", grpc_addr.port());
-                }
-            }
-        };
-        
-        // Create shutdown signal handler
-        let shutdown_signal = async {
-            signal::ctrl_c()
-                .await
-                .expect("Failed to install CTRL+C signal handler");
+    // Readiness also covers the gRPC upstream when it is served, and Redis when configured
+    let mut settings = AppSettings::from_config(&config);
+    settings.readiness_targets.grpc_upstream = enable_grpc.then(|| config.grpc_upstream_endpoint.clone());
+    settings.readiness_targets.redis_url = std::env::var("APP_REDIS__URL").ok().filter(|url| !url.is_empty());
+    
+    // REST API server
+    let rest_app = app_with_settings(pool.clone(), &config, settings);
+    
+    // Every server drains on the same signal; a server that stops on its own
+    // (e.g. failed to bind) takes the others down with it
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    tokio::spawn({
+        let shutdown_tx = shutdown_tx.clone();
+        async move {
+            shutdown_signal().await;
             tracing::info!("Received shutdown signal, gracefully shutting down...");
-        };
-
-        // Run both servers concurrently with graceful shutdown
-        tokio::select! {
-            _ = rest_server => {
-                tracing::info!("REST server finished");
-            }
-            _ = grpc_server_task => {
-                tracing::info!("gRPC server finished");
-            }
-            _ = shutdown_signal => {
-                tracing::info!("Shutdown signal received, terminating servers...");
-            }
+            let _ = shutdown_tx.send(true);
         }
-    } else {
-        tracing::info!("gRPC server disabled (running in Render or ENABLE_GRPC=false)");
-        
-        // Start only REST server
-        let listener = match TcpListener::bind(rest_addr).await {
+    });
+    let grace = Duration::from_secs(config.shutdown_grace_secs);
+    
+    let rest_server = async {
+        let listener = match bind_rest_listener(rest_addr, TcpTuning::from_config(&config)) {
             Ok(listener) => listener,
             Err(e) => {
                 tracing::error!("Failed to bind REST server to {}: {}", rest_addr, e);
@@ -105,28 +85,80 @@ async fn main() {
             }
         };
         
-        // Create shutdown signal handler
-        let shutdown_signal = async {
-            signal::ctrl_c()
-                .await
-                .expect("Failed to install CTRL+C signal handler");
-            tracing::info!("Received shutdown signal, gracefully shutting down...");
-        };
-
-        // Run REST server with graceful shutdown
-        tokio::select! {
-            result = axum::serve(listener, rest_app.into_make_service()) => {
-                if let Err(e) = result {
-                    tracing::error!("REST server error: {}", e);
-                } else {
-                    tracing::info!("REST server finished");
+        match serve_rest_with_shutdown(listener, rest_app, RestServerOptions::from_config(&config), shutdown_requested(shutdown_rx.clone()), grace).await {
+            Ok(()) => tracing::info!("REST server finished"),
+            Err(e) => tracing::error!("REST server error: {}", e),
+        }
+        let _ = shutdown_tx.send(true);
+    };
+    
+    if enable_grpc {
+        #[cfg(feature = "grpc")]
+        {
+            tracing::info!("gRPC server enabled");
+            let grpc_pool = pool.clone();
+            let grpc_addr = if config.server_port == u16::MAX {
+                tracing::error!("Cannot start gRPC server: REST port {} is the maximum allowed (65535), cannot assign gRPC port.", config.server_port);
+                std::process::exit(1);
+            } else {
+                SocketAddr::from(([0, 0, 0, 0], config.server_port + 1))
+            };
+        
+            let grpc_server_task = async {
+                match grpc_server(grpc_pool, grpc_addr, &config, shutdown_requested(shutdown_rx.clone())).await {
+                    Ok(()) => tracing::info!("gRPC server finished"),
+                    Err(e) => {
+                        tracing::error!("gRPC server error: {}", e);
+                        // Check if it's a port binding issue
+                        if e.to_string().contains("Address already in use") || e.to_string().contains("AddrInUse") {
+                            tracing::error!("Port {} is already in use for gRPC server. Please ensure no other instance is running or use a different port.", grpc_addr.port());
+                        }
+                    }
                 }
-            }
-            _ = shutdown_signal => {
-                tracing::info!("Shutdown signal received, terminating server...");
-            }
+                let _ = shutdown_tx.send(true);
+            };
+        
+            // Run both servers; each drains for at most SHUTDOWN_GRACE_SECS
+            tokio::join!(rest_server, grpc_server_task);
         }
+    } else {
+        tracing::info!("gRPC server disabled (running in Render, ENABLE_GRPC=false or built without the grpc feature)");
+        rest_server.await;
+    }
+    
+    // Background jobs die with the process; don't leave them `running`
+    if let Err(e) = server::infrastructure::jobs::fail_interrupted(&pool).await {
+        tracing::error!("Failed to mark interrupted jobs: {}", e);
     }
     
     tracing::info!("Application shutdown complete");
-}
\ No newline at end of file
+}
+
+/// Resolves on Ctrl+C, or SIGTERM on Unix (what orchestrators send)
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("Failed to install CTRL+C signal handler");
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// Resolves once shutdown has been requested on `rx`
+async fn shutdown_requested(mut rx: watch::Receiver<bool>) {
+    // A dropped sender means nothing can request shutdown any more; treat it as a request
+    let _ = rx.wait_for(|requested| *requested).await;
+}
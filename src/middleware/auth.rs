@@ -1,49 +1,390 @@
-use axum::extract::FromRequestParts;
-use axum::http::{request::Parts, StatusCode};
-use crate::core::auth::verify_jwt;
+use axum::extract::{FromRef, FromRequestParts};
+use axum::http::{header::{AUTHORIZATION, COOKIE, WWW_AUTHENTICATE}, request::Parts, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use crate::api::auth::{database_error_response, ErrorCode, ErrorResponse};
+use chrono::{DateTime, Utc};
+use crate::config::settings::{self, AppSettings};
+use crate::core::auth::{is_expired_jwt_error, verify_jwt_with_claims, VerifiedToken, ACCESS_TOKEN_COOKIE};
+use crate::core::role::Role;
 use uuid::Uuid;
+use sqlx::PgPool;
 use async_trait::async_trait;
 use tracing::{info, warn, error, debug};
 
+/// Realm used when `AUTH_REALM` is unset
+pub const DEFAULT_AUTH_REALM: &str = "api";
+
+/// Whether `realm` can go inside the quoted `realm` parameter as is
+pub fn is_valid_realm(realm: &str) -> bool {
+    realm.chars().all(|c| (' '..='~').contains(&c) && c != '"' && c != '\\')
+}
+
+/// Error codes for a `Bearer` challenge (RFC 6750 §3.1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BearerError {
+    /// The Authorization header is malformed
+    InvalidRequest,
+    /// The token is expired, revoked, malformed or fails verification
+    InvalidToken,
+}
+
+impl BearerError {
+    fn as_str(self) -> &'static str {
+        match self {
+            BearerError::InvalidRequest => "invalid_request",
+            BearerError::InvalidToken => "invalid_token",
+        }
+    }
+}
+
+/// `WWW-Authenticate` value for a 401 (RFC 6750 §3).
+///
+/// Only the realm when no credentials were sent, as the RFC asks; otherwise
+/// also the error code and a description for the client's developer.
+pub fn bearer_challenge(realm: &str, error: Option<(BearerError, &str)>) -> String {
+    match error {
+        None => format!("Bearer realm=\"{}\"", realm),
+        Some((error, description)) => {
+            // error_description may not contain quotes or backslashes
+            let description: String = description.chars().filter(|c| !matches!(c, '"' | '\\')).collect();
+            format!("Bearer realm=\"{}\", error=\"{}\", error_description=\"{}\"", realm, error.as_str(), description)
+        }
+    }
+}
+
+/// `invalid_token` challenge for a [`verify_jwt`](crate::core::auth::verify_jwt) failure,
+/// telling an expired token apart from one that is otherwise invalid
+pub fn invalid_token_challenge(realm: &str, e: &anyhow::Error) -> String {
+    let description = if is_expired_jwt_error(e) { "The access token expired" } else { "The access token is invalid" };
+    bearer_challenge(realm, Some((BearerError::InvalidToken, description)))
+}
+
+/// 401 with a plain-text body and a `Bearer` challenge
+fn unauthorized(challenge: String, body: &'static str) -> Response {
+    (StatusCode::UNAUTHORIZED, [(WWW_AUTHENTICATE, challenge)], body).into_response()
+}
+
+/// Value of the access token cookie from the `Cookie` headers, if any
+fn access_token_from_cookies(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|h| h.to_str().ok())
+        .flat_map(|h| h.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, value)| *name == ACCESS_TOKEN_COOKIE && !value.is_empty())
+        .map(|(_, value)| value)
+}
+
+/// The JWT a request authenticates with.
+///
+/// API clients send `Authorization: Bearer <token>`; browser clients that
+/// opted into cookie delivery send the `access_token` cookie. When both are
+/// present the header wins, so a stale cookie can't override an explicit token.
+pub fn access_token(headers: &HeaderMap) -> Option<&str> {
+    access_token_with_source(headers).map(|(token, _)| token)
+}
+
+/// Where [`access_token`] found the request's token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenSource {
+    /// `Authorization: Bearer <token>`
+    Bearer,
+    /// The `access_token` cookie
+    Cookie,
+}
+
+/// [`access_token`] along with where the token came from
+pub fn access_token_with_source(headers: &HeaderMap) -> Option<(&str, TokenSource)> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(|token| (token, TokenSource::Bearer))
+        .or_else(|| access_token_from_cookies(headers).map(|token| (token, TokenSource::Cookie)))
+}
+
 /// Represents an authenticated user in the system.
 /// 
 /// This struct wraps a user ID and is used to represent an authenticated user
-/// in the request handling pipeline.
+/// in the request handling pipeline. The token is read by [`access_token`] and
+/// rejected when [`is_token_revoked`] says its sessions were logged out.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct AuthenticatedUser(pub Uuid);
 
 #[async_trait]
 impl<S> FromRequestParts<S> for AuthenticatedUser   
 where
+    PgPool: FromRef<S>,
     S: Send + Sync,
 {
-    type Rejection = (StatusCode, &'static str);
+    type Rejection = Response;
 
     async fn from_request_parts(
         parts: &mut Parts,
-        _state: &S,
+        state: &S,
     ) -> Result<Self, Self::Rejection> {
-        debug!("Starting authentication middleware processing");
-        
-        let auth_header = parts.headers.get(axum::http::header::AUTHORIZATION)
-            .and_then(|h| h.to_str().ok())
-            .and_then(|h| h.strip_prefix("Bearer "));
-            
-        if let Some(token) = auth_header {
-            debug!("Authorization header found, verifying JWT token");
-            match verify_jwt(token) {
-                Ok(user_id) => {
-                    info!(user_id = %user_id, "Authentication successful");
-                    Ok(AuthenticatedUser(user_id))
-                },
-                Err(e) => {
-                    error!(error = %e, "Authentication failed - invalid or expired token");
-                    Err((StatusCode::UNAUTHORIZED, "Invalid or expired token"))
-                },
-            }
-        } else {
-            warn!("Authentication failed - missing Authorization header");
-            Err((StatusCode::UNAUTHORIZED, "Missing Authorization header"))
+        let verified = authenticate_request(parts, &PgPool::from_ref(state)).await?;
+        Ok(AuthenticatedUser(verified.user_id))
+    }
+}
+
+/// The verified claims of the request's access token, for handlers that need
+/// more than the user id, such as the session (`sid`) the token belongs to.
+/// Accepts and rejects exactly the requests [`AuthenticatedUser`] does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthenticatedToken(pub VerifiedToken);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthenticatedToken
+where
+    PgPool: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        authenticate_request(parts, &PgPool::from_ref(state)).await.map(AuthenticatedToken)
+    }
+}
+
+/// Why [`verify_access_token`] refused a token
+#[derive(Debug)]
+pub enum TokenRejection {
+    /// Bad signature, expired, or otherwise not a token we issued
+    Invalid(anyhow::Error),
+    /// Issued before the user's last logout from every session
+    Revoked,
+    /// The revocation check couldn't reach the database
+    Database(sqlx::Error),
+}
+
+/// Verifies `token` and checks it hasn't been revoked.
+///
+/// Every transport authenticates through this, so REST and gRPC accept
+/// exactly the same tokens.
+pub async fn verify_access_token(pool: &PgPool, token: &str, settings: &AppSettings) -> Result<VerifiedToken, TokenRejection> {
+    let verified = verify_jwt_with_claims(token, &settings.jwt_keys, &settings.jwt_claims).map_err(TokenRejection::Invalid)?;
+    match is_token_revoked(pool, &verified).await {
+        Ok(false) => Ok(verified),
+        Ok(true) => {
+            warn!(user_id = %verified.user_id, "Authentication failed - token issued before logout");
+            Err(TokenRejection::Revoked)
+        },
+        Err(e) => {
+            error!(user_id = %verified.user_id, error = %e, "Failed to check token revocation");
+            Err(TokenRejection::Database(e))
+        },
+    }
+}
+
+/// Verifies the request's access token and checks it hasn't been revoked
+async fn authenticate_request(parts: &Parts, pool: &PgPool) -> Result<VerifiedToken, Response> {
+    debug!("Starting authentication middleware processing");
+    let settings = settings::from_extensions(&parts.extensions)?;
+
+    let Some(token) = access_token(&parts.headers) else {
+        warn!("Authentication failed - missing Authorization header or access token cookie");
+        return Err(unauthorized(bearer_challenge(&settings.auth_realm, None), "Missing Authorization header or access token cookie"));
+    };
+    debug!("Access token found, verifying JWT token");
+    match verify_access_token(pool, token, &settings).await {
+        Ok(verified) => {
+            info!(user_id = %verified.user_id, "Authentication successful");
+            Ok(verified)
+        },
+        Err(TokenRejection::Revoked) => {
+            let challenge = bearer_challenge(&settings.auth_realm, Some((BearerError::InvalidToken, "The access token has been revoked")));
+            Err(unauthorized(challenge, "Token has been revoked"))
+        },
+        Err(TokenRejection::Database(e)) => Err(database_error_response(&e)),
+        Err(TokenRejection::Invalid(e)) => {
+            error!(error = %e, "Authentication failed - invalid or expired token");
+            Err(unauthorized(invalid_token_challenge(&settings.auth_realm, &e), "Invalid or expired token"))
+        },
+    }
+}
+
+/// Returns true when `token` was issued before its user's `tokens_invalid_before` cutoff.
+///
+/// `iat` carries microseconds, so a login right after a logout-all isn't
+/// caught by a cutoff from the same second. Tokens without `iat` predate any
+/// cutoff.
+pub async fn is_token_revoked(pool: &PgPool, token: &VerifiedToken) -> Result<bool, sqlx::Error> {
+    let cutoff = sqlx::query_scalar::<_, Option<DateTime<Utc>>>("SELECT tokens_invalid_before FROM users WHERE id = $1")
+        .bind(token.user_id)
+        .fetch_optional(pool)
+        .await?
+        .flatten();
+    Ok(cutoff.is_some_and(|cutoff| token.issued_at.is_none_or(|iat| iat <= cutoff)))
+}
+
+/// Returns true when `user_id` has the `admin` role.
+///
+/// The role is read from the database on each call so a demotion takes
+/// effect immediately rather than when the JWT expires.
+pub async fn is_admin(pool: &PgPool, user_id: Uuid) -> Result<bool, sqlx::Error> {
+    let is_admin = sqlx::query_scalar::<_, Role>("SELECT role FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        == Some(Role::Admin);
+    debug!(user_id = %user_id, is_admin, "Checked admin role");
+    Ok(is_admin)
+}
+
+/// Answers `403 Forbidden` unless `user_id` has the `admin` role, for
+/// handlers to return as is.
+///
+/// Like [`is_admin`], the role is read from the database on each call.
+pub async fn require_admin(pool: &PgPool, user_id: Uuid) -> Result<(), Response> {
+    match is_admin(pool, user_id).await {
+        Ok(true) => Ok(()),
+        Ok(false) => {
+            warn!(authenticated_user_id = %user_id, "Non-admin request refused");
+            Err((StatusCode::FORBIDDEN, Json(ErrorResponse::coded(ErrorCode::Forbidden, "Forbidden", Some("Admin role required".to_string())))).into_response())
+        },
+        Err(e) => {
+            error!(authenticated_user_id = %user_id, error = %e, "Failed to check admin role");
+            Err(database_error_response(&e))
+        },
+    }
+}
+
+/// Returns true when `actor` may modify the account identified by `target`.
+///
+/// Users may always manage their own account; anyone else needs the `admin` role.
+pub async fn can_manage_user(pool: &PgPool, actor: Uuid, target: Uuid) -> Result<bool, sqlx::Error> {
+    if actor == target {
+        return Ok(true);
+    }
+    is_admin(pool, actor).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::AppSettings;
+    use crate::test_support::{access_token, insert_user_with_role, lazy_pool, sign_claims, test_pool};
+    use axum::{body::Body, http::Request, routing::get, Router};
+    use tower::ServiceExt;
+
+    async fn whoami(AuthenticatedUser(user_id): AuthenticatedUser) -> String {
+        user_id.to_string()
+    }
+
+    async fn authenticate(headers: &[(&str, String)]) -> (StatusCode, String) {
+        let app = Router::new().route("/whoami", get(whoami)).with_state(lazy_pool()).layer(AppSettings::default().layer());
+        let mut req = Request::builder().uri("/whoami");
+        for (name, value) in headers {
+            req = req.header(*name, value);
+        }
+        let res = app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+        let status = res.status();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_header_only() {
+        let user_id = Uuid::new_v4();
+        let headers = [("authorization", format!("Bearer {}", access_token(user_id)))];
+        assert_eq!(authenticate(&headers).await, (StatusCode::OK, user_id.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_cookie_only() {
+        let user_id = Uuid::new_v4();
+        let headers = [("cookie", format!("lang=en; {}={}", ACCESS_TOKEN_COOKIE, access_token(user_id)))];
+        assert_eq!(authenticate(&headers).await, (StatusCode::OK, user_id.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_header_wins_over_cookie() {
+        let header_user = Uuid::new_v4();
+        let cookie_user = Uuid::new_v4();
+        let headers = [
+            ("authorization", format!("Bearer {}", access_token(header_user))),
+            ("cookie", format!("{}={}", ACCESS_TOKEN_COOKIE, access_token(cookie_user))),
+        ];
+        assert_eq!(authenticate(&headers).await, (StatusCode::OK, header_user.to_string()));
+
+        // An invalid header is not rescued by a valid cookie
+        let headers = [
+            ("authorization", "Bearer not-a-jwt".to_string()),
+            ("cookie", format!("{}={}", ACCESS_TOKEN_COOKIE, access_token(cookie_user))),
+        ];
+        assert_eq!(authenticate(&headers).await.0, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_alg_none_token_is_unauthorized() {
+        use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+        let signed = access_token(Uuid::new_v4());
+        let claims = signed.split('.').nth(1).unwrap();
+        let unsigned = format!("{}.{}.", URL_SAFE_NO_PAD.encode(r#"{"alg":"none","typ":"JWT"}"#), claims);
+
+        let (status, body) = authenticate(&[("authorization", format!("Bearer {}", unsigned))]).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body, "Invalid or expired token");
+        let cookie = format!("{}={}", ACCESS_TOKEN_COOKIE, unsigned);
+        assert_eq!(authenticate(&[("cookie", cookie)]).await.0, StatusCode::UNAUTHORIZED);
+    }
+
+    async fn challenge(settings: AppSettings, authorization: Option<String>) -> (StatusCode, Option<String>) {
+        let app = Router::new().route("/whoami", get(whoami)).with_state(lazy_pool()).layer(settings.layer());
+        let mut req = Request::builder().uri("/whoami");
+        if let Some(authorization) = authorization {
+            req = req.header("authorization", authorization);
+        }
+        let res = app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+        (res.status(), res.headers().get(WWW_AUTHENTICATE).map(|v| v.to_str().unwrap().to_string()))
+    }
+
+    #[tokio::test]
+    async fn test_rejections_carry_bearer_challenge() {
+        let expired = sign_claims(&serde_json::json!({ "sub": Uuid::new_v4().to_string(), "exp": Utc::now().timestamp() - 3600 }));
+
+        assert_eq!(challenge(AppSettings::default(), None).await, (StatusCode::UNAUTHORIZED, Some("Bearer realm=\"api\"".to_string())));
+        let invalid = challenge(AppSettings::default(), Some("Bearer not-a-jwt".to_string())).await;
+        assert_eq!(invalid.1.as_deref(), Some("Bearer realm=\"api\", error=\"invalid_token\", error_description=\"The access token is invalid\""));
+        let expired = challenge(AppSettings::default(), Some(format!("Bearer {}", expired))).await;
+        assert_eq!(expired.1.as_deref(), Some("Bearer realm=\"api\", error=\"invalid_token\", error_description=\"The access token expired\""));
+    }
+
+    #[tokio::test]
+    async fn test_challenge_realm_is_configurable() {
+        let settings = AppSettings { auth_realm: "kitchen".to_string(), ..AppSettings::default() };
+        let missing = challenge(settings.clone(), None).await;
+        let invalid = challenge(settings, Some("Bearer not-a-jwt".to_string())).await;
+
+        assert_eq!(missing.1.as_deref(), Some("Bearer realm=\"kitchen\""));
+        assert!(invalid.1.unwrap().starts_with("Bearer realm=\"kitchen\", error=\"invalid_token\""));
+        assert!(is_valid_realm("Kitchen API"));
+        assert!(!is_valid_realm("api\", error=\"x"));
+    }
+
+    #[tokio::test]
+    async fn test_neither_is_rejected() {
+        let (status, body) = authenticate(&[("cookie", "lang=en".to_string())]).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body, "Missing Authorization header or access token cookie");
+    }
+
+    #[tokio::test]
+    async fn test_require_admin() {
+        let pool = test_pool().await;
+        let admin = insert_user_with_role(&pool, "admin").await;
+        let cook = insert_user_with_role(&pool, "line_cook").await;
+
+        assert!(require_admin(&pool, admin).await.is_ok());
+        for user_id in [cook, Uuid::new_v4()] {
+            let response = require_admin(&pool, user_id).await.unwrap_err();
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["details"], "Admin role required");
         }
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,264 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::Value;
+use tracing::{debug, error, Level};
+
+use crate::middleware::validation::is_json_content_type;
+
+/// Logged bodies are cut to this many bytes after redaction
+pub const MAX_LOGGED_BODY_BYTES: usize = 2048;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Field names whose values never reach the logs. Matching is
+/// case-insensitive and by substring, so `refresh_token` and
+/// `current_password` are covered too.
+const SENSITIVE_KEY_FRAGMENTS: [&str; 3] = ["password", "token", "authorization"];
+
+fn is_sensitive_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    SENSITIVE_KEY_FRAGMENTS.iter().any(|fragment| key.contains(fragment))
+}
+
+fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_sensitive_key(key) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(value);
+                }
+            }
+        },
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {},
+    }
+}
+
+fn redact_form(body: &str) -> String {
+    body.split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((key, _)) if is_sensitive_key(key) => format!("{}={}", key, REDACTED),
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn truncate(mut text: String, max_bytes: usize) -> String {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let total = text.len();
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    format!("{}... ({} bytes total)", text, total)
+}
+
+/// Render a body for logging with sensitive fields replaced.
+///
+/// Only JSON and form bodies are shown, since those are the formats we can
+/// redact; anything else, including JSON that fails to parse, is summarized
+/// by size so an unrecognised payload can't leak a secret.
+pub fn redact_body(content_type: &str, bytes: &[u8]) -> String {
+    if bytes.is_empty() {
+        return String::new();
+    }
+
+    let essence = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    let rendered = if is_json_content_type(content_type) {
+        match serde_json::from_slice::<Value>(bytes) {
+            Ok(mut value) => {
+                redact_json(&mut value);
+                value.to_string()
+            },
+            Err(_) => format!("<{} bytes of unparseable JSON>", bytes.len()),
+        }
+    } else if essence == "application/x-www-form-urlencoded" {
+        redact_form(&String::from_utf8_lossy(bytes))
+    } else {
+        format!("<{} bytes of {}>", bytes.len(), if essence.is_empty() { "unknown type" } else { &essence })
+    };
+    truncate(rendered, MAX_LOGGED_BODY_BYTES)
+}
+
+fn content_type(headers: &HeaderMap) -> &str {
+    headers.get(CONTENT_TYPE).and_then(|ct| ct.to_str().ok()).unwrap_or("")
+}
+
+/// Logs redacted request and response bodies at debug level.
+///
+/// Bodies are buffered in full, so this is for debugging client issues only
+/// and stays off unless `DEBUG_LOG_BODIES` is set. Streaming responses
+/// (`text/event-stream`, `application/x-ndjson`) are passed through untouched.
+#[derive(Debug, Clone, Copy)]
+pub struct BodyLog {
+    enabled: bool,
+}
+
+impl BodyLog {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled && tracing::enabled!(Level::DEBUG)
+    }
+
+    pub async fn middleware(&self, request: Request, next: Next) -> Response {
+        if !self.is_enabled() {
+            return next.run(request).await;
+        }
+
+        let (parts, body) = request.into_parts();
+        let bytes = match to_bytes(body, usize::MAX).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                error!(error = %e, "Failed to read request body for logging");
+                return StatusCode::BAD_REQUEST.into_response();
+            },
+        };
+        debug!(
+            method = %parts.method,
+            path = %parts.uri.path(),
+            body = %redact_body(content_type(&parts.headers), &bytes),
+            "Request body"
+        );
+        let method = parts.method.clone();
+        let path = parts.uri.path().to_string();
+
+        let response = next.run(Request::from_parts(parts, Body::from(bytes))).await;
+        let response_type = content_type(response.headers());
+        if response_type.starts_with("text/event-stream") || response_type.starts_with("application/x-ndjson") {
+            return response;
+        }
+
+        let (parts, body) = response.into_parts();
+        let bytes = match to_bytes(body, usize::MAX).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                error!(error = %e, "Failed to read response body for logging");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            },
+        };
+        debug!(
+            method = %method,
+            path = %path,
+            status = parts.status.as_u16(),
+            body = %redact_body(content_type(&parts.headers), &bytes),
+            "Response body"
+        );
+        Response::from_parts(parts, Body::from(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Json, Router};
+    use serde_json::json;
+    use crate::test_support::CapturedLogs;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_redacts_sensitive_json_fields_at_any_depth() {
+        let body = json!({
+            "email": "chef@example.com",
+            "password": "Hunter2Secret!",
+            "profile": { "Refresh_Token": "abc123", "tags": [{ "authorization": "Bearer xyz" }] },
+        });
+        let logged = redact_body("application/json; charset=utf-8", body.to_string().as_bytes());
+
+        assert!(logged.contains("chef@example.com"));
+        for secret in ["Hunter2Secret!", "abc123", "Bearer xyz"] {
+            assert!(!logged.contains(secret), "{} leaked into {}", secret, logged);
+        }
+        assert_eq!(logged.matches(REDACTED).count(), 3);
+    }
+
+    #[test]
+    fn test_redacts_form_fields() {
+        let logged = redact_body(
+            "application/x-www-form-urlencoded",
+            b"grant_type=password&username=chef&password=Hunter2Secret!",
+        );
+        assert_eq!(logged, "grant_type=password&username=chef&password=[REDACTED]");
+    }
+
+    #[test]
+    fn test_unredactable_bodies_are_summarized() {
+        assert_eq!(redact_body("application/json", b"{\"password\": "), "<13 bytes of unparseable JSON>");
+        assert_eq!(redact_body("text/plain", b"password=x"), "<10 bytes of text/plain>");
+        assert_eq!(redact_body("", b""), "");
+    }
+
+    #[test]
+    fn test_truncates_on_char_boundary() {
+        let long = json!({ "note": "é".repeat(MAX_LOGGED_BODY_BYTES) }).to_string();
+        let logged = redact_body("application/json", long.as_bytes());
+        assert!(logged.len() < MAX_LOGGED_BODY_BYTES + 40);
+        assert!(logged.ends_with(&format!("... ({} bytes total)", long.len())));
+    }
+
+    async fn logged_exchange(enabled: bool) -> (String, Value) {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_max_level(Level::DEBUG)
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let body_log = BodyLog::new(enabled);
+        let app = Router::new()
+            .route(
+                "/login",
+                post(|Json(body): Json<Value>| async move {
+                    Json(json!({ "email": body["email"], "token": "eyJhbGciOi.secret.jwt" }))
+                }),
+            )
+            .layer(axum::middleware::from_fn(move |req, next| async move { body_log.middleware(req, next).await }));
+
+        let req = Request::builder()
+            .method("POST")
+            .uri("/login")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({ "email": "chef@example.com", "password": "Hunter2Secret!" }).to_string()))
+            .unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        let bytes = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let contents = logs.contents();
+        (contents, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_middleware_logs_redacted_bodies() {
+        let (logs, response) = logged_exchange(true).await;
+
+        assert!(logs.contains("Request body"), "expected request log, got: {}", logs);
+        assert!(logs.contains("Response body"));
+        assert!(logs.contains("chef@example.com"));
+        assert!(!logs.contains("Hunter2Secret!"), "password leaked: {}", logs);
+        assert!(!logs.contains("eyJhbGciOi.secret.jwt"), "token leaked: {}", logs);
+
+        // The handler and client still see the original bodies
+        assert_eq!(response["token"], "eyJhbGciOi.secret.jwt");
+    }
+
+    #[tokio::test]
+    async fn test_middleware_disabled_by_default_logs_nothing() {
+        let (logs, response) = logged_exchange(false).await;
+        assert!(!logs.contains("Request body"));
+        assert_eq!(response["email"], "chef@example.com");
+    }
+}
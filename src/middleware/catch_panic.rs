@@ -0,0 +1,104 @@
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::any::Any;
+use tracing::{error, Instrument};
+use uuid::Uuid;
+
+use crate::api::auth::{ErrorCode, ErrorResponse};
+use crate::middleware::error_reporting::PanicReported;
+
+/// Header carrying the request id; taken from the client when present
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Run the request inside a span tagged with its request id and echo the id
+/// back, so a client reporting a 500 can be matched to the panic log.
+///
+/// Must sit outside [`CatchPanicLayer`](tower_http::catch_panic::CatchPanicLayer)
+/// so [`panic_response`] logs within the span.
+pub async fn request_id_middleware(mut request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty() && value.len() <= 128)
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let header = HeaderValue::from_str(&request_id).ok();
+    if let Some(value) = &header {
+        request.headers_mut().insert(REQUEST_ID_HEADER.clone(), value.clone());
+    }
+
+    let span = tracing::info_span!("request", request_id = %request_id);
+    let mut response = next.run(request).instrument(span).await;
+    if let Some(value) = header {
+        response.headers_mut().insert(REQUEST_ID_HEADER.clone(), value);
+    }
+    response
+}
+
+/// Turn a handler panic into a 500 `ErrorResponse` instead of a reset
+/// connection. The panic message is logged, never returned to the client.
+pub fn panic_response(panic: Box<dyn Any + Send + 'static>) -> Response {
+    let message = panic
+        .downcast_ref::<String>()
+        .map(String::as_str)
+        .or_else(|| panic.downcast_ref::<&str>().copied())
+        .unwrap_or("non-string panic payload");
+    error!(panic = message, "Handler panicked");
+
+    let mut response = (StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse::coded(ErrorCode::InternalError, "Internal server error", None))).into_response();
+    response.extensions_mut().insert(PanicReported);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware::from_fn, routing::get, Router};
+    use tower::ServiceExt;
+    use tower_http::catch_panic::CatchPanicLayer;
+
+    async fn boom() -> &'static str {
+        panic!("kaboom")
+    }
+
+    fn app() -> Router {
+        Router::new()
+            .route("/boom", get(boom))
+            .route("/ok", get(|| async { "fine" }))
+            .layer(CatchPanicLayer::custom(panic_response))
+            .layer(from_fn(request_id_middleware))
+    }
+
+    #[tokio::test]
+    async fn test_panicking_handler_returns_500() {
+        let req = Request::builder()
+            .uri("/boom")
+            .header("x-request-id", "req-123")
+            .body(Body::empty())
+            .unwrap();
+        let res = app().oneshot(req).await.unwrap();
+
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(res.headers()["x-request-id"], "req-123");
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["error"], "Internal server error");
+        assert!(!body.windows(6).any(|w| w == b"kaboom"), "panic message must not leak");
+    }
+
+    #[tokio::test]
+    async fn test_request_id_generated_when_missing() {
+        let req = Request::builder().uri("/ok").body(Body::empty()).unwrap();
+        let res = app().oneshot(req).await.unwrap();
+
+        assert_eq!(res.status(), StatusCode::OK);
+        let id = res.headers()["x-request-id"].to_str().unwrap();
+        assert!(Uuid::parse_str(id).is_ok());
+    }
+}
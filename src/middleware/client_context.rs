@@ -0,0 +1,176 @@
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header::USER_AGENT, request::Parts, Extensions, HeaderMap},
+};
+use axum::response::Response;
+use std::net::{IpAddr, SocketAddr};
+use tracing::debug;
+
+use crate::config::settings;
+
+/// Resolve the originating client address.
+///
+/// `X-Forwarded-For` is client-controlled: anything a client sends is kept by
+/// proxies, which only *append* the address they received the request from.
+/// So only the rightmost `trusted_hops` entries were written by our own
+/// infrastructure, and the `trusted_hops`-th entry from the right is the
+/// first address no client could have forged. Entries further left are
+/// ignored entirely.
+///
+/// With `trusted_hops == 0` the header is never consulted and the TCP peer is
+/// used. If the chain is shorter than `trusted_hops`, or the selected entry
+/// isn't an IP address, the request didn't come through the expected proxies
+/// and we also fall back to the peer.
+pub fn resolve_client_ip(peer: Option<IpAddr>, headers: &HeaderMap, trusted_hops: usize) -> Option<IpAddr> {
+    if trusted_hops == 0 {
+        return peer;
+    }
+
+    // Multiple X-Forwarded-For headers are one list in order (RFC 7230 3.2.2)
+    let chain: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .collect();
+
+    let Some(index) = chain.len().checked_sub(trusted_hops) else {
+        debug!(chain_len = chain.len(), trusted_hops, "Forwarded chain shorter than trusted hops; using peer address");
+        return peer;
+    };
+
+    match chain[index].parse::<IpAddr>() {
+        Ok(ip) => Some(ip),
+        Err(_) => {
+            debug!(entry = chain[index], "Unparseable forwarded address; using peer address");
+            peer
+        }
+    }
+}
+
+/// Information about the caller derived from the connection and trusted headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientContext {
+    /// Best-effort client address; `None` when neither the peer nor a trusted
+    /// forwarded entry is available
+    pub ip: Option<IpAddr>,
+}
+
+impl ClientContext {
+    /// Build the context from request extensions and headers, trusting the
+    /// rightmost `trusted_hops` forwarded entries
+    pub fn from_request_data(extensions: &Extensions, headers: &HeaderMap, trusted_hops: usize) -> Self {
+        let peer = extensions.get::<ConnectInfo<SocketAddr>>().map(|info| info.ip());
+        Self { ip: resolve_client_ip(peer, headers, trusted_hops) }
+    }
+}
+
+#[axum::async_trait]
+impl<S> FromRequestParts<S> for ClientContext
+where
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let settings = settings::from_extensions(&parts.extensions)?;
+        Ok(Self::from_request_data(&parts.extensions, &parts.headers, settings.trusted_proxy_hops))
+    }
+}
+
+/// Longest `User-Agent` kept with a session; longer values are truncated
+pub const MAX_SESSION_USER_AGENT_LEN: usize = 512;
+
+/// The client a session is issued to, stored with its refresh token
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionClient {
+    pub ip: Option<IpAddr>,
+    pub user_agent: Option<String>,
+}
+
+impl SessionClient {
+    pub fn from_request_data(extensions: &Extensions, headers: &HeaderMap, trusted_hops: usize) -> Self {
+        let user_agent = headers
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.trim().chars().take(MAX_SESSION_USER_AGENT_LEN).collect::<String>())
+            .filter(|value| !value.is_empty());
+        Self { ip: ClientContext::from_request_data(extensions, headers, trusted_hops).ip, user_agent }
+    }
+}
+
+#[axum::async_trait]
+impl<S> FromRequestParts<S> for SessionClient
+where
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let settings = settings::from_extensions(&parts.extensions)?;
+        Ok(Self::from_request_data(&parts.extensions, &parts.headers, settings.trusted_proxy_hops))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    const PEER: &str = "10.0.0.2";
+
+    fn peer() -> Option<IpAddr> {
+        Some(PEER.parse().unwrap())
+    }
+
+    fn forwarded(values: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append("x-forwarded-for", HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    fn ip(s: &str) -> Option<IpAddr> {
+        Some(s.parse().unwrap())
+    }
+
+    #[test]
+    fn test_zero_hops_ignores_header() {
+        let headers = forwarded(&["203.0.113.7"]);
+        assert_eq!(resolve_client_ip(peer(), &headers, 0), peer());
+        assert_eq!(resolve_client_ip(None, &headers, 0), None);
+    }
+
+    #[test]
+    fn test_hop_counts_select_from_the_right() {
+        let headers = forwarded(&["198.51.100.1, 203.0.113.7, 10.0.0.1"]);
+        assert_eq!(resolve_client_ip(peer(), &headers, 1), ip("10.0.0.1"));
+        assert_eq!(resolve_client_ip(peer(), &headers, 2), ip("203.0.113.7"));
+        assert_eq!(resolve_client_ip(peer(), &headers, 3), ip("198.51.100.1"));
+    }
+
+    #[test]
+    fn test_spoofed_entries_are_ignored() {
+        // Client sent a forged value; our single load balancer appended the real one
+        let headers = forwarded(&["1.2.3.4, 203.0.113.7"]);
+        assert_eq!(resolve_client_ip(peer(), &headers, 1), ip("203.0.113.7"));
+
+        // Forged entries split across repeated headers are still one list
+        let headers = forwarded(&["1.2.3.4", "5.6.7.8, 203.0.113.7"]);
+        assert_eq!(resolve_client_ip(peer(), &headers, 1), ip("203.0.113.7"));
+    }
+
+    #[test]
+    fn test_short_or_invalid_chain_falls_back_to_peer() {
+        let headers = forwarded(&["203.0.113.7"]);
+        assert_eq!(resolve_client_ip(peer(), &headers, 2), peer());
+
+        assert_eq!(resolve_client_ip(peer(), &HeaderMap::new(), 1), peer());
+
+        let headers = forwarded(&["not-an-ip"]);
+        assert_eq!(resolve_client_ip(peer(), &headers, 1), peer());
+    }
+}
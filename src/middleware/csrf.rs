@@ -0,0 +1,174 @@
+use axum::{
+    extract::Request,
+    http::{header::{HOST, ORIGIN}, HeaderMap, HeaderName, Method, Uri},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::api::auth::{ErrorCode, ErrorResponse};
+use crate::middleware::auth::{access_token_with_source, TokenSource};
+
+/// Fetch metadata header browsers send describing where a request came from
+pub static SEC_FETCH_SITE: HeaderName = HeaderName::from_static("sec-fetch-site");
+
+/// Rejects cross-site state-changing requests that authenticate with the
+/// access token cookie.
+///
+/// Browsers attach cookies to cross-site form posts and `fetch` calls, so a
+/// cookie alone doesn't prove the caller is our front end. On POST, PUT,
+/// PATCH and DELETE with cookie auth, `Sec-Fetch-Site` (or, from browsers
+/// that don't send it, `Origin`) must show the request came from this origin
+/// or one in `CORS_ALLOWED_ORIGINS`. Requests whose token comes from an
+/// `Authorization: Bearer` header carry their credential explicitly and are
+/// let through, as are requests without browser provenance headers. Off unless `CSRF_PROTECTION` is set.
+#[derive(Debug, Clone)]
+pub struct CsrfProtection {
+    enabled: bool,
+    trusted_origins: Arc<[String]>,
+}
+
+impl CsrfProtection {
+    pub fn new(enabled: bool, trusted_origins: &[String]) -> Self {
+        let trusted_origins = trusted_origins.iter().map(|origin| origin.trim_end_matches('/').to_ascii_lowercase()).collect();
+        Self { enabled, trusted_origins }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn is_trusted(&self, origin: &str, headers: &HeaderMap) -> bool {
+        let origin = origin.trim_end_matches('/').to_ascii_lowercase();
+        if self.trusted_origins.contains(&origin) {
+            return true;
+        }
+        // Same origin as the host the request was sent to
+        let host = headers.get(HOST).and_then(|h| h.to_str().ok());
+        let authority = origin.parse::<Uri>().ok().and_then(|uri| uri.authority().map(|a| a.as_str().to_string()));
+        matches!((authority, host), (Some(authority), Some(host)) if authority.eq_ignore_ascii_case(host))
+    }
+
+    /// Why `request` must be rejected, or `None` when it may proceed
+    pub fn violation(&self, method: &Method, headers: &HeaderMap) -> Option<&'static str> {
+        let state_changing = matches!(*method, Method::POST | Method::PUT | Method::PATCH | Method::DELETE);
+        let cookie_auth = matches!(access_token_with_source(headers), Some((_, TokenSource::Cookie)));
+        if !state_changing || !cookie_auth {
+            return None;
+        }
+
+        let origin = headers.get(ORIGIN).map(|o| o.to_str().unwrap_or(""));
+        match headers.get(&SEC_FETCH_SITE).and_then(|v| v.to_str().ok()) {
+            // Typed into the address bar, a bookmark, or our own pages
+            Some("same-origin") | Some("none") => None,
+            Some(_) => match origin {
+                Some(origin) if self.is_trusted(origin, headers) => None,
+                _ => Some("cross-site request from an untrusted origin"),
+            },
+            // Older browsers send Origin on cross-origin writes; non-browser clients send neither
+            None => match origin {
+                Some(origin) if !self.is_trusted(origin, headers) => Some("request from an untrusted origin"),
+                _ => None,
+            },
+        }
+    }
+
+    pub async fn middleware(&self, request: Request, next: Next) -> Response {
+        if !self.enabled {
+            return next.run(request).await;
+        }
+        if let Some(reason) = self.violation(request.method(), request.headers()) {
+            warn!(
+                method = %request.method(),
+                path = %request.uri().path(),
+                origin = ?request.headers().get(ORIGIN),
+                sec_fetch_site = ?request.headers().get(&SEC_FETCH_SITE),
+                reason,
+                "Rejected cross-site request using cookie authentication"
+            );
+            return ErrorResponse::coded(
+                ErrorCode::Forbidden,
+                "Cross-site request rejected",
+                Some("Send the access token in the Authorization header or call from a trusted origin".to_string()),
+            )
+            .into_response();
+        }
+        next.run(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::auth::ACCESS_TOKEN_COOKIE;
+    use axum::{body::Body, http::StatusCode, middleware::from_fn, routing::post, Router};
+    use tower::ServiceExt;
+
+    fn app(csrf: CsrfProtection) -> Router {
+        Router::new()
+            .route("/write", post(|| async { "written" }).get(|| async { "read" }))
+            .layer(from_fn(move |req, next| {
+                let csrf = csrf.clone();
+                async move { csrf.middleware(req, next).await }
+            }))
+    }
+
+    async fn send(app: Router, method: &str, headers: &[(&str, &str)]) -> StatusCode {
+        let mut request = Request::builder().method(method).uri("/write").header(HOST, "api.kitchen.test");
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap().status()
+    }
+
+    fn cookie() -> String {
+        format!("{}=token", ACCESS_TOKEN_COOKIE)
+    }
+
+    #[tokio::test]
+    async fn test_same_site_requests_are_accepted() {
+        let app = app(CsrfProtection::new(true, &["https://app.kitchen.test".to_string()]));
+        let cookie = cookie();
+
+        assert_eq!(send(app.clone(), "POST", &[("cookie", &cookie), ("sec-fetch-site", "same-origin")]).await, StatusCode::OK);
+        assert_eq!(send(app.clone(), "POST", &[("cookie", &cookie), ("sec-fetch-site", "none")]).await, StatusCode::OK);
+        // A separate front-end origin listed in CORS_ALLOWED_ORIGINS
+        let trusted = [("cookie", cookie.as_str()), ("sec-fetch-site", "same-site"), ("origin", "https://app.kitchen.test")];
+        assert_eq!(send(app.clone(), "POST", &trusted).await, StatusCode::OK);
+        // No fetch metadata: Origin matching the Host is same-origin
+        assert_eq!(send(app.clone(), "POST", &[("cookie", &cookie), ("origin", "https://api.kitchen.test")]).await, StatusCode::OK);
+        // Non-browser client without provenance headers
+        assert_eq!(send(app, "POST", &[("cookie", &cookie)]).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_cross_site_requests_are_rejected() {
+        let app = app(CsrfProtection::new(true, &["https://app.kitchen.test".to_string()]));
+        let cookie = cookie();
+
+        let cross_site = [("cookie", cookie.as_str()), ("sec-fetch-site", "cross-site"), ("origin", "https://evil.test")];
+        assert_eq!(send(app.clone(), "POST", &cross_site).await, StatusCode::FORBIDDEN);
+        assert_eq!(send(app.clone(), "POST", &[("cookie", &cookie), ("sec-fetch-site", "cross-site")]).await, StatusCode::FORBIDDEN);
+        // A sibling subdomain isn't trusted just for being same-site
+        let sibling = [("cookie", cookie.as_str()), ("sec-fetch-site", "same-site"), ("origin", "https://blog.kitchen.test")];
+        assert_eq!(send(app.clone(), "POST", &sibling).await, StatusCode::FORBIDDEN);
+        // Older browsers: untrusted Origin without fetch metadata
+        assert_eq!(send(app.clone(), "POST", &[("cookie", &cookie), ("origin", "null")]).await, StatusCode::FORBIDDEN);
+
+        // Reads, and writes carrying an explicit bearer token, are unaffected
+        assert_eq!(send(app.clone(), "GET", &cross_site).await, StatusCode::OK);
+        let bearer = [("authorization", "Bearer token"), ("sec-fetch-site", "cross-site"), ("origin", "https://evil.test")];
+        assert_eq!(send(app.clone(), "POST", &bearer).await, StatusCode::OK);
+        // Any other Authorization scheme leaves the cookie as the credential
+        let basic = [("authorization", "Basic dXNlcjpwYXNz"), ("cookie", cookie.as_str()), ("sec-fetch-site", "cross-site"), ("origin", "https://evil.test")];
+        assert_eq!(send(app, "POST", &basic).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_by_default() {
+        let app = app(CsrfProtection::new(false, &[]));
+        let cross_site = [("cookie", "access_token=token"), ("sec-fetch-site", "cross-site"), ("origin", "https://evil.test")];
+        assert_eq!(send(app, "POST", &cross_site).await, StatusCode::OK);
+    }
+}
@@ -0,0 +1,196 @@
+//! Optional error tracking.
+//!
+//! With `SENTRY_DSN` set, panics and 5xx responses are sent to Sentry tagged
+//! with the request id, route and user. Without it nothing is initialised and
+//! every hook here is a no-op.
+
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use sentry::{protocol::User, types::Dsn, Hub, Level, SentryFutureExt};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::config::settings;
+use crate::core::auth::verify_jwt_with_claims;
+use crate::middleware::auth::access_token;
+use crate::middleware::catch_panic::REQUEST_ID_HEADER;
+
+/// Marks a 500 built by [`panic_response`](crate::middleware::catch_panic::panic_response);
+/// the panic integration has already reported it, so the middleware skips it
+#[derive(Debug, Clone, Copy)]
+pub struct PanicReported;
+
+/// Start the Sentry client when `dsn` is set.
+///
+/// Keep the returned guard alive for the life of the process; dropping it
+/// flushes queued events. Release and environment come from `SENTRY_RELEASE`
+/// and `SENTRY_ENVIRONMENT` when set.
+pub fn init(dsn: Option<&str>) -> Option<sentry::ClientInitGuard> {
+    let dsn = dsn.filter(|dsn| !dsn.is_empty())?;
+    let dsn: Dsn = match dsn.parse() {
+        Ok(dsn) => dsn,
+        Err(e) => {
+            warn!(error = %e, "SENTRY_DSN is invalid; error reporting disabled");
+            return None;
+        }
+    };
+    let guard = sentry::init(sentry::ClientOptions {
+        dsn: Some(dsn),
+        release: release(),
+        ..Default::default()
+    });
+    info!("Sentry error reporting enabled");
+    Some(guard)
+}
+
+/// `SENTRY_RELEASE`, falling back to the crate name and version. Sentry only
+/// reads the variable itself when no release is given.
+fn release() -> Option<std::borrow::Cow<'static, str>> {
+    std::env::var("SENTRY_RELEASE")
+        .ok()
+        .filter(|release| !release.is_empty())
+        .map(Into::into)
+        .or_else(|| sentry::release_name!())
+}
+
+/// Report 5xx responses with request context.
+///
+/// Each request runs on its own hub so the tags set here also reach panics
+/// captured by the panic integration. Must sit inside `request_id_middleware`
+/// and outside `CatchPanicLayer`.
+pub async fn error_reporting_middleware(request: Request, next: Next) -> Response {
+    let parent = Hub::current();
+    if parent.client().is_none() {
+        return next.run(request).await;
+    }
+    let settings = match settings::from_extensions(request.extensions()) {
+        Ok(settings) => settings,
+        Err(response) => return response,
+    };
+
+    let method = request.method().clone();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let token = access_token(request.headers()).map(str::to_string);
+
+    let hub = Arc::new(Hub::new_from_top(parent));
+    hub.configure_scope(|scope| {
+        scope.set_transaction(Some(&format!("{} {}", method, route)));
+        scope.set_tag("route", &route);
+        if let Some(request_id) = &request_id {
+            scope.set_tag("request_id", request_id);
+        }
+    });
+
+    let response = next.run(request).bind_hub(hub.clone()).await;
+    let status = response.status();
+    if status.is_server_error() && response.extensions().get::<PanicReported>().is_none() {
+        // Only decoded on the error path; the handler already did the real check
+        let user_id = token.and_then(|token| verify_jwt_with_claims(&token, &settings.jwt_keys, &settings.jwt_claims).ok()).map(|verified| verified.user_id);
+        hub.with_scope(
+            |scope| {
+                scope.set_tag("status", status.as_u16());
+                scope.set_user(user_id.map(|id| User { id: Some(id.to_string()), ..Default::default() }));
+            },
+            || hub.capture_message(&format!("{} {} returned {}", method, route, status), Level::Error),
+        );
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::AppSettings;
+    use crate::test_support::access_token;
+    use axum::{body::Body, http::StatusCode, middleware::from_fn, routing::get, Router};
+    use sentry::test::TestTransport;
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/fail/:id", get(|| async { StatusCode::INTERNAL_SERVER_ERROR }))
+            .route("/missing", get(|| async { StatusCode::NOT_FOUND }))
+            .layer(from_fn(error_reporting_middleware))
+            .layer(AppSettings::default().layer())
+    }
+
+    /// Hub whose client sends events to `transport` instead of the network
+    fn test_hub(transport: &Arc<TestTransport>) -> Arc<Hub> {
+        let options = sentry::ClientOptions {
+            dsn: Some("https://public@sentry.invalid/1".parse().unwrap()),
+            transport: Some(Arc::new(transport.clone())),
+            default_integrations: false,
+            ..Default::default()
+        };
+        Arc::new(Hub::new(Some(Arc::new(options.into())), Arc::new(Default::default())))
+    }
+
+    async fn call(app: Router, uri: &str, headers: &[(&str, String)]) -> StatusCode {
+        let mut request = Request::builder().uri(uri);
+        for (name, value) in headers {
+            request = request.header(*name, value);
+        }
+        app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap().status()
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_release_prefers_env() {
+        std::env::set_var("SENTRY_RELEASE", "kitchen-api@2026.10.1");
+        assert_eq!(release().as_deref(), Some("kitchen-api@2026.10.1"));
+        std::env::remove_var("SENTRY_RELEASE");
+        assert_eq!(release(), sentry::release_name!());
+    }
+
+    #[tokio::test]
+    async fn test_server_error_is_captured_with_request_context() {
+        let user_id = uuid::Uuid::new_v4();
+        let transport = TestTransport::new();
+        let headers = [
+            ("x-request-id", "req-500".to_string()),
+            ("authorization", format!("Bearer {}", access_token(user_id))),
+        ];
+
+        let status = call(app(), "/fail/7", &headers).bind_hub(test_hub(&transport)).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+
+        let events = transport.fetch_and_clear_events();
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.level, Level::Error);
+        assert_eq!(event.message.as_deref(), Some("GET /fail/:id returned 500 Internal Server Error"));
+        assert_eq!(event.transaction.as_deref(), Some("GET /fail/:id"));
+        assert_eq!(event.tags["request_id"], "req-500");
+        assert_eq!(event.tags["route"], "/fail/:id");
+        assert_eq!(event.tags["status"], "500");
+        assert_eq!(event.user.as_ref().and_then(|user| user.id.clone()), Some(user_id.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_are_not_captured() {
+        let transport = TestTransport::new();
+        let status = call(app(), "/missing", &[]).bind_hub(test_hub(&transport)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(transport.fetch_and_clear_events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_noop_without_client() {
+        assert_eq!(call(app(), "/fail/1", &[]).await, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(init(None).is_none());
+        assert!(init(Some("")).is_none());
+        assert!(init(Some("not a dsn")).is_none());
+    }
+}
@@ -0,0 +1,109 @@
+use axum::{
+    extract::Request,
+    http::HeaderMap,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::warn;
+use crate::api::auth::{ErrorCode, ErrorResponse};
+use crate::config::settings;
+
+/// Default limit on the combined size of a request's headers
+pub const DEFAULT_MAX_HEADER_BYTES: usize = 16 * 1024;
+
+/// Smallest read buffer hyper accepts for HTTP/1
+const MIN_TRANSPORT_BUF_SIZE: usize = 8 * 1024;
+
+/// Hard cap for the server's connection-level header buffer, given the
+/// middleware limit `max_header_bytes`.
+///
+/// Twice that limit, so requests between the two reach
+/// [`header_limit_middleware`] and get a JSON 431; anything beyond is cut off
+/// by hyper with a bare 431 before it is buffered. `None` when the limit is
+/// disabled (0), leaving hyper's defaults.
+pub fn transport_header_cap(max_header_bytes: usize) -> Option<usize> {
+    match max_header_bytes {
+        0 => None,
+        max => Some((max * 2).max(MIN_TRANSPORT_BUF_SIZE)),
+    }
+}
+
+/// Size of the headers as sent: name, `": "`, value and CRLF for each
+fn header_bytes(headers: &HeaderMap) -> usize {
+    headers.iter().map(|(name, value)| name.as_str().len() + value.len() + 4).sum()
+}
+
+/// Answer requests whose headers exceed `MAX_HEADER_BYTES` with
+/// `431 Request Header Fields Too Large` and a structured body naming the
+/// largest header, usually an oversized cookie or `Authorization` token.
+pub async fn header_limit_middleware(request: Request, next: Next) -> Response {
+    let max = match settings::from_extensions(request.extensions()) {
+        Ok(settings) => settings.max_header_bytes,
+        Err(response) => return response,
+    };
+    let size = header_bytes(request.headers());
+    if max == 0 || size <= max {
+        return next.run(request).await;
+    }
+
+    let largest = request
+        .headers()
+        .iter()
+        .max_by_key(|(_, value)| value.len())
+        .map(|(name, value)| (name.as_str().to_string(), value.len()));
+    warn!(
+        method = %request.method(),
+        path = %request.uri().path(),
+        header_bytes = size,
+        max_header_bytes = max,
+        largest_header = ?largest,
+        "Request headers too large"
+    );
+    let details = match largest {
+        Some((name, len)) => format!("Request headers total {} bytes; the limit is {}. Largest header: {} ({} bytes)", size, max, name, len),
+        None => format!("Request headers total {} bytes; the limit is {}", size, max),
+    };
+    ErrorResponse::coded(ErrorCode::HeaderFieldsTooLarge, "Request headers too large", Some(details)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::AppSettings;
+    use axum::{body::Body, http::StatusCode, middleware::from_fn, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn app(max_header_bytes: usize) -> Router {
+        Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(from_fn(header_limit_middleware))
+            .layer(AppSettings { max_header_bytes, ..AppSettings::default() }.layer())
+    }
+
+    async fn call(max_header_bytes: usize, cookie: &str) -> (StatusCode, serde_json::Value) {
+        let req = axum::http::Request::builder().uri("/").header("cookie", cookie).body(Body::empty()).unwrap();
+        let res = app(max_header_bytes).oneshot(req).await.unwrap();
+        let status = res.status();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null))
+    }
+
+    #[tokio::test]
+    async fn test_oversized_headers_get_structured_431() {
+        let (ok, _) = call(1024, &"a".repeat(512)).await;
+        let (status, body) = call(1024, &"a".repeat(2048)).await;
+
+        assert_eq!(ok, StatusCode::OK);
+        assert_eq!(status, StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE);
+        assert_eq!(body["code"], "HEADER_FIELDS_TOO_LARGE");
+        assert_eq!(body["error"], "Request headers too large");
+        assert!(body["details"].as_str().unwrap().contains("Largest header: cookie (2048 bytes)"));
+    }
+
+    #[test]
+    fn test_transport_cap_leaves_room_for_the_middleware() {
+        assert_eq!(transport_header_cap(16 * 1024), Some(32 * 1024));
+        assert_eq!(transport_header_cap(1024), Some(MIN_TRANSPORT_BUF_SIZE));
+        assert_eq!(transport_header_cap(0), None);
+    }
+}
@@ -0,0 +1,126 @@
+use axum::{
+    extract::Request,
+    http::{
+        header::{CONNECTION, FORWARDED, PROXY_AUTHORIZATION, TE, TRANSFER_ENCODING, UPGRADE},
+        HeaderMap, HeaderName,
+    },
+    middleware::Next,
+    response::Response,
+};
+use tracing::debug;
+
+use crate::config::settings;
+
+/// Hop-by-hop headers (RFC 9110 7.6.1) that describe one connection and mean
+/// nothing once the request has been read
+const HOP_BY_HOP: [&str; 4] = ["keep-alive", "proxy-connection", "proxy-authenticate", "trailer"];
+
+/// Headers a proxy writes to describe the original request. Without a trusted
+/// proxy in front (`TRUSTED_PROXY_HOPS=0`) only the client could have sent them.
+const FORWARDING: [&str; 5] = ["x-forwarded-for", "x-forwarded-host", "x-forwarded-proto", "x-forwarded-port", "x-real-ip"];
+
+/// Remove the headers a client could use to confuse handlers, returning the
+/// names removed.
+///
+/// Hop-by-hop headers, including any the `Connection` header nominates, are
+/// always dropped; `TE: trailers` survives because gRPC-Web clients need it.
+/// Forwarding headers are dropped unless `trust_forwarding` says a proxy in
+/// front of us writes them.
+pub fn strip_untrusted_headers(headers: &mut HeaderMap, trust_forwarding: bool) -> Vec<HeaderName> {
+    let mut names: Vec<HeaderName> = headers
+        .get_all(CONNECTION)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|name| HeaderName::from_bytes(name.trim().as_bytes()).ok())
+        .collect();
+    names.extend([CONNECTION, TRANSFER_ENCODING, UPGRADE, PROXY_AUTHORIZATION]);
+    names.extend(HOP_BY_HOP.map(HeaderName::from_static));
+    if headers.get(TE).is_some_and(|value| !value.as_bytes().eq_ignore_ascii_case(b"trailers")) {
+        names.push(TE);
+    }
+    if !trust_forwarding {
+        names.push(FORWARDED);
+        names.extend(FORWARDING.map(HeaderName::from_static));
+    }
+
+    names.retain(|name| headers.remove(name).is_some());
+    names
+}
+
+/// Strip hop-by-hop and, without a trusted proxy, forwarding headers before
+/// extractors and handlers read them
+pub async fn hop_by_hop_middleware(mut request: Request, next: Next) -> Response {
+    let trust_forwarding = match settings::from_extensions(request.extensions()) {
+        Ok(settings) => settings.trusted_proxy_hops > 0,
+        Err(response) => return response,
+    };
+    let removed = strip_untrusted_headers(request.headers_mut(), trust_forwarding);
+    if !removed.is_empty() {
+        debug!(path = %request.uri().path(), headers = ?removed, "Stripped untrusted request headers");
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::AppSettings;
+    use axum::{body::Body, middleware::from_fn, routing::get, Router};
+    use tower::ServiceExt;
+
+    /// Echoes the names of the headers the handler received
+    async fn received(trusted_proxy_hops: usize, headers: &[(&str, &str)]) -> Vec<String> {
+        let app = Router::new()
+            .route("/", get(|headers: HeaderMap| async move { headers.keys().map(|name| name.as_str()).collect::<Vec<_>>().join(",") }))
+            .layer(from_fn(hop_by_hop_middleware))
+            .layer(AppSettings { trusted_proxy_hops, ..AppSettings::default() }.layer());
+        let mut request = Request::builder().uri("/");
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let res = app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap().split(',').filter(|name| !name.is_empty()).map(str::to_string).collect()
+    }
+
+    #[tokio::test]
+    async fn test_spoofed_forwarding_headers_removed_without_trusted_proxy() {
+        let names = received(0, &[
+            ("x-forwarded-for", "1.2.3.4"),
+            ("x-forwarded-host", "evil.test"),
+            ("x-forwarded-proto", "https"),
+            ("x-real-ip", "1.2.3.4"),
+            ("forwarded", "for=1.2.3.4"),
+            ("authorization", "Bearer token"),
+        ])
+        .await;
+        assert_eq!(names, ["authorization"]);
+    }
+
+    #[tokio::test]
+    async fn test_forwarding_headers_kept_behind_trusted_proxy() {
+        let names = received(1, &[("x-forwarded-for", "1.2.3.4"), ("x-forwarded-proto", "https"), ("forwarded", "for=1.2.3.4")]).await;
+        assert_eq!(names, ["x-forwarded-for", "x-forwarded-proto", "forwarded"]);
+    }
+
+    #[tokio::test]
+    async fn test_hop_by_hop_headers_always_removed() {
+        let names = received(1, &[
+            ("connection", "keep-alive, x-internal-flag"),
+            ("keep-alive", "timeout=5"),
+            ("x-internal-flag", "1"),
+            ("upgrade", "websocket"),
+            ("proxy-authorization", "Basic Zm9vOmJhcg=="),
+            ("te", "gzip"),
+            ("accept", "application/json"),
+        ])
+        .await;
+        assert_eq!(names, ["accept"]);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(TE, "trailers".parse().unwrap());
+        assert!(strip_untrusted_headers(&mut headers, false).is_empty());
+        assert!(headers.contains_key(TE));
+    }
+}
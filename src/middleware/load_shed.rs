@@ -0,0 +1,215 @@
+use axum::{
+    extract::Request,
+    http::{header::RETRY_AFTER, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::convert::Infallible;
+use std::future::{ready, Ready};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio_util::sync::PollSemaphore;
+use tower::{buffer::Buffer, Service, ServiceExt};
+use tracing::{debug, warn};
+use crate::api::auth::{ErrorCode, ErrorResponse};
+
+/// Seconds clients are asked to wait before retrying a shed request
+pub const LOAD_SHED_RETRY_AFTER_SECS: u64 = 1;
+
+/// Hands out in-flight permits; ready only while one is free
+struct Permits {
+    semaphore: PollSemaphore,
+    acquired: Option<OwnedSemaphorePermit>,
+}
+
+impl Service<()> for Permits {
+    type Response = OwnedSemaphorePermit;
+    type Error = Infallible;
+    type Future = Ready<Result<OwnedSemaphorePermit, Infallible>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        if self.acquired.is_none() {
+            // The semaphore is never closed, so this always yields a permit
+            self.acquired = std::task::ready!(self.semaphore.poll_acquire(cx));
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _: ()) -> Self::Future {
+        ready(Ok(self.acquired.take().expect("poll_ready is called before call")))
+    }
+}
+
+/// Caps how many requests run at once, answering `503 Service Unavailable`
+/// with `Retry-After` once the cap is reached.
+///
+/// With a buffer, requests over the cap first wait in a bounded
+/// [`tower::buffer::Buffer`] for a permit, so a brief burst is smoothed out
+/// instead of shed; only when the buffer is full too is the request answered
+/// 503. Without one they are shed straight away.
+///
+/// Clones share the same permits and buffer, so one instance can guard several
+/// routers. Only data routes should be wrapped: health probes are mounted
+/// outside this layer so an overloaded instance still reports itself alive
+/// and isn't restarted on top of the load. A permit is held until the response
+/// headers are ready; body streaming is not counted. A zero limit disables
+/// shedding.
+#[derive(Clone)]
+pub struct LoadShed {
+    permits: Option<Arc<Semaphore>>,
+    buffer: Option<tower::load_shed::LoadShed<Buffer<Permits, ()>>>,
+}
+
+impl std::fmt::Debug for LoadShed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoadShed")
+            .field("permits", &self.permits)
+            .field("buffered", &self.buffer.is_some())
+            .finish()
+    }
+}
+
+impl LoadShed {
+    /// `buffer_capacity` requests may wait for a permit; 0 sheds as soon as
+    /// the cap is reached. A buffer spawns its worker, so it must be created
+    /// inside the Tokio runtime.
+    pub fn new(max_in_flight: usize, buffer_capacity: usize) -> Self {
+        let permits = (max_in_flight > 0).then(|| Arc::new(Semaphore::new(max_in_flight)));
+        let buffer = permits.as_ref().filter(|_| buffer_capacity > 0).map(|semaphore| {
+            let permits = Permits { semaphore: PollSemaphore::new(semaphore.clone()), acquired: None };
+            tower::load_shed::LoadShed::new(Buffer::new(permits, buffer_capacity))
+        });
+        Self { permits, buffer }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.permits.is_some()
+    }
+
+    /// A permit, waiting in the buffer if there is one and it has room
+    async fn acquire(&self, permits: &Arc<Semaphore>) -> Option<OwnedSemaphorePermit> {
+        if let Ok(permit) = permits.clone().try_acquire_owned() {
+            return Some(permit);
+        }
+        let mut buffer = self.buffer.clone()?;
+        let buffer = buffer.ready().await.ok()?;
+        // Fails with `Overloaded` when the buffer is full
+        let permit = buffer.call(()).await.ok()?;
+        debug!("Buffered request admitted");
+        Some(permit)
+    }
+
+    pub async fn middleware(&self, request: Request, next: Next) -> Response {
+        let Some(permits) = &self.permits else {
+            return next.run(request).await;
+        };
+        let Some(_permit) = self.acquire(permits).await else {
+            warn!(method = %request.method(), path = %request.uri().path(), "Request shed: too many requests in flight");
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                [(RETRY_AFTER, LOAD_SHED_RETRY_AFTER_SECS.to_string())],
+                Json(ErrorResponse::coded(ErrorCode::ServiceUnavailable, "Service unavailable", Some("The service is overloaded; retry shortly".to_string()))),
+            )
+                .into_response();
+        };
+        next.run(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware::from_fn, routing::get, Router};
+    use std::time::Duration;
+    use tokio::sync::Notify;
+    use tower::ServiceExt;
+
+    /// Health mounted outside the shed layer, the same way `app_with_config` does it
+    fn app(load_shed: LoadShed, release: Arc<Notify>) -> Router {
+        let data = Router::new()
+            .route(
+                "/api/v1/slow",
+                get(move || {
+                    let release = release.clone();
+                    async move {
+                        release.notified().await;
+                        "done"
+                    }
+                }),
+            )
+            .route("/api/v1/fast", get(|| async { "fast" }))
+            .layer(from_fn(move |req, next| {
+                let load_shed = load_shed.clone();
+                async move { load_shed.middleware(req, next).await }
+            }));
+        Router::new()
+            .route("/health/live", get(|| async { "live" }))
+            .merge(data)
+    }
+
+    async fn get_status(app: &Router, uri: &str) -> (StatusCode, Option<String>) {
+        let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let res = app.clone().oneshot(req).await.unwrap();
+        let retry_after = res.headers().get(RETRY_AFTER).map(|v| v.to_str().unwrap().to_string());
+        (res.status(), retry_after)
+    }
+
+    async fn get_status_owned(app: Router, uri: &'static str) -> (StatusCode, Option<String>) {
+        get_status(&app, uri).await
+    }
+
+    #[tokio::test]
+    async fn test_health_survives_saturated_data_routes() {
+        let release = Arc::new(Notify::new());
+        let app = app(LoadShed::new(2, 0), release.clone());
+
+        let in_flight: Vec<_> = (0..2)
+            .map(|_| tokio::spawn(get_status_owned(app.clone(), "/api/v1/slow")))
+            .collect();
+        // Let both slow requests take their permits
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(get_status(&app, "/api/v1/fast").await, (StatusCode::SERVICE_UNAVAILABLE, Some("1".to_string())));
+        assert_eq!(get_status(&app, "/health/live").await, (StatusCode::OK, None));
+
+        release.notify_waiters();
+        for request in in_flight {
+            assert_eq!(request.await.unwrap().0, StatusCode::OK);
+        }
+        assert_eq!(get_status(&app, "/api/v1/fast").await, (StatusCode::OK, None));
+    }
+
+    #[tokio::test]
+    async fn test_bursts_within_buffer_wait_and_overflow_is_shed() {
+        let release = Arc::new(Notify::new());
+        let app = app(LoadShed::new(2, 2), release.clone());
+
+        // Two run, two wait in the buffer
+        let burst: Vec<_> = (0..4)
+            .map(|_| tokio::spawn(get_status_owned(app.clone(), "/api/v1/slow")))
+            .collect();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(get_status(&app, "/api/v1/fast").await, (StatusCode::SERVICE_UNAVAILABLE, Some("1".to_string())));
+        assert_eq!(get_status(&app, "/health/live").await, (StatusCode::OK, None));
+
+        // Releasing the running pair admits the buffered pair, then release those too
+        release.notify_waiters();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        release.notify_waiters();
+        for request in burst {
+            assert_eq!(request.await.unwrap(), (StatusCode::OK, None));
+        }
+        assert_eq!(get_status(&app, "/api/v1/fast").await, (StatusCode::OK, None));
+    }
+
+    #[tokio::test]
+    async fn test_zero_limit_disables_shedding() {
+        let load_shed = LoadShed::new(0, 16);
+        assert!(!load_shed.is_enabled());
+        let app = app(load_shed, Arc::new(Notify::new()));
+        assert_eq!(get_status(&app, "/api/v1/fast").await, (StatusCode::OK, None));
+    }
+}
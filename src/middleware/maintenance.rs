@@ -0,0 +1,106 @@
+use axum::{
+    extract::Request,
+    http::{header::RETRY_AFTER, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::LazyLock;
+use tracing::{info, warn};
+use crate::api::auth::{ErrorCode, ErrorResponse};
+
+/// Seconds clients are asked to wait before retrying a rejected write
+pub const MAINTENANCE_RETRY_AFTER_SECS: u64 = 120;
+
+/// Path of the admin toggle, which must stay writable so maintenance can be turned off
+pub const MAINTENANCE_TOGGLE_PATH: &str = "/api/v1/admin/maintenance";
+
+/// Endpoints that issue tokens. They stay open during maintenance so admins
+/// can still sign in to turn it off, and sessions can be renewed meanwhile.
+const AUTH_PATHS: [&str; 3] = ["/api/v1/auth/login", "/api/v1/auth/refresh", "/api/v1/auth/token"];
+
+/// Process-wide maintenance flag, seeded from `MAINTENANCE_MODE` at first use
+static MAINTENANCE_MODE: LazyLock<AtomicBool> = LazyLock::new(|| {
+    let enabled = std::env::var("MAINTENANCE_MODE")
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "on"))
+        .unwrap_or(false);
+    AtomicBool::new(enabled)
+});
+
+/// Whether write requests are currently being rejected
+pub fn is_enabled() -> bool {
+    MAINTENANCE_MODE.load(Ordering::Relaxed)
+}
+
+/// Turn maintenance mode on or off for this instance
+pub fn set_enabled(enabled: bool) {
+    let previous = MAINTENANCE_MODE.swap(enabled, Ordering::Relaxed);
+    if previous != enabled {
+        info!(enabled, "Maintenance mode changed");
+    }
+}
+
+fn is_write(method: &Method) -> bool {
+    matches!(*method, Method::POST | Method::PUT | Method::PATCH | Method::DELETE)
+}
+
+/// Reject writes with `503 Service Unavailable` while maintenance mode is on.
+///
+/// Reads, health checks, sign-in and the maintenance toggle itself always
+/// pass through.
+pub async fn maintenance_middleware(request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    if is_enabled()
+        && is_write(request.method())
+        && !path.starts_with("/health")
+        && path != MAINTENANCE_TOGGLE_PATH
+        && !AUTH_PATHS.contains(&path)
+    {
+        warn!(method = %request.method(), path = %path, "Write rejected: maintenance mode enabled");
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(RETRY_AFTER, MAINTENANCE_RETRY_AFTER_SECS.to_string())],
+            Json(ErrorResponse::coded(ErrorCode::ServiceUnavailable, "Service unavailable", Some("The service is in maintenance mode; writes are temporarily disabled".to_string()))),
+        )
+            .into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware::from_fn, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/api/v1/items", get(|| async { "read" }).post(|| async { "written" }))
+            .route("/health/live", get(|| async { "live" }))
+            .route(MAINTENANCE_TOGGLE_PATH, axum::routing::put(|| async { "toggled" }))
+            .route("/api/v1/auth/login", axum::routing::post(|| async { "logged in" }))
+            .layer(from_fn(maintenance_middleware))
+    }
+
+    async fn status(method: &str, uri: &str) -> (StatusCode, Option<String>) {
+        let req = Request::builder().method(method).uri(uri).body(Body::empty()).unwrap();
+        let res = app().oneshot(req).await.unwrap();
+        let retry_after = res.headers().get(RETRY_AFTER).map(|v| v.to_str().unwrap().to_string());
+        (res.status(), retry_after)
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_maintenance_blocks_writes_but_not_reads() {
+        set_enabled(true);
+        assert_eq!(status("POST", "/api/v1/items").await, (StatusCode::SERVICE_UNAVAILABLE, Some(MAINTENANCE_RETRY_AFTER_SECS.to_string())));
+        assert_eq!(status("GET", "/api/v1/items").await.0, StatusCode::OK);
+        assert_eq!(status("GET", "/health/live").await.0, StatusCode::OK);
+        assert_eq!(status("PUT", MAINTENANCE_TOGGLE_PATH).await.0, StatusCode::OK);
+        assert_eq!(status("POST", "/api/v1/auth/login").await.0, StatusCode::OK);
+
+        set_enabled(false);
+        assert_eq!(status("POST", "/api/v1/items").await, (StatusCode::OK, None));
+    }
+}
@@ -1,6 +1,19 @@
 pub mod auth;
+pub mod body_log;
+pub mod catch_panic;
+pub mod client_context;
+pub mod csrf;
+pub mod error_reporting;
+pub mod header_limit;
+pub mod hop_by_hop;
+pub mod load_shed;
+pub mod maintenance;
 pub mod rate_limit;
 pub mod rate_limit_configs;
+pub mod repr_digest;
+pub mod server_timing;
+pub mod slow_request;
+pub mod startup;
 pub mod validation;
 
 #[cfg(test)]
@@ -1,21 +1,81 @@
 use axum::{
-    extract::{ConnectInfo, Request},
-    http::{HeaderMap, StatusCode},
+    extract::Request,
+    http::{header::RETRY_AFTER, HeaderMap, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
 };
 use dashmap::DashMap;
 use std::{
-    net::SocketAddr,
+    net::IpAddr,
+    str::FromStr,
     sync::Arc,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use tracing::{debug, info, warn};
 use serde::Serialize;
+use crate::config::settings::{self, AppSettings};
+use crate::middleware::client_context::ClientContext;
+
+/// An address or CIDR block, e.g. `10.0.0.7` or `35.191.0.0/16`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpRange {
+    network: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpRange {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        // An IPv4 client on a dual-stack socket shows up as ::ffff:a.b.c.d
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+            v4 => v4,
+        };
+        match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - u32::from(self.prefix_len)).unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - u32::from(self.prefix_len)).unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, prefix) = match s.split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix)),
+            None => (s, None),
+        };
+        let network: IpAddr = address.trim().parse().map_err(|_| format!("invalid IP address '{}'", address))?;
+        let max_prefix = if network.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix {
+            Some(prefix) => prefix
+                .trim()
+                .parse::<u8>()
+                .ok()
+                .filter(|&len| len <= max_prefix)
+                .ok_or_else(|| format!("invalid prefix length '{}'", prefix))?,
+            None => max_prefix,
+        };
+        Ok(Self { network, prefix_len })
+    }
+}
+
+/// Whether `ip` falls in any of the `allowlist` ranges, e.g. load balancer
+/// health checkers that are never rate limited
+pub fn is_allowlisted(allowlist: &[IpRange], ip: IpAddr) -> bool {
+    allowlist.iter().any(|range| range.contains(ip))
+}
 
 /// Rate limiting configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RateLimitConfig {
     /// Maximum number of requests per window
     pub max_requests: u32,
@@ -98,6 +158,16 @@ impl RateLimitBucket {
         self.requests = 0;
     }
 
+    /// Whole seconds until the current window ends, rounded up and at least 1
+    fn secs_until_reset(&self, window_duration: Duration) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let window_end = self.window_start + window_duration.as_millis() as u64;
+        window_end.saturating_sub(now).div_ceil(1000).max(1)
+    }
+
     fn add_request(&mut self) {
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -175,6 +245,7 @@ impl InMemoryRateLimiter {
                 0
             },
             reset_time: bucket.window_start + self.config.window_duration.as_secs(),
+            retry_after_secs: bucket.secs_until_reset(self.config.window_duration),
             total_requests: bucket.total_requests,
         }
     }
@@ -196,6 +267,8 @@ pub struct RateLimitResult {
     pub allowed: bool,
     pub requests_remaining: u32,
     pub reset_time: u64,
+    /// Seconds until the window resets and requests are allowed again
+    pub retry_after_secs: u64,
     pub total_requests: u64,
 }
 
@@ -239,22 +312,21 @@ impl RateLimitMiddleware {
         Self { limiter, strategy }
     }
 
+    /// A middleware keyed the same way as this one but with its own limiter
+    /// and counters, for routes that need a different limit than their group
+    pub fn with_config(&self, config: RateLimitConfig) -> Self {
+        Self::new(RateLimiter::new_in_memory(config), self.strategy.clone())
+    }
+
     /// Extract the rate limiting key based on the strategy
-    fn extract_key(&self, request: &Request, headers: &HeaderMap) -> Option<String> {
+    fn extract_key(&self, request: &Request, headers: &HeaderMap, settings: &AppSettings) -> Option<String> {
         match &self.strategy {
             RateLimitStrategy::ByIp => {
-                // Try to get IP from ConnectInfo or X-Forwarded-For header
-                if let Some(connect_info) = request.extensions().get::<ConnectInfo<SocketAddr>>() {
-                    Some(format!("ip:{}", connect_info.ip()))
-                } else if let Some(forwarded_for) = headers.get("x-forwarded-for") {
-                    forwarded_for.to_str().ok()
-                        .and_then(|s| s.split(',').next())
-                        .map(|ip| format!("ip:{}", ip.trim()))
-                } else if let Some(real_ip) = headers.get("x-real-ip") {
-                    real_ip.to_str().ok()
-                        .map(|ip| format!("ip:{}", ip.trim()))
-                } else {
-                    Some("ip:unknown".to_string())
+                // Peer address, or a trusted X-Forwarded-For entry behind proxies
+                let client = ClientContext::from_request_data(request.extensions(), headers, settings.trusted_proxy_hops);
+                match client.ip {
+                    Some(ip) => Some(format!("ip:{}", ip)),
+                    None => Some("ip:unknown".to_string()),
                 }
             }
             RateLimitStrategy::ByUser => {
@@ -263,9 +335,9 @@ impl RateLimitMiddleware {
                     .and_then(|h| h.to_str().ok())
                     .and_then(|h| h.strip_prefix("Bearer "))
                     .and_then(|token| {
-                        crate::core::auth::verify_jwt(token).ok()
+                        crate::core::auth::verify_jwt_with_claims(token, &settings.jwt_keys, &settings.jwt_claims).ok()
                     })
-                    .map(|user_id| format!("user:{}", user_id))
+                    .map(|verified| format!("user:{}", verified.user_id))
             }
             RateLimitStrategy::ByApiKey => {
                 headers.get("x-api-key")
@@ -285,21 +357,38 @@ impl RateLimitMiddleware {
         next: Next,
     ) -> Result<Response, StatusCode> {
         let headers = request.headers().clone();
+        let settings = match settings::from_extensions(request.extensions()) {
+            Ok(settings) => settings,
+            Err(response) => return Ok(response),
+        };
         
-        if let Some(key) = self.extract_key(&request, &headers) {
+        // Allowlisted sources skip limiting whatever the strategy, and aren't counted
+        let client = ClientContext::from_request_data(request.extensions(), &headers, settings.trusted_proxy_hops);
+        if client.ip.is_some_and(|ip| is_allowlisted(&settings.rate_limit_allowlist, ip)) {
+            debug!(ip = ?client.ip, "Client is allowlisted; skipping rate limit");
+            return Ok(next.run(request).await);
+        }
+        
+        if let Some(key) = self.extract_key(&request, &headers, &settings) {
             let result = self.limiter.check_rate_limit(&key).await;
             
             if !result.allowed {
                 warn!(key = %key, "Request blocked by rate limiter");
                 
+                // The body repeats Retry-After for clients that don't read headers
                 let response = axum::Json(serde_json::json!({
-                    "error": "Rate limit exceeded",
+                    "error": "rate_limited",
                     "details": "Too many requests. Please try again later.",
+                    "retry_after_secs": result.retry_after_secs,
                     "requests_remaining": result.requests_remaining,
                     "reset_time": result.reset_time
                 }));
                 
-                return Ok((StatusCode::TOO_MANY_REQUESTS, response).into_response());
+                return Ok((
+                    StatusCode::TOO_MANY_REQUESTS,
+                    [(RETRY_AFTER, result.retry_after_secs.to_string())],
+                    response,
+                ).into_response());
             }
             
             info!(
@@ -375,6 +464,98 @@ mod tests {
         assert!(!result.allowed, "Request should be blocked");
     }
 
+    #[tokio::test]
+    async fn test_rate_limited_body_matches_retry_after_header() {
+        use axum::{body::Body, middleware::from_fn, routing::get, Router};
+        use tower::ServiceExt;
+
+        let limiter = create_global_rate_limiter(RateLimitConfig {
+            max_requests: 1,
+            window_duration: Duration::from_secs(30),
+            burst_allowance: 0,
+            use_redis: false,
+        });
+        let app = Router::new()
+            .route("/ping", get(|| async { "pong" }))
+            .layer(from_fn(move |req, next| {
+                let limiter = limiter.clone();
+                async move { limiter.middleware(req, next).await }
+            }))
+            .layer(AppSettings::default().layer());
+        let send = || app.clone().oneshot(Request::builder().uri("/ping").body(Body::empty()).unwrap());
+
+        let allowed = send().await.unwrap();
+        assert_eq!(allowed.status(), StatusCode::OK);
+        assert!(allowed.headers().get(RETRY_AFTER).is_none());
+
+        let limited = send().await.unwrap();
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        let header: u64 = limited.headers()[RETRY_AFTER].to_str().unwrap().parse().unwrap();
+        assert!((1..=30).contains(&header), "Retry-After {} outside the window", header);
+        let body = axum::body::to_bytes(limited.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"], "rate_limited");
+        assert_eq!(body["retry_after_secs"], header);
+    }
+
+    #[test]
+    fn test_ip_range_parsing_and_matching() {
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+        let single: IpRange = "10.0.0.7".parse().unwrap();
+        assert!(single.contains(ip("10.0.0.7")));
+        assert!(!single.contains(ip("10.0.0.8")));
+
+        let block: IpRange = "35.191.0.0/16".parse().unwrap();
+        assert!(block.contains(ip("35.191.4.2")));
+        assert!(block.contains(ip("::ffff:35.191.4.2")));
+        assert!(!block.contains(ip("35.192.0.1")));
+
+        let v6: IpRange = "2001:db8::/32".parse().unwrap();
+        assert!(v6.contains(ip("2001:db8:1::1")));
+        assert!(!v6.contains(ip("2001:db9::1")));
+        assert!(!v6.contains(ip("10.0.0.7")));
+
+        let everything: IpRange = "0.0.0.0/0".parse().unwrap();
+        assert!(everything.contains(ip("192.0.2.1")));
+
+        assert!("10.0.0.0/33".parse::<IpRange>().is_err());
+        assert!("not-an-ip".parse::<IpRange>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_allowlisted_ip_is_never_limited() {
+        use axum::{body::Body, extract::ConnectInfo, middleware::from_fn, routing::get, Router};
+        use std::net::SocketAddr;
+        use tower::ServiceExt;
+
+        let limiter = create_ip_rate_limiter(RateLimitConfig {
+            max_requests: 1,
+            window_duration: Duration::from_secs(60),
+            burst_allowance: 0,
+            use_redis: false,
+        });
+        let app = Router::new()
+            .route("/ping", get(|| async { "pong" }))
+            .layer(from_fn(move |req, next| {
+                let limiter = limiter.clone();
+                async move { limiter.middleware(req, next).await }
+            }))
+            .layer(AppSettings { rate_limit_allowlist: vec!["198.51.100.0/24".parse().unwrap()], ..AppSettings::default() }.layer());
+        let send = |peer: &str| {
+            let mut request = Request::builder().uri("/ping").body(Body::empty()).unwrap();
+            request.extensions_mut().insert(ConnectInfo(peer.parse::<SocketAddr>().unwrap()));
+            app.clone().oneshot(request)
+        };
+
+        for _ in 0..5 {
+            let res = send("198.51.100.20:40000").await.unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+            assert!(res.headers().get("X-RateLimit-Remaining").is_none());
+        }
+        assert_eq!(send("203.0.113.9:40000").await.unwrap().status(), StatusCode::OK);
+        assert_eq!(send("203.0.113.9:40000").await.unwrap().status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
     #[tokio::test]
     async fn test_rate_limit_window_reset() {
         let config = RateLimitConfig {
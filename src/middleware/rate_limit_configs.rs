@@ -1,5 +1,14 @@
-use crate::middleware::rate_limit::{RateLimitConfig, RateLimitMiddleware, create_ip_rate_limiter, create_user_rate_limiter, create_global_rate_limiter};
+use crate::middleware::rate_limit::{IpRange, RateLimitConfig, RateLimitMiddleware, create_ip_rate_limiter, create_user_rate_limiter, create_global_rate_limiter};
+use axum::{
+    extract::{MatchedPath, Request},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
+use tracing::warn;
 
 /// Pre-configured rate limiters for different use cases
 pub struct RateLimitConfigs;
@@ -110,6 +119,102 @@ impl RateLimitConfigs {
     }
 }
 
+/// Parse per-route limits from `ROUTE_RATE_LIMITS`.
+///
+/// Entries are comma-separated `<route>=<max_requests>/<window_secs>[/<burst>]`,
+/// where `<route>` is the route template as registered, e.g.
+/// `/api/v1/users/me=20/60,/api/v1/users/:id=50/60/5`. Malformed entries are
+/// logged and skipped.
+pub fn parse_route_rate_limits(value: &str) -> HashMap<String, RateLimitConfig> {
+    let mut limits = HashMap::new();
+    for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        match parse_route_rate_limit(entry) {
+            Some((route, config)) => {
+                limits.insert(route, config);
+            }
+            None => warn!(entry, "Ignoring malformed ROUTE_RATE_LIMITS entry"),
+        }
+    }
+    limits
+}
+
+fn parse_route_rate_limit(entry: &str) -> Option<(String, RateLimitConfig)> {
+    let (route, limit) = entry.rsplit_once('=')?;
+    let route = route.trim();
+    if !route.starts_with('/') {
+        return None;
+    }
+    let mut parts = limit.trim().split('/');
+    let max_requests = parts.next()?.trim().parse().ok()?;
+    let window_secs: u64 = parts.next()?.trim().parse().ok().filter(|secs| *secs > 0)?;
+    let burst_allowance = match parts.next() {
+        Some(burst) => burst.trim().parse().ok()?,
+        None => 0,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((
+        route.to_string(),
+        RateLimitConfig {
+            max_requests,
+            window_duration: Duration::from_secs(window_secs),
+            burst_allowance,
+            use_redis: false,
+        },
+    ))
+}
+
+/// Parse `RATE_LIMIT_ALLOWLIST`: comma-separated addresses or CIDR blocks,
+/// e.g. `10.0.0.7,35.191.0.0/16`. Malformed entries are logged and skipped.
+pub fn parse_rate_limit_allowlist(value: &str) -> Vec<IpRange> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .filter_map(|entry| match entry.parse() {
+            Ok(range) => Some(range),
+            Err(e) => {
+                warn!(entry, error = %e, "Ignoring malformed RATE_LIMIT_ALLOWLIST entry");
+                None
+            }
+        })
+        .collect()
+}
+
+/// A router group's rate limiter with per-route overrides.
+///
+/// Requests whose matched route template has an override are counted against
+/// that route's own limiter; everything else shares the group default. Overrides
+/// key clients the same way as the group (by IP, user, ...).
+#[derive(Clone)]
+pub struct RouteRateLimits {
+    default: RateLimitMiddleware,
+    routes: Arc<HashMap<String, RateLimitMiddleware>>,
+}
+
+impl RouteRateLimits {
+    pub fn new(default: RateLimitMiddleware, overrides: &HashMap<String, RateLimitConfig>) -> Self {
+        let routes = overrides
+            .iter()
+            .map(|(route, config)| (route.clone(), default.with_config(config.clone())))
+            .collect();
+        Self { default, routes: Arc::new(routes) }
+    }
+
+    /// Limiter for a route template, falling back to the group default
+    fn limiter_for(&self, route: Option<&str>) -> &RateLimitMiddleware {
+        route
+            .and_then(|route| self.routes.get(route))
+            .unwrap_or(&self.default)
+    }
+
+    pub async fn middleware(&self, request: Request, next: Next) -> Result<Response, StatusCode> {
+        let route = request.extensions().get::<MatchedPath>().map(|path| path.as_str().to_string());
+        self.limiter_for(route.as_deref()).middleware(request, next).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -160,4 +265,62 @@ mod tests {
         let _limiter = RateLimitConfigs::custom(50, 60, 5, true);
         let _limiter2 = RateLimitConfigs::custom(100, 120, 10, false);
     }
+
+    #[test]
+    fn test_parse_route_rate_limits() {
+        let limits = parse_route_rate_limits(" /api/v1/users/me=20/60 , /api/v1/users/:id=50/30/5,bogus=1/1,/x=1,/y=1/0");
+        assert_eq!(limits.len(), 2);
+        assert_eq!(
+            limits["/api/v1/users/me"],
+            RateLimitConfig { max_requests: 20, window_duration: Duration::from_secs(60), burst_allowance: 0, use_redis: false }
+        );
+        assert_eq!(
+            limits["/api/v1/users/:id"],
+            RateLimitConfig { max_requests: 50, window_duration: Duration::from_secs(30), burst_allowance: 5, use_redis: false }
+        );
+    }
+
+    #[test]
+    fn test_parse_rate_limit_allowlist() {
+        let allowlist = parse_rate_limit_allowlist(" 10.0.0.7 , 35.191.0.0/16,bogus,10.0.0.0/40,,2001:db8::/32");
+        assert_eq!(allowlist.len(), 3);
+        assert!(allowlist[0].contains("10.0.0.7".parse().unwrap()));
+        assert!(allowlist[1].contains("35.191.200.1".parse().unwrap()));
+        assert!(allowlist[2].contains("2001:db8::1".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_route_limit_enforced_independently_of_group() {
+        use axum::{body::Body, middleware::from_fn, routing::get, Router};
+        use tower::ServiceExt;
+        use crate::config::settings::AppSettings;
+
+        let overrides = parse_route_rate_limits("/hot/:id=1/60");
+        let limits = RouteRateLimits::new(RateLimitConfigs::custom(3, 60, 0, false), &overrides);
+        let app = Router::new()
+            .route("/hot/:id", get(|| async { "hot" }))
+            .route("/cold", get(|| async { "cold" }))
+            .layer(from_fn(move |req, next| {
+                let limits = limits.clone();
+                async move { limits.middleware(req, next).await }
+            }))
+            .layer(AppSettings::default().layer());
+        let status = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+                app.oneshot(req).await.unwrap().status()
+            }
+        };
+
+        // The override is keyed by template, so both ids share one budget
+        assert_eq!(status("/hot/1").await, StatusCode::OK);
+        assert_eq!(status("/hot/2").await, StatusCode::TOO_MANY_REQUESTS);
+
+        // The rest of the group keeps its own, larger budget
+        for _ in 0..3 {
+            assert_eq!(status("/cold").await, StatusCode::OK);
+        }
+        assert_eq!(status("/cold").await, StatusCode::TOO_MANY_REQUESTS);
+    }
 }
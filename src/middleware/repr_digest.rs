@@ -0,0 +1,114 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::{header::CONTENT_TYPE, HeaderName, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sha2::{Digest, Sha256};
+use tracing::error;
+
+/// `Repr-Digest` response header (RFC 9530)
+pub static REPR_DIGEST: HeaderName = HeaderName::from_static("repr-digest");
+
+/// `Repr-Digest` value for `body`: `sha-256=:<base64 of the SHA-256 hash>:`
+pub fn repr_digest_value(body: &[u8]) -> HeaderValue {
+    let hash = Sha256::digest(body);
+    HeaderValue::from_str(&format!("sha-256=:{}:", STANDARD.encode(hash))).expect("base64 is a valid header value")
+}
+
+/// Adds a `Repr-Digest` header so clients and caches can detect bodies
+/// altered in transit, e.g. by a misbehaving proxy.
+///
+/// The response body is buffered to hash it, so this stays off unless
+/// `REPR_DIGEST_ENABLED` is set. HEAD requests, bodyless statuses and
+/// streaming responses (`text/event-stream`, `application/x-ndjson`) are
+/// passed through untouched.
+#[derive(Debug, Clone, Copy)]
+pub struct ReprDigest {
+    enabled: bool,
+}
+
+impl ReprDigest {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub async fn middleware(&self, request: Request, next: Next) -> Response {
+        if !self.enabled || request.method() == Method::HEAD {
+            return next.run(request).await;
+        }
+
+        let response = next.run(request).await;
+        let streaming = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|ct| ct.to_str().ok())
+            .is_some_and(|ct| ct.starts_with("text/event-stream") || ct.starts_with("application/x-ndjson"));
+        let bodyless = matches!(response.status(), StatusCode::NO_CONTENT | StatusCode::NOT_MODIFIED);
+        if streaming || bodyless || response.headers().contains_key(&REPR_DIGEST) {
+            return response;
+        }
+
+        let (mut parts, body) = response.into_parts();
+        let bytes = match to_bytes(body, usize::MAX).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                error!(error = %e, "Failed to read response body for digest");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            },
+        };
+        parts.headers.insert(REPR_DIGEST.clone(), repr_digest_value(&bytes));
+        Response::from_parts(parts, Body::from(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware::from_fn, routing::get, Json, Router};
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    fn app(enabled: bool) -> Router {
+        let repr_digest = ReprDigest::new(enabled);
+        Router::new()
+            .route("/data", get(|| async { Json(json!({ "dish": "ratatouille", "portions": 4 })) }))
+            .route("/empty", get(|| async { StatusCode::NO_CONTENT }))
+            .layer(from_fn(move |req, next| async move { repr_digest.middleware(req, next).await }))
+    }
+
+    async fn fetch(app: Router, uri: &str) -> (Option<String>, Vec<u8>) {
+        let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        let digest = res.headers().get(&REPR_DIGEST).map(|v| v.to_str().unwrap().to_string());
+        let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        (digest, body.to_vec())
+    }
+
+    #[test]
+    fn test_repr_digest_value_format() {
+        // SHA-256 of the empty string
+        assert_eq!(repr_digest_value(b""), "sha-256=:47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=:");
+    }
+
+    #[tokio::test]
+    async fn test_digest_matches_body_when_enabled() {
+        let (digest, body) = fetch(app(true), "/data").await;
+        let digest = digest.expect("Repr-Digest header");
+
+        let encoded = digest.strip_prefix("sha-256=:").and_then(|d| d.strip_suffix(':')).unwrap();
+        assert_eq!(STANDARD.decode(encoded).unwrap(), Sha256::digest(&body).to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_no_digest_when_disabled_or_bodyless() {
+        assert_eq!(fetch(app(false), "/data").await.0, None);
+        assert_eq!(fetch(app(true), "/empty").await.0, None);
+    }
+}
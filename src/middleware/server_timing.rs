@@ -0,0 +1,71 @@
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use std::time::{Duration, Instant};
+
+/// `Server-Timing` response header (W3C Server Timing)
+pub static SERVER_TIMING: HeaderName = HeaderName::from_static("server-timing");
+
+/// `Server-Timing` value for time spent producing the response, in
+/// milliseconds with microsecond precision
+pub fn server_timing_value(elapsed: Duration) -> HeaderValue {
+    let ms = elapsed.as_secs_f64() * 1000.0;
+    HeaderValue::from_str(&format!("app;dur={:.3}", ms)).expect("formatted duration is a valid header value")
+}
+
+/// Report how long the server spent on a request via `Server-Timing: app;dur=<ms>`
+/// so browser dev tools can split latency into network and server time.
+///
+/// The duration covers everything inside this layer, i.e. routing, inner
+/// middleware and the handler, up to when the response headers are ready.
+/// Time spent streaming the body is not included.
+pub async fn server_timing_middleware(request: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let mut response = next.run(request).await;
+    response.headers_mut().append(SERVER_TIMING.clone(), server_timing_value(started.elapsed()));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware::from_fn, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn parse_app_duration(value: &str) -> Option<f64> {
+        value
+            .split(',')
+            .map(str::trim)
+            .find_map(|metric| metric.strip_prefix("app;dur="))
+            .and_then(|dur| dur.parse().ok())
+    }
+
+    #[test]
+    fn test_server_timing_value_format() {
+        assert_eq!(server_timing_value(Duration::from_micros(12_345)), "app;dur=12.345");
+        assert_eq!(server_timing_value(Duration::ZERO), "app;dur=0.000");
+    }
+
+    #[tokio::test]
+    async fn test_server_timing_header_reports_handler_duration() {
+        let app = Router::new()
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    "done"
+                }),
+            )
+            .layer(from_fn(server_timing_middleware));
+
+        let req = Request::builder().uri("/slow").body(Body::empty()).unwrap();
+        let res = app.oneshot(req).await.unwrap();
+
+        let value = res.headers()["server-timing"].to_str().unwrap();
+        let dur = parse_app_duration(value).expect("app;dur metric");
+        assert!(dur >= 20.0, "duration should cover the handler, got {}", dur);
+    }
+}
@@ -0,0 +1,111 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Logs a warning for any request that takes longer than `threshold`.
+///
+/// The route template (e.g. `/api/v1/users/:id`) is logged instead of the raw
+/// path so slow endpoints aggregate cleanly. A zero threshold disables it.
+#[derive(Debug, Clone, Copy)]
+pub struct SlowRequestLog {
+    threshold: Duration,
+}
+
+impl SlowRequestLog {
+    pub fn new(threshold: Duration) -> Self {
+        Self { threshold }
+    }
+
+    pub fn from_millis(threshold_ms: u64) -> Self {
+        Self::new(Duration::from_millis(threshold_ms))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.threshold.is_zero()
+    }
+
+    pub async fn middleware(&self, request: Request, next: Next) -> Response {
+        if !self.is_enabled() {
+            return next.run(request).await;
+        }
+
+        let method = request.method().clone();
+        let route = request
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str().to_string())
+            .unwrap_or_else(|| request.uri().path().to_string());
+
+        let started = Instant::now();
+        let response = next.run(request).await;
+        let elapsed = started.elapsed();
+
+        if elapsed > self.threshold {
+            warn!(
+                method = %method,
+                route = %route,
+                status = response.status().as_u16(),
+                elapsed_ms = elapsed.as_millis() as u64,
+                threshold_ms = self.threshold.as_millis() as u64,
+                "Slow request"
+            );
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request, middleware::from_fn, routing::get, Router};
+    use crate::test_support::CapturedLogs;
+    use tower::ServiceExt;
+
+    async fn request_with_threshold(threshold_ms: u64) -> String {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let slow_log = SlowRequestLog::from_millis(threshold_ms);
+        let app = Router::new()
+            .route(
+                "/items/:id",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    "done"
+                }),
+            )
+            .layer(from_fn(move |req, next| async move { slow_log.middleware(req, next).await }));
+
+        let req = Request::builder().uri("/items/42").body(Body::empty()).unwrap();
+        app.oneshot(req).await.unwrap();
+        logs.contents()
+    }
+
+    #[tokio::test]
+    async fn test_warns_past_threshold() {
+        let logs = request_with_threshold(10).await;
+        assert!(logs.contains("Slow request"), "expected warning, got: {}", logs);
+        assert!(logs.contains("route=/items/:id"));
+    }
+
+    #[tokio::test]
+    async fn test_silent_below_threshold() {
+        let logs = request_with_threshold(5_000).await;
+        assert!(!logs.contains("Slow request"), "unexpected warning: {}", logs);
+    }
+
+    #[tokio::test]
+    async fn test_zero_threshold_disables() {
+        let logs = request_with_threshold(0).await;
+        assert!(!logs.contains("Slow request"));
+    }
+}
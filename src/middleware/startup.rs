@@ -0,0 +1,102 @@
+use axum::{
+    extract::Request,
+    http::{header::RETRY_AFTER, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sqlx::PgPool;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tracing::{info, warn};
+use crate::api::auth::{ErrorCode, ErrorResponse};
+
+/// Seconds clients are asked to wait before retrying during startup
+pub const STARTUP_RETRY_AFTER_SECS: u64 = 2;
+
+/// Whether startup has finished. Instances that never call [`mark_starting`]
+/// (tests, embedders) count as started.
+static STARTUP_COMPLETE: AtomicBool = AtomicBool::new(true);
+
+/// Whether data endpoints are being served
+pub fn is_started() -> bool {
+    STARTUP_COMPLETE.load(Ordering::Relaxed)
+}
+
+/// Hold data endpoints back until [`mark_started`] is called
+pub fn mark_starting() {
+    if STARTUP_COMPLETE.swap(false, Ordering::Relaxed) {
+        info!("Startup in progress; data endpoints return 503 until ready");
+    }
+}
+
+/// Start serving data endpoints
+pub fn mark_started() {
+    if !STARTUP_COMPLETE.swap(true, Ordering::Relaxed) {
+        info!("Startup complete");
+    }
+}
+
+/// Mark startup complete once the database answers, retrying every
+/// `STARTUP_RETRY_AFTER_SECS` until it does
+pub async fn complete_when_ready(pool: PgPool) {
+    loop {
+        match sqlx::query_scalar::<_, i32>("SELECT 1").fetch_one(&pool).await {
+            Ok(_) => break,
+            Err(e) => {
+                warn!(error = %e, "Database not ready during startup; retrying");
+                tokio::time::sleep(Duration::from_secs(STARTUP_RETRY_AFTER_SECS)).await;
+            }
+        }
+    }
+    mark_started();
+}
+
+/// Answer `503 Service Unavailable` with `Retry-After` until startup completes,
+/// so orchestrators and clients back off instead of seeing 500s during boot.
+///
+/// Health routes always pass through so probes can report progress.
+pub async fn startup_middleware(request: Request, next: Next) -> Response {
+    if !is_started() && !request.uri().path().starts_with("/health") {
+        warn!(method = %request.method(), path = %request.uri().path(), "Request rejected: startup in progress");
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(RETRY_AFTER, STARTUP_RETRY_AFTER_SECS.to_string())],
+            Json(ErrorResponse::coded(ErrorCode::ServiceUnavailable, "Service unavailable", Some("The service is starting up; retry shortly".to_string()))),
+        )
+            .into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware::from_fn, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/api/v1/users", get(|| async { "users" }))
+            .route("/health/live", get(|| async { "live" }))
+            .layer(from_fn(startup_middleware))
+    }
+
+    async fn status(uri: &str) -> (StatusCode, Option<String>) {
+        let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let res = app().oneshot(req).await.unwrap();
+        let retry_after = res.headers().get(RETRY_AFTER).map(|v| v.to_str().unwrap().to_string());
+        (res.status(), retry_after)
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_data_routes_wait_for_startup() {
+        mark_starting();
+        assert_eq!(status("/api/v1/users").await, (StatusCode::SERVICE_UNAVAILABLE, Some("2".to_string())));
+        assert_eq!(status("/health/live").await, (StatusCode::OK, None));
+
+        mark_started();
+        assert_eq!(status("/api/v1/users").await, (StatusCode::OK, None));
+    }
+}
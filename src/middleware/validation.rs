@@ -1,7 +1,7 @@
 use axum::{
     body::{to_bytes, Body},
     extract::Request,
-    http::StatusCode,
+    http::{header::CONTENT_TYPE, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
     Json,
@@ -10,38 +10,72 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use tracing::{error, warn, debug};
+use unicode_normalization::UnicodeNormalization;
 use utoipa::ToSchema;
-use validator::{Validate, ValidationErrors};
+use validator::{Validate, ValidationErrors, ValidationErrorsKind};
+
+use crate::config::settings::{self, AppSettings};
+use crate::core::auth::validate_password_size;
+
+/// Whether a request was unreadable or only its values were wrong
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationFailure {
+    /// The body couldn't be read as a request at all: malformed or oversized
+    /// JSON, or the wrong content type
+    Malformed,
+    /// Well-formed, but the values break the endpoint's rules
+    Semantic,
+}
 
 /// Standard validation error response
 #[derive(Debug, Serialize, ToSchema)]
 pub struct ValidationErrorResponse {
     pub error: String,
     pub message: String,
+    /// Messages keyed by field path. Nested fields are dotted and list items
+    /// indexed, e.g. `preferences.theme` or `users[2].email`.
     pub validation_errors: HashMap<String, Vec<String>>,
+    #[serde(skip)]
+    kind: ValidationFailure,
+}
+
+/// Flatten `errors` into `out`, prefixing each field with `path`
+fn collect_field_errors(path: &str, errors: &ValidationErrors, out: &mut HashMap<String, Vec<String>>) {
+    for (field, kind) in errors.errors() {
+        let field_path = if path.is_empty() { field.to_string() } else { format!("{}.{}", path, field) };
+        match kind {
+            ValidationErrorsKind::Field(field_errors) => {
+                let error_messages: Vec<String> = field_errors
+                    .iter()
+                    .map(|error| {
+                        error.message
+                            .as_ref()
+                            .map(|msg| msg.to_string())
+                            .unwrap_or_else(|| format!("Invalid value for field '{}'", field_path))
+                    })
+                    .collect();
+                out.entry(field_path).or_default().extend(error_messages);
+            }
+            ValidationErrorsKind::Struct(nested) => collect_field_errors(&field_path, nested, out),
+            ValidationErrorsKind::List(items) => {
+                for (index, nested) in items {
+                    collect_field_errors(&format!("{}[{}]", field_path, index), nested, out);
+                }
+            }
+        }
+    }
 }
 
 impl ValidationErrorResponse {
     pub fn new(errors: ValidationErrors) -> Self {
         let mut validation_errors = HashMap::new();
-        
-        for (field, field_errors) in errors.field_errors() {
-            let error_messages: Vec<String> = field_errors
-                .iter()
-                .map(|error| {
-                    error.message
-                        .as_ref()
-                        .map(|msg| msg.to_string())
-                        .unwrap_or_else(|| format!("Invalid value for field '{}'", field))
-                })
-                .collect();
-            validation_errors.insert(field.to_string(), error_messages);
-        }
+        collect_field_errors("", &errors, &mut validation_errors);
         
         Self {
             error: "VALIDATION_ERROR".to_string(),
             message: "Request validation failed".to_string(),
             validation_errors,
+            kind: ValidationFailure::Semantic,
         }
     }
     
@@ -53,6 +87,20 @@ impl ValidationErrorResponse {
             error: "JSON_PARSE_ERROR".to_string(),
             message: "Invalid JSON format".to_string(),
             validation_errors,
+            kind: ValidationFailure::Malformed,
+        }
+    }
+    
+    /// Well-formed JSON nested deeper or with longer arrays than allowed
+    pub fn from_json_limit(error: &str) -> Self {
+        let mut validation_errors = HashMap::new();
+        validation_errors.insert("json".to_string(), vec![error.to_string()]);
+        
+        Self {
+            error: "JSON_LIMIT_EXCEEDED".to_string(),
+            message: "JSON document exceeds structural limits".to_string(),
+            validation_errors,
+            kind: ValidationFailure::Malformed,
         }
     }
     
@@ -64,13 +112,29 @@ impl ValidationErrorResponse {
             error: "INVALID_CONTENT_TYPE".to_string(),
             message: "Invalid content type".to_string(),
             validation_errors,
+            kind: ValidationFailure::Malformed,
+        }
+    }
+}
+
+impl ValidationErrorResponse {
+    pub fn kind(&self) -> ValidationFailure {
+        self.kind
+    }
+
+    /// 400 for a [`Malformed`](ValidationFailure::Malformed) body, 422 for a
+    /// [`Semantic`](ValidationFailure::Semantic) failure
+    pub fn status(&self) -> StatusCode {
+        match self.kind {
+            ValidationFailure::Malformed => StatusCode::BAD_REQUEST,
+            ValidationFailure::Semantic => StatusCode::UNPROCESSABLE_ENTITY,
         }
     }
 }
 
 impl IntoResponse for ValidationErrorResponse {
     fn into_response(self) -> Response {
-        (StatusCode::BAD_REQUEST, Json(self)).into_response()
+        (self.status(), Json(self)).into_response()
     }
 }
 
@@ -79,13 +143,70 @@ pub trait ValidatedRequest: for<'de> Deserialize<'de> + Validate + Send + 'stati
     fn validate_request(&self) -> Result<(), ValidationErrors> {
         self.validate()
     }
+
+    /// Password fields by name, held to `max_password_bytes` by [`validate_with`](Self::validate_with)
+    fn password_fields(&self) -> Vec<(&'static str, &str)> {
+        Vec::new()
+    }
+
+    /// [`validate_request`](Self::validate_request) plus the limits the app
+    /// was configured with
+    fn validate_with(&self, settings: &AppSettings) -> Result<(), ValidationErrors> {
+        let mut errors = self.validate_request().err().unwrap_or_default();
+        for (field, password) in self.password_fields() {
+            if let Err(error) = validate_password_size(password, settings.max_password_bytes) {
+                errors.add(field, error);
+            }
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+/// True for `application/json` and structured `+json` types such as
+/// `application/merge-patch+json`, ignoring parameters like `charset`
+pub(crate) fn is_json_content_type(content_type: &str) -> bool {
+    let essence = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    essence == "application/json" || (essence.starts_with("application/") && essence.ends_with("+json"))
 }
 
-/// Middleware function for request validation
+/// Default deepest nesting of objects and arrays in a request body
+pub const DEFAULT_JSON_MAX_DEPTH: usize = 32;
+/// Default longest array in a request body; above the default batch size
+pub const DEFAULT_JSON_MAX_ARRAY_LEN: usize = 1000;
+
+/// Why `value` breaks the limits, or `None` when it is within them.
+///
+/// serde_json already refuses nesting past 128 while parsing, so the
+/// recursion here is bounded.
+fn json_limit_violation(value: &Value, depth: usize, max_depth: usize, max_array_len: usize) -> Option<String> {
+    let within = |child| json_limit_violation(child, depth + 1, max_depth, max_array_len);
+    match value {
+        Value::Array(items) if max_array_len > 0 && items.len() > max_array_len => {
+            Some(format!("Array of {} items exceeds the limit of {}", items.len(), max_array_len))
+        }
+        Value::Array(_) | Value::Object(_) if max_depth > 0 && depth > max_depth => {
+            Some(format!("Nesting exceeds the maximum depth of {}", max_depth))
+        }
+        Value::Array(items) => items.iter().find_map(within),
+        Value::Object(fields) => fields.values().find_map(within),
+        _ => None,
+    }
+}
+
+/// Middleware function for request validation.
+///
+/// POST/PUT/PATCH requests carrying a body must declare a JSON content type
+/// (415 otherwise) and contain syntactically valid JSON (400 otherwise)
+/// within the configured depth and array-length limits (400 with
+/// `JSON_LIMIT_EXCEEDED` otherwise).
 pub async fn validate_json_middleware(
     request: Request,
     next: Next,
 ) -> Result<Response, ValidationErrorResponse> {
+    let settings = match settings::from_extensions(request.extensions()) {
+        Ok(settings) => settings,
+        Err(response) => return Ok(response),
+    };
     let (parts, body) = request.into_parts();
     
     // Only validate POST, PUT, PATCH requests with JSON body
@@ -99,17 +220,6 @@ pub async fn validate_json_middleware(
         return Ok(next.run(request).await);
     }
     
-    // Check content type for JSON requests
-    let content_type = parts.headers
-        .get("content-type")
-        .and_then(|ct| ct.to_str().ok())
-        .unwrap_or("");
-    
-    if !content_type.starts_with("application/json") && !parts.uri.path().contains("/health") {
-        warn!(path = %parts.uri.path(), content_type = %content_type, "Invalid content type for validation");
-        return Err(ValidationErrorResponse::from_content_type_error());
-    }
-    
     // Read body bytes
     let body_bytes = match to_bytes(body, usize::MAX).await {
         Ok(bytes) => bytes,
@@ -119,10 +229,31 @@ pub async fn validate_json_middleware(
         }
     };
     
+    // Bodyless writes (e.g. token refresh) don't need a content type
+    let content_type = parts.headers
+        .get(CONTENT_TYPE)
+        .and_then(|ct| ct.to_str().ok())
+        .unwrap_or("");
+    
+    if !body_bytes.is_empty() && !is_json_content_type(content_type) && !parts.uri.path().contains("/health") {
+        warn!(path = %parts.uri.path(), content_type = %content_type, "Unsupported content type for request body");
+        return Ok((StatusCode::UNSUPPORTED_MEDIA_TYPE, Json(ValidationErrorResponse::from_content_type_error())).into_response());
+    }
+    
+    // Reject invalid byte sequences explicitly so no handler ever sees them
+    if let Err(e) = std::str::from_utf8(&body_bytes) {
+        warn!(path = %parts.uri.path(), error = %e, "Request body is not valid UTF-8");
+        return Err(ValidationErrorResponse::from_json_error(&format!("Request body is not valid UTF-8: {}", e)));
+    }
+    
     // Validate JSON syntax if body is not empty
     if !body_bytes.is_empty() {
         match serde_json::from_slice::<Value>(&body_bytes) {
-            Ok(_) => {
+            Ok(value) => {
+                if let Some(violation) = json_limit_violation(&value, 1, settings.json_max_depth, settings.json_max_array_len) {
+                    warn!(path = %parts.uri.path(), violation = %violation, "JSON body exceeds structural limits");
+                    return Err(ValidationErrorResponse::from_json_limit(&violation));
+                }
                 debug!(path = %parts.uri.path(), "JSON syntax validation passed");
             }
             Err(e) => {
@@ -150,8 +281,17 @@ impl InputSanitizer {
     }
     
     /// Sanitize general text input (remove potential XSS patterns)
+    ///
+    /// Text is first normalized to Unicode NFC, so a composed `é` and an `e`
+    /// followed by a combining accent are stored identically. Leading/trailing
+    /// whitespace is removed and internal runs of whitespace collapse to a
+    /// single space; non-ASCII letters are otherwise left untouched.
     pub fn sanitize_text(text: &str) -> String {
-        text.trim()
+        text.nfc()
+            .collect::<String>()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
             .replace("&", "&amp;")
             .replace("<", "&lt;")
             .replace(">", "&gt;")
@@ -159,6 +299,19 @@ impl InputSanitizer {
             .replace("'", "&#x27;")
     }
     
+    /// Normalize every string value inside a JSON document to Unicode NFC.
+    ///
+    /// Used for free-form documents such as preferences, where escaping
+    /// would change the stored data; object keys are left as sent.
+    pub fn normalize_json_strings(value: &mut Value) {
+        match value {
+            Value::String(text) => *text = text.nfc().collect(),
+            Value::Array(items) => items.iter_mut().for_each(Self::normalize_json_strings),
+            Value::Object(fields) => fields.values_mut().for_each(Self::normalize_json_strings),
+            Value::Null | Value::Bool(_) | Value::Number(_) => {},
+        }
+    }
+    
     /// Sanitize SQL input (basic protection)
     pub fn sanitize_sql_input(input: &str) -> String {
         input.trim()
@@ -223,6 +376,7 @@ mod tests {
         routing::post,
         Router,
     };
+    use crate::config::settings::AppSettings;
     use tower::ServiceExt;
 
     async fn dummy_handler() -> &'static str {
@@ -230,9 +384,14 @@ mod tests {
     }
 
     fn create_test_app() -> Router {
+        test_app_with(AppSettings::default())
+    }
+
+    fn test_app_with(settings: AppSettings) -> Router {
         Router::new()
             .route("/test", post(dummy_handler))
             .layer(middleware::from_fn(validate_json_middleware))
+            .layer(settings.layer())
     }
 
     #[tokio::test]
@@ -265,18 +424,80 @@ mod tests {
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 
-    #[tokio::test]
-    async fn test_missing_content_type() {
-        let app = create_test_app();
-        
+    async fn post_with_content_type(content_type: Option<&str>, body: &'static str) -> StatusCode {
+        let mut request = Request::builder().method("POST").uri("/test");
+        if let Some(content_type) = content_type {
+            request = request.header("content-type", content_type);
+        }
+        let request = request.body(Body::from(body)).unwrap();
+        create_test_app().oneshot(request).await.unwrap().status()
+    }
+
+    async fn post_json(settings: AppSettings, body: String) -> (StatusCode, serde_json::Value) {
         let request = Request::builder()
             .method("POST")
             .uri("/test")
-            .body(Body::from(r#"{"test": "value"}"#))
+            .header("content-type", "application/json")
+            .body(Body::from(body))
             .unwrap();
-        
-        let response = app.oneshot(request).await.unwrap();
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = test_app_with(settings).oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null))
+    }
+
+    #[tokio::test]
+    async fn test_over_deep_json_rejected() {
+        let nested = |depth: usize| format!("{}1{}", "[".repeat(depth), "]".repeat(depth));
+        assert_eq!(post_json(AppSettings::default(), nested(DEFAULT_JSON_MAX_DEPTH)).await.0, StatusCode::OK);
+
+        let (status, body) = post_json(AppSettings::default(), nested(DEFAULT_JSON_MAX_DEPTH + 1)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "JSON_LIMIT_EXCEEDED");
+        assert_eq!(body["validation_errors"]["json"][0], format!("Nesting exceeds the maximum depth of {}", DEFAULT_JSON_MAX_DEPTH));
+
+        let objects = format!("{}1{}", r#"{"a":"#.repeat(DEFAULT_JSON_MAX_DEPTH + 1), "}".repeat(DEFAULT_JSON_MAX_DEPTH + 1));
+        assert_eq!(post_json(AppSettings::default(), objects).await.0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_over_long_array_rejected() {
+        let limited = AppSettings { json_max_array_len: 3, ..AppSettings::default() };
+        let within = post_json(limited.clone(), r#"{"users": [1, 2, 3]}"#.to_string()).await;
+        let (status, body) = post_json(limited, r#"{"users": [1, 2, 3, 4]}"#.to_string()).await;
+        let unlimited = AppSettings { json_max_depth: 0, json_max_array_len: 0, ..AppSettings::default() };
+        let unlimited = post_json(unlimited, format!("[{}]", vec!["1"; 5000].join(","))).await;
+
+        assert_eq!(within.0, StatusCode::OK);
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "JSON_LIMIT_EXCEEDED");
+        assert_eq!(body["validation_errors"]["json"][0], "Array of 4 items exceeds the limit of 3");
+        assert_eq!(unlimited.0, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_missing_content_type() {
+        let status = post_with_content_type(None, r#"{"test": "value"}"#).await;
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn test_wrong_content_type() {
+        assert_eq!(post_with_content_type(Some("text/plain"), r#"{"test": "value"}"#).await, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(post_with_content_type(Some("application/x-www-form-urlencoded"), "test=value").await, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(post_with_content_type(Some("application/jsonx"), r#"{"test": "value"}"#).await, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn test_json_content_type_variants_accepted() {
+        assert_eq!(post_with_content_type(Some("application/json"), r#"{"test": "value"}"#).await, StatusCode::OK);
+        assert_eq!(post_with_content_type(Some("Application/JSON; charset=utf-8"), r#"{"test": "value"}"#).await, StatusCode::OK);
+        assert_eq!(post_with_content_type(Some("application/merge-patch+json"), r#"{"test": "value"}"#).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_empty_body_needs_no_content_type() {
+        assert_eq!(post_with_content_type(None, "").await, StatusCode::OK);
     }
 
     #[tokio::test]
@@ -308,6 +529,54 @@ mod tests {
         assert_eq!(InputSanitizer::sanitize_text(input), expected);
     }
 
+    #[test]
+    fn test_sanitize_text_collapses_whitespace() {
+        assert_eq!(InputSanitizer::sanitize_text("  Head   Chef "), "Head Chef");
+        assert_eq!(InputSanitizer::sanitize_text("Sous\t\n Chef"), "Sous Chef");
+        assert_eq!(InputSanitizer::sanitize_text("  José   Núñez "), "José Núñez");
+        assert_eq!(InputSanitizer::sanitize_text("   "), "");
+    }
+
+    #[test]
+    fn test_sanitize_text_normalizes_to_nfc() {
+        let composed = "Caf\u{e9} Ren\u{e9}";
+        let decomposed = "Cafe\u{301} Rene\u{301}";
+        assert_ne!(composed, decomposed);
+        assert_eq!(InputSanitizer::sanitize_text(decomposed), InputSanitizer::sanitize_text(composed));
+        assert_eq!(InputSanitizer::sanitize_text(decomposed), composed);
+    }
+
+    #[test]
+    fn test_normalize_json_strings() {
+        let mut preferences = serde_json::json!({"cuisine": "cafe\u{301}", "stations": ["saute\u{301}", 3], "nested": {"note": "Cre\u{300}me"}});
+        InputSanitizer::normalize_json_strings(&mut preferences);
+        assert_eq!(preferences, serde_json::json!({"cuisine": "caf\u{e9}", "stations": ["saut\u{e9}", 3], "nested": {"note": "Cr\u{e8}me"}}));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_utf8_body_rejected() {
+        let app = Router::new()
+            .route("/test", post(dummy_handler))
+            .layer(middleware::from_fn(validate_json_middleware))
+            .layer(AppSettings::default().layer());
+        let request = Request::builder()
+            .method("POST")
+            .uri("/test")
+            .header("content-type", "application/json")
+            .body(Body::from(b"{\"full_name\": \"Caf\xe9\"}".to_vec()))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("not valid UTF-8"));
+    }
+
+    #[test]
+    fn test_sanitize_text_collapses_and_escapes() {
+        assert_eq!(InputSanitizer::sanitize_text("  Tom  &   Jerry's  "), "Tom &amp; Jerry&#x27;s");
+    }
+
     #[test]
     fn test_password_strength_validation() {
         // Valid password
@@ -339,4 +608,73 @@ mod tests {
         assert!(response.validation_errors.contains_key("email"));
         assert!(response.validation_errors.contains_key("password"));
     }
+
+    #[test]
+    fn test_status_follows_failure_kind() {
+        let semantic = ValidationErrorResponse::new(ValidationErrors::new());
+        assert_eq!((semantic.kind(), semantic.status()), (ValidationFailure::Semantic, StatusCode::UNPROCESSABLE_ENTITY));
+
+        let malformed = [
+            ValidationErrorResponse::from_json_error("expected value"),
+            ValidationErrorResponse::from_json_limit("too deep"),
+            ValidationErrorResponse::from_content_type_error(),
+        ];
+        for response in malformed {
+            assert_eq!((response.kind(), response.status()), (ValidationFailure::Malformed, StatusCode::BAD_REQUEST), "{}", response.error);
+        }
+
+        // The wire format is unchanged; the kind isn't serialized
+        let body = serde_json::to_value(ValidationErrorResponse::from_content_type_error()).unwrap();
+        assert!(body.get("kind").is_none());
+    }
+
+    #[derive(Debug, Serialize, Deserialize, Validate)]
+    struct BatchItem {
+        #[validate(email(message = "Invalid email format"))]
+        email: String,
+        #[validate(nested)]
+        preferences: Option<BatchPreferences>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, Validate)]
+    struct BatchPreferences {
+        #[validate(length(min = 1, max = 20))]
+        theme: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, Validate)]
+    struct BatchPayload {
+        #[validate(length(min = 1, message = "At least one user is required"))]
+        #[validate(nested)]
+        users: Vec<BatchItem>,
+    }
+
+    #[test]
+    fn test_validation_error_response_indexes_batch_items() {
+        let payload: BatchPayload = serde_json::from_value(serde_json::json!({
+            "users": [
+                { "email": "ok@example.com" },
+                { "email": "ok2@example.com", "preferences": { "theme": "" } },
+                { "email": "not-an-email", "preferences": { "theme": "dark" } }
+            ]
+        }))
+        .unwrap();
+
+        let response = ValidationErrorResponse::new(payload.validate().unwrap_err());
+        let mut paths: Vec<_> = response.validation_errors.keys().cloned().collect();
+        paths.sort();
+        assert_eq!(paths, ["users[1].preferences.theme", "users[2].email"]);
+        assert_eq!(response.validation_errors["users[2].email"], ["Invalid email format"]);
+        assert_eq!(
+            response.validation_errors["users[1].preferences.theme"],
+            ["Invalid value for field 'users[1].preferences.theme'"]
+        );
+    }
+
+    #[test]
+    fn test_validation_error_response_keeps_list_level_errors() {
+        let payload = BatchPayload { users: Vec::new() };
+        let response = ValidationErrorResponse::new(payload.validate().unwrap_err());
+        assert_eq!(response.validation_errors["users"], ["At least one user is required"]);
+    }
 }
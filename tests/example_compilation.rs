@@ -39,6 +39,25 @@ mod example_compilation_tests {
         );
     }
 
+    /// REST-only builds must keep compiling without the default `grpc` feature.
+    ///
+    /// Ignored by default because it rebuilds the crate with a different
+    /// feature set; CI runs it with `cargo test --test example_compilation -- --ignored`.
+    #[test]
+    #[ignore]
+    fn test_builds_without_default_features() {
+        let output = Command::new(env!("CARGO"))
+            .args(["check", "--lib", "--bins", "--no-default-features"])
+            .output()
+            .expect("Failed to run cargo check --no-default-features");
+
+        assert!(
+            output.status.success(),
+            "Crate failed to build without default features:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
     /// Test compilation of standalone example files
     #[test]
     fn test_example_files_compile() {
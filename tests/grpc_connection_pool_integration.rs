@@ -1,3 +1,4 @@
+#![cfg(feature = "grpc")]
 use std::time::Duration;
 
 // Import our connection pool
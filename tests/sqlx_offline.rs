@@ -0,0 +1,49 @@
+//! Offline query cache checks
+//!
+//! The `query!`/`query_as!` macros read `.sqlx/` when no `DATABASE_URL` is
+//! set, so CI and Docker builds depend on that cache being committed and
+//! current.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn test_offline_query_cache_present() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(".sqlx");
+    let entries: Vec<_> = fs::read_dir(&dir)
+        .unwrap_or_else(|e| panic!("{} is missing ({}); run `cargo sqlx prepare`", dir.display(), e))
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    assert!(!entries.is_empty(), "{} has no cached queries; run `cargo sqlx prepare`", dir.display());
+
+    for path in entries {
+        let data: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap())
+            .unwrap_or_else(|e| panic!("{} is not valid JSON: {}", path.display(), e));
+        assert_eq!(data["db_name"], "PostgreSQL", "{}", path.display());
+        assert!(data["query"].is_string(), "{} has no query", path.display());
+    }
+}
+
+/// Checked queries must compile from the cache alone.
+///
+/// Ignored by default because it rebuilds the crate; CI runs it with
+/// `cargo test --test sqlx_offline -- --ignored`.
+#[test]
+#[ignore]
+fn test_queries_compile_offline() {
+    let output = Command::new(env!("CARGO"))
+        .args(["check", "--lib"])
+        .env("SQLX_OFFLINE", "true")
+        .env_remove("DATABASE_URL")
+        .output()
+        .expect("Failed to run cargo check");
+
+    assert!(
+        output.status.success(),
+        "Queries failed to compile against .sqlx; run `cargo sqlx prepare`:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}